@@ -0,0 +1,172 @@
+//! Stripping the ANSI escape sequences out of the input, for `--strip-ansi`.
+//!
+//! The sequences are removed by a small state machine fed the lines one after the other, so a sequence
+//! spanning several lines, like an OSC window title with a newline in it, is still removed whole, newlines
+//! included: the text around it makes a single line. Only the
+//! sequences introduced by ESC are recognized: the 8-bit C1 introducers are also UTF-8 continuation bytes, and
+//! taking them for controls would cut multibyte characters apart.
+
+use std::borrow::Cow;
+
+/// The escape character starting every sequence.
+const ESC: u8 = 0x1b;
+
+/// The bell character, which ends an OSC sequence as well as the string terminator `ESC \` does.
+const BEL: u8 = 0x07;
+
+/// `State` enum is where the state machine is within a sequence.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Outside of any sequence, the bytes are kept.
+    #[default]
+    Ground,
+    /// Right after an ESC.
+    Escape,
+    /// In the intermediate bytes of a two-character sequence such as `ESC ( B`.
+    Intermediate,
+    /// In the parameters of a CSI sequence, `ESC [`, up to its final byte.
+    Csi,
+    /// In a string sequence (OSC `ESC ]`, DCS `ESC P`, SOS, PM or APC), up to its terminator.
+    String,
+    /// Right after an ESC within a string sequence, which is the terminator if a `\` follows.
+    StringEscape,
+}
+
+/// `AnsiStripper` struct removes the escape sequences from the lines of a file.
+///
+/// # Fields
+///
+/// * `state`: Where the state machine is, carried from one line to the next.
+/// * `held`: What was kept of the lines whose newline is within a string sequence, put in front of the next line.
+#[derive(Debug, Default)]
+pub struct AnsiStripper {
+    state: State,
+    held: Vec<u8>,
+}
+
+impl AnsiStripper {
+    /// Forgets a sequence left unfinished at the end of the previous file.
+    pub fn reset(&mut self) {
+        self.state = State::Ground;
+        self.held.clear();
+    }
+
+    /// Returns the line, without its newline, stripped of the escape sequences or the parts of them it contains.
+    ///
+    /// A line without any escape character is returned as it is, without being copied. The other sequences end
+    /// at the newline, but a string sequence goes on over it: `newline` tells whether the line has one, and if
+    /// it is within a string sequence, `None` is returned and what was kept of the line starts the next one.
+    pub fn strip<'l>(&mut self, line: &'l [u8], newline: bool) -> Option<Cow<'l, [u8]>> {
+        if self.state == State::Ground && self.held.is_empty() && !line.contains(&ESC) {
+            return Some(Cow::Borrowed(line));
+        }
+        let mut stripped = std::mem::take(&mut self.held);
+        for &byte in line {
+            if self.feed(byte) {
+                stripped.push(byte);
+            }
+        }
+        match self.state {
+            State::String | State::StringEscape if newline => {
+                self.held = stripped;
+                None
+            }
+            _ => {
+                self.state = State::Ground;
+                Some(Cow::Owned(stripped))
+            }
+        }
+    }
+
+    /// Moves the state machine over a byte, returning `true` if the byte is to be kept, being outside of any
+    /// sequence.
+    pub fn feed(&mut self, byte: u8) -> bool {
+        let (state, keep) = match (self.state, byte) {
+            (State::Ground, ESC) => (State::Escape, false),
+            (State::Ground, _) => (State::Ground, true),
+            (State::Escape, b'[') => (State::Csi, false),
+            (State::Escape, b']' | b'P' | b'X' | b'^' | b'_') => (State::String, false),
+            (State::Escape, 0x20..=0x2f) | (State::Intermediate, 0x20..=0x2f) => (State::Intermediate, false),
+            (State::Escape | State::Intermediate, 0x30..=0x7e) => (State::Ground, false),
+            (State::Csi, 0x20..=0x3f) => (State::Csi, false),
+            (State::Csi, 0x40..=0x7e) => (State::Ground, false),
+            (State::String, BEL) => (State::Ground, false),
+            (State::String, ESC) => (State::StringEscape, false),
+            (State::String, _) => (State::String, false),
+            (State::StringEscape, b'\\') => (State::Ground, false),
+            // an ESC without its terminator starts another sequence
+            (State::StringEscape, _) => {
+                self.state = State::Escape;
+                return self.feed(byte);
+            }
+            // a byte that cannot be part of the sequence ends it and is printed, unless it starts a new one
+            (State::Escape | State::Intermediate | State::Csi, ESC) => (State::Escape, false),
+            (State::Escape | State::Intermediate | State::Csi, _) => (State::Ground, true),
+        };
+        self.state = state;
+        keep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strips the lines one after the other with a single stripper, all of them ending with a newline, and
+    /// returns those printed.
+    fn strip(lines: &[&[u8]]) -> Vec<Vec<u8>> {
+        let mut stripper = AnsiStripper::default();
+        lines.iter().filter_map(|line| stripper.strip(line, true)).map(Cow::into_owned).collect()
+    }
+
+    #[test]
+    fn csi_sequences_are_removed() {
+        assert_eq!(strip(&[b"\x1b[1;31mred\x1b[0m plain"]), [b"red plain"]);
+        assert_eq!(strip(&[b"\x1b[2K\x1b[?25lcursor"]), [b"cursor"]);
+    }
+
+    #[test]
+    fn string_sequences_are_removed_up_to_their_terminator() {
+        assert_eq!(strip(&[b"\x1b]0;title\x07text"]), [b"text"]);
+        assert_eq!(strip(&[b"\x1b]8;;http://a\x1b\\link\x1b]8;;\x1b\\"]), [b"link"]);
+        assert_eq!(strip(&[b"a\x1b]0;multi", b"line\x07b", b"c"]), [&b"ab"[..], b"c"]);
+    }
+
+    #[test]
+    fn two_character_sequences_are_removed() {
+        assert_eq!(strip(&[b"\x1b(Bx\x1b=y\x1b7z"]), [b"xyz"]);
+    }
+
+    #[test]
+    fn multibyte_characters_are_kept() {
+        assert_eq!(strip(&["\x1b[1m\u{e9}t\u{e9} \u{9b}".as_bytes()]), ["\u{e9}t\u{e9} \u{9b}".as_bytes()]);
+    }
+
+    #[test]
+    fn a_byte_outside_of_a_sequence_ends_it() {
+        assert_eq!(strip(&[b"\x1b[1\x01x"]), [b"\x01x"]);
+        assert_eq!(strip(&[b"\x1b[1\x1b[2mx"]), [b"x"]);
+    }
+
+    #[test]
+    fn other_sequences_end_at_the_newline() {
+        assert_eq!(strip(&[b"a\x1b[1", b"b"]), [b"a", b"b"]);
+        assert_eq!(strip(&[b"a\x1b", b"b"]), [b"a", b"b"]);
+    }
+
+    #[test]
+    fn last_line_ends_an_unfinished_sequence() {
+        let mut stripper = AnsiStripper::default();
+        assert_eq!(stripper.strip(b"a\x1b]0;open", true), None);
+        assert_eq!(stripper.strip(b"still in it", false).as_deref(), Some(&b"a"[..]));
+    }
+
+    #[test]
+    fn lines_without_escapes_are_borrowed() {
+        let mut stripper = AnsiStripper::default();
+        assert!(matches!(stripper.strip(b"plain", true), Some(Cow::Borrowed(b"plain"))));
+        assert_eq!(stripper.strip(b"x\x1b]0;open", true), None);
+        stripper.reset();
+        assert!(matches!(stripper.strip(b"next file", true), Some(Cow::Borrowed(b"next file"))));
+    }
+}
@@ -0,0 +1,299 @@
+//! Printing a single member of a tar or zip archive, as in `minicat logs.tar.gz:var/log/syslog` or with
+//! `--archive-member`, and listing the members of archives for `--list-members`.
+//!
+//! A tar archive is read front to back through the decompression layer, and the headers are skipped over until
+//! the member shows up, whose data is then streamed without anything being extracted to the disk. Only the
+//! headers are parsed, with the `tar` crate, so the reader can be handed over as it is once the member is found.
+//!
+//! A zip archive lists its members at its end, so it has to be a file, the standard input cannot be one. The
+//! `zip` crate finds the member, whose data is then read straight from the file and decompressed as it is
+//! streamed.
+
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::bufread::DeflateDecoder;
+use tar::{EntryType, Header};
+use zip::{CompressionMethod, ZipArchive};
+
+use crate::io_backend::InputFile;
+
+/// The size of the blocks of a tar archive, every header and the data of every member taking whole blocks.
+const BLOCK: u64 = 512;
+
+/// The extensions of the names of archives, compressed or not.
+const EXTENSIONS: [&str; 10] =
+    [".tar", ".tar.gz", ".tgz", ".tar.zst", ".tzst", ".tar.xz", ".txz", ".tar.bz2", ".tbz2", ".zip"];
+
+/// Splits an input given as `ARCHIVE:MEMBER` into the path of the archive and the name of the member.
+///
+/// The input is only split if it is not the name of an existing file, and if what comes before the colon is
+/// named like an archive.
+///
+/// ## Returns
+/// The path of the archive and the member, or the input itself and `None` if it does not name a member.
+pub fn split(filename: &str) -> (&str, Option<&str>) {
+    if Path::new(filename).exists() {
+        return (filename, None);
+    }
+    let archive = filename.match_indices(':').map(|(index, _)| &filename[..index]).find(|archive| {
+        let lowercase = archive.to_ascii_lowercase();
+        EXTENSIONS.iter().any(|extension| lowercase.ends_with(extension))
+    });
+    match archive {
+        Some(archive) => (archive, Some(&filename[archive.len() + 1..])),
+        None => (filename, None),
+    }
+}
+
+/// Returns `true` if the file is named like a zip archive, any other archive being a tar archive.
+pub fn is_zip(filename: &str) -> bool {
+    Path::new(filename).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// Walks the members of a tar archive, handing the name, the type and the size of each of them to `visit`,
+/// with `reader` positioned at the start of its data. The walk stops at the first member `visit` returns `true`
+/// for, otherwise its data is skipped.
+///
+/// # Errors
+/// The function will return an error if reading the archive fails, or if it is not a tar archive.
+fn walk_tar(reader: &mut dyn BufRead, mut visit: impl FnMut(&[u8], EntryType, u64) -> bool) -> io::Result<()> {
+    // the long names of the GNU and PAX formats come in an entry of their own, before the member they belong to
+    let mut long_name: Option<Vec<u8>> = None;
+    loop {
+        let mut block = [0; BLOCK as usize];
+        match reader.read_exact(&mut block) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            result => result?,
+        }
+        // a block of zeros ends the archive
+        if block.iter().all(|&byte| byte == 0) {
+            return Ok(());
+        }
+        let header = Header::from_byte_slice(&block);
+        // the checksum field of data that is not a header may not even be a number
+        if header.cksum().ok() != Some(checksum(&block)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a tar archive"));
+        }
+        let size = header.entry_size()?;
+        let padded = size.div_ceil(BLOCK) * BLOCK;
+        let entry_type = header.entry_type();
+        if entry_type.is_gnu_longname() || entry_type.is_pax_local_extensions() {
+            let mut data = Vec::new();
+            (&mut *reader).take(padded).read_to_end(&mut data)?;
+            data.truncate(size as usize);
+            long_name = if entry_type.is_gnu_longname() {
+                Some(data.split(|&byte| byte == 0).next().unwrap_or_default().to_vec())
+            } else {
+                pax_path(&data).or(long_name)
+            };
+            continue;
+        }
+        let name = long_name.take().unwrap_or_else(|| header.path_bytes().into_owned());
+        if visit(&name, entry_type, size) {
+            return Ok(());
+        }
+        io::copy(&mut (&mut *reader).take(padded), &mut io::sink())?;
+    }
+}
+
+/// Reads the tar archive of `reader` up to the given member.
+///
+/// ## Returns
+/// A reader giving the content of the member.
+///
+/// # Errors
+/// The function will return an error of kind `io::ErrorKind::NotFound` if the archive has no such member,
+/// `io::ErrorKind::InvalidInput` if the member is not a regular file, and any other error if reading the archive
+/// fails or if it is not a tar archive.
+pub fn open_member(mut reader: Box<dyn BufRead>, member: &str) -> io::Result<Box<dyn BufRead>> {
+    let wanted = normalize(member.as_bytes());
+    let mut found = None;
+    walk_tar(&mut reader, |name, entry_type, size| {
+        if normalize(name) == wanted {
+            found = Some((entry_type, size));
+        }
+        found.is_some()
+    })?;
+    match found {
+        Some((entry_type, size)) if entry_type.is_file() => Ok(Box::new(reader.take(size))),
+        Some(_) => Err(not_a_file(member)),
+        None => Err(not_found(member)),
+    }
+}
+
+/// Reads the names of the members of the tar archive of `reader`.
+///
+/// ## Returns
+/// A reader giving the names, one per line.
+///
+/// # Errors
+/// The function will return an error if reading the archive fails, or if it is not a tar archive.
+pub fn list_members(mut reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    let mut names = Vec::new();
+    walk_tar(&mut reader, |name, _, _| {
+        names.extend_from_slice(name);
+        names.push(b'\n');
+        false
+    })?;
+    Ok(Box::new(io::Cursor::new(names)))
+}
+
+/// Opens a member of a zip archive, or lists its members if `member` is `None`.
+///
+/// Stored and deflated members are always supported, and so are the members compressed with zstd or bzip2
+/// with the `zstd` and `bzip2` features.
+///
+/// ## Returns
+/// A reader giving the content of the member, or the names of the members, one per line.
+///
+/// # Errors
+/// The function will return an error of kind `io::ErrorKind::NotFound` if the archive has no such member,
+/// `io::ErrorKind::InvalidInput` if the member is not a regular file, `io::ErrorKind::Unsupported` if it is
+/// encrypted or compressed with another method, and any other error if reading the archive fails or if it is
+/// not a zip archive.
+pub fn open_zip(mut input: InputFile, member: Option<&str>, buffer_size: usize) -> io::Result<Box<dyn BufRead>> {
+    let mut archive = ZipArchive::new(input.file_mut())?;
+    let Some(member) = member else {
+        let mut names = Vec::new();
+        for index in 0..archive.len() {
+            names.extend_from_slice(archive.by_index_raw(index)?.name().as_bytes());
+            names.push(b'\n');
+        }
+        return Ok(Box::new(io::Cursor::new(names)));
+    };
+    let wanted = normalize(member.as_bytes());
+    let index = (0..archive.len())
+        .find(|&index| archive.name_for_index(index).is_some_and(|name| normalize(name.as_bytes()) == wanted))
+        .ok_or_else(|| not_found(member))?;
+    let entry = archive.by_index_raw(index)?;
+    if entry.is_dir() {
+        return Err(not_a_file(member));
+    }
+    if entry.encrypted() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, format!("{}: encrypted member", member)));
+    }
+    let (method, start, size) = (entry.compression(), entry.data_start(), entry.compressed_size());
+    drop(entry);
+    drop(archive);
+    input.file_mut().seek(SeekFrom::Start(start))?;
+    let data = BufReader::with_capacity(buffer_size, input.take(size));
+    let reader: Box<dyn Read> = match method {
+        CompressionMethod::Stored => Box::new(data),
+        CompressionMethod::Deflated => Box::new(DeflateDecoder::new(data)),
+        #[cfg(feature = "zstd")]
+        CompressionMethod::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(data)?),
+        #[cfg(feature = "bzip2")]
+        CompressionMethod::Bzip2 => Box::new(bzip2::bufread::BzDecoder::new(data)),
+        method => {
+            let message = format!("{}: unsupported compression method {:?}", member, method);
+            return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+        }
+    };
+    Ok(Box::new(BufReader::with_capacity(buffer_size, reader)))
+}
+
+fn not_found(member: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{}: no such member in the archive", member))
+}
+
+fn not_a_file(member: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{}: not a regular file in the archive", member))
+}
+
+/// Returns the sum of the bytes of a header, its checksum field counting as spaces, which is what the checksum
+/// of a valid header is.
+fn checksum(block: &[u8]) -> u32 {
+    block
+        .iter()
+        .enumerate()
+        .map(|(index, &byte)| if (148..156).contains(&index) { 32 } else { u32::from(byte) })
+        .sum()
+}
+
+/// Returns the `path` of the records of a PAX extended header, `LENGTH path=VALUE\n`.
+fn pax_path(data: &[u8]) -> Option<Vec<u8>> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&byte| byte == b' ')?;
+        let length: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..length)?;
+        if let Some(value) = record.strip_prefix(b"path=") {
+            return Some(value.strip_suffix(b"\n").unwrap_or(value).to_vec());
+        }
+        rest = &rest[length..];
+    }
+    None
+}
+
+/// Returns the name of a member without a leading `./` nor a trailing `/`, so `./a.txt` and `a.txt` are the same.
+fn normalize(name: &[u8]) -> &[u8] {
+    let name = name.strip_prefix(b"./").unwrap_or(name);
+    name.strip_suffix(b"/").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a tar archive of a folder, a short member and one whose name is too long for the header.
+    fn archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut folder = Header::new_gnu();
+        folder.set_entry_type(EntryType::Directory);
+        folder.set_size(0);
+        builder.append_data(&mut folder, "dir/", io::empty()).unwrap();
+        let mut header = Header::new_gnu();
+        header.set_size(7);
+        builder.append_data(&mut header, "./dir/in.txt", &b"inside\n"[..]).unwrap();
+        let mut header = Header::new_gnu();
+        header.set_size(5);
+        builder.append_data(&mut header, format!("{}.txt", "long".repeat(40)), &b"long\n"[..]).unwrap();
+        builder.into_inner().unwrap()
+    }
+
+    /// Reads a whole reader into a string.
+    fn read(mut reader: Box<dyn BufRead>) -> String {
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        content
+    }
+
+    #[test]
+    fn inputs_are_split_after_the_archive_name() {
+        assert_eq!(split("logs.tar.gz:var/log/syslog"), ("logs.tar.gz", Some("var/log/syslog")));
+        assert_eq!(split("a.ZIP:b:c"), ("a.ZIP", Some("b:c")));
+        assert_eq!(split("notes.txt:12"), ("notes.txt:12", None));
+        assert!(is_zip("a.zip") && !is_zip("a.tar"));
+    }
+
+    #[test]
+    fn members_are_found_by_their_normalized_name() {
+        assert_eq!(read(open_member(Box::new(io::Cursor::new(archive())), "dir/in.txt").unwrap()), "inside\n");
+        let long = format!("{}.txt", "long".repeat(40));
+        assert_eq!(read(open_member(Box::new(io::Cursor::new(archive())), &long).unwrap()), "long\n");
+    }
+
+    #[test]
+    fn missing_members_and_folders_are_errors() {
+        let error = open_member(Box::new(io::Cursor::new(archive())), "nope").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = open_member(Box::new(io::Cursor::new(archive())), "dir").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = open_member(Box::new(io::Cursor::new(vec![b'x'; 1024])), "a").err().unwrap();
+        assert_eq!(error.to_string(), "not a tar archive");
+    }
+
+    #[test]
+    fn members_are_listed_in_order() {
+        let listed = read(list_members(Box::new(io::Cursor::new(archive()))).unwrap());
+        assert_eq!(listed, format!("dir/\ndir/in.txt\n{}.txt\n", "long".repeat(40)));
+    }
+
+    #[test]
+    fn pax_records_give_the_path() {
+        assert_eq!(pax_path(b"20 mtime=1700000000\n14 path=a/b.c\n"), Some(b"a/b.c".to_vec()));
+        assert_eq!(pax_path(b"12 size=100\n"), None);
+        assert_eq!(pax_path(b"99 path=x\n"), None);
+    }
+}
@@ -0,0 +1,264 @@
+//! Encoding the files to base64 and decoding them from it, for `--base64-encode` and `--base64-decode`.
+//!
+//! Both directions are `Renderer`s, so the files are streamed through them chunk by chunk. The encoded output
+//! is wrapped in lines of 76 characters like the `base64` tool does, and the decoder skips the line breaks and
+//! the other whitespace of wrapped input. Malformed data is reported with its offset in the file.
+
+use crate::dump::Renderer;
+
+/// The alphabet of the standard base64 encoding, RFC 4648.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The number of encoded characters on every line of the output.
+const LINE_WIDTH: usize = 76;
+
+/// `Base64Mode` enum is the direction of the conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Mode {
+    /// The files are encoded to base64.
+    Encode,
+    /// The files are decoded from base64.
+    Decode,
+}
+
+impl Base64Mode {
+    /// Returns a new `Renderer` converting the files in this direction.
+    pub fn renderer(self) -> Box<dyn Renderer> {
+        match self {
+            Base64Mode::Encode => Box::new(Encoder::default()),
+            Base64Mode::Decode => Box::new(Decoder::default()),
+        }
+    }
+}
+
+/// `Encoder` struct encodes the data to base64, three bytes at a time.
+///
+/// # Fields
+///
+/// * `pending`: The bytes left over from the previous chunk, fewer than three.
+/// * `column`: The number of characters on the current line of the output.
+#[derive(Debug, Default)]
+struct Encoder {
+    pending: Vec<u8>,
+    column: usize,
+}
+
+impl Encoder {
+    /// Writes the characters of a group of one to three bytes, padded with `=` when there are fewer than three.
+    fn write_group(&mut self, bytes: &[u8], output: &mut Vec<u8>) {
+        let value = bytes
+            .iter()
+            .enumerate()
+            .fold(0u32, |value, (index, &byte)| value | (u32::from(byte) << (16 - 8 * index)));
+        for index in 0..4 {
+            let character = if index <= bytes.len() {
+                ALPHABET[((value >> (18 - 6 * index)) & 0x3f) as usize]
+            } else {
+                b'='
+            };
+            output.push(character);
+            self.column += 1;
+            if self.column == LINE_WIDTH {
+                output.push(b'\n');
+                self.column = 0;
+            }
+        }
+    }
+}
+
+impl Renderer for Encoder {
+    fn render(&mut self, mut data: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+        if !self.pending.is_empty() {
+            let length = (3 - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if self.pending.len() < 3 {
+                return Ok(());
+            }
+            let pending = std::mem::take(&mut self.pending);
+            self.write_group(&pending, output);
+        }
+        let mut groups = data.chunks_exact(3);
+        for group in groups.by_ref() {
+            self.write_group(group, output);
+        }
+        self.pending.extend_from_slice(groups.remainder());
+
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), String> {
+        if !self.pending.is_empty() {
+            let pending = std::mem::take(&mut self.pending);
+            self.write_group(&pending, output);
+        }
+        if self.column > 0 {
+            output.push(b'\n');
+            self.column = 0;
+        }
+
+        Ok(())
+    }
+}
+
+/// `Decoder` struct decodes base64 data, four characters at a time.
+///
+/// # Fields
+///
+/// * `quad`: The values of the characters of the current group.
+/// * `length`: The number of characters in `quad`.
+/// * `padding`: The number of `=` the current group ends with.
+/// * `ended`: Whether a padded group has ended the data, after which only whitespace may come in this file.
+/// * `offset`: The offset in the current file of the next byte, used in error messages.
+#[derive(Debug, Default)]
+struct Decoder {
+    quad: [u8; 4],
+    length: usize,
+    padding: usize,
+    ended: bool,
+    offset: u64,
+}
+
+impl Decoder {
+    /// Writes the bytes of a complete group.
+    fn write_group(&mut self, output: &mut Vec<u8>) {
+        let value = self.quad.iter().fold(0u32, |value, &sextet| (value << 6) | u32::from(sextet));
+        let bytes = [(value >> 16) as u8, (value >> 8) as u8, value as u8];
+        output.extend_from_slice(&bytes[..3 - self.padding]);
+        self.ended = self.padding > 0;
+        self.length = 0;
+        self.padding = 0;
+    }
+
+    /// Decodes a chunk of the data into `output`, stopping at the first error.
+    fn decode(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+        for &byte in data {
+            let offset = self.offset;
+            self.offset += 1;
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+            if self.ended {
+                return Err(format!("invalid base64 data at byte {}: data after the padding", offset));
+            }
+            let sextet = match byte {
+                b'A'..=b'Z' => byte - b'A',
+                b'a'..=b'z' => byte - b'a' + 26,
+                b'0'..=b'9' => byte - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                // padding is only allowed as the last one or two characters of a group
+                b'=' if self.length >= 2 => {
+                    self.padding += 1;
+                    0
+                }
+                _ => return Err(format!("invalid base64 character {:?} at byte {}", byte as char, offset)),
+            };
+            if self.padding > 0 && byte != b'=' {
+                return Err(format!("invalid base64 data at byte {}: data after the padding", offset));
+            }
+            self.quad[self.length] = sextet;
+            self.length += 1;
+            if self.length == 4 {
+                self.write_group(output);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Renderer for Decoder {
+    fn start_file(&mut self) {
+        // a new file may start a new stream, but a group cannot be split between two files
+        self.ended = false;
+        self.offset = 0;
+    }
+
+    fn render(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+        let decoded = self.decode(data, output);
+        if decoded.is_err() {
+            // the rest of the file is not read, so its unfinished group is not reported again by `finish`
+            self.length = 0;
+            self.padding = 0;
+        }
+        decoded
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) -> Result<(), String> {
+        if self.length > 0 {
+            return Err(format!("truncated base64 data at byte {}", self.offset));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the chunks of a single file through a renderer of `mode`, and returns its output or its error.
+    fn convert(mode: Base64Mode, chunks: &[&[u8]]) -> Result<Vec<u8>, String> {
+        let mut renderer = mode.renderer();
+        let mut output = Vec::new();
+        renderer.start_file();
+        for chunk in chunks {
+            renderer.render(chunk, &mut output)?;
+        }
+        renderer.finish(&mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn encoder_pads_the_last_group() {
+        assert_eq!(convert(Base64Mode::Encode, &[b"Man"]), Ok(b"TWFu\n".to_vec()));
+        assert_eq!(convert(Base64Mode::Encode, &[b"Ma"]), Ok(b"TWE=\n".to_vec()));
+        assert_eq!(convert(Base64Mode::Encode, &[b"M", b"a"]), Ok(b"TWE=\n".to_vec()));
+        assert_eq!(convert(Base64Mode::Encode, &[b""]), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn encoder_wraps_the_lines() {
+        let encoded = convert(Base64Mode::Encode, &[&[0; 60]]).unwrap();
+        let lines: Vec<_> = encoded.split(|&byte| byte == b'\n').map(<[u8]>::len).collect();
+        assert_eq!(lines, [LINE_WIDTH, 4, 0]);
+    }
+
+    #[test]
+    fn decoder_undoes_the_encoder() {
+        let data: Vec<u8> = (0..=255).collect();
+        let encoded = convert(Base64Mode::Encode, &[&data]).unwrap();
+        let (first, second) = encoded.split_at(101);
+        assert_eq!(convert(Base64Mode::Decode, &[first, second]), Ok(data));
+        assert_eq!(convert(Base64Mode::Decode, &[b"TW\r\nE= \n"]), Ok(b"Ma".to_vec()));
+    }
+
+    #[test]
+    fn decoder_reports_the_offsets_in_the_file() {
+        let error = convert(Base64Mode::Decode, &[b"TWFu\n", b"TW*u"]).unwrap_err();
+        assert_eq!(error, "invalid base64 character '*' at byte 7");
+        let error = convert(Base64Mode::Decode, &[b"TWE=TWFu"]).unwrap_err();
+        assert_eq!(error, "invalid base64 data at byte 4: data after the padding");
+        let error = convert(Base64Mode::Decode, &[b"TW=u"]).unwrap_err();
+        assert_eq!(error, "invalid base64 data at byte 3: data after the padding");
+        assert_eq!(convert(Base64Mode::Decode, &[b"TWF"]).unwrap_err(), "truncated base64 data at byte 3");
+    }
+
+    #[test]
+    fn decoder_counts_the_offsets_from_each_file() {
+        let mut decoder = Base64Mode::Decode.renderer();
+        let mut output = Vec::new();
+        decoder.render(b"TWE=\n", &mut output).unwrap();
+        decoder.start_file();
+        assert_eq!(decoder.render(b"T!", &mut output).unwrap_err(), "invalid base64 character '!' at byte 1");
+    }
+
+    #[test]
+    fn decoder_forgets_the_group_of_a_rejected_file() {
+        let mut decoder = Base64Mode::Decode.renderer();
+        let mut output = Vec::new();
+        assert!(decoder.render(b"TWE!", &mut output).is_err());
+        assert_eq!(decoder.finish(&mut output), Ok(()));
+    }
+}
@@ -0,0 +1,74 @@
+//! Telling the binary files from the text files, for `--binary` in recursive mode, and for the output going to
+//! a terminal.
+//!
+//! A file is taken for binary if a NUL byte shows up in its first 8 KiB, the heuristic of git, grep and
+//! ripgrep: text files, whatever their encoding but UTF-16, do not have any. A tree walked with `--recursive`
+//! often holds object files, images or archives next to the sources, which are skipped by default, and can
+//! also be printed as they are or dumped in hexadecimal. The binary files printed on a terminal are skipped
+//! with a warning unless `--force` is given, see the `terminal` module.
+
+use std::fs::{self, File};
+use std::io::Read;
+
+/// The number of bytes looked at at the start of a file.
+pub const PEEK: usize = 8192;
+
+/// `BinaryMode` enum decides what happens to the binary files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryMode {
+    /// The binary files are printed like any other file, without looking at them.
+    #[default]
+    Raw,
+    /// The binary files are skipped, with a notice on the standard error.
+    Skip,
+    /// The binary files are dumped in hexadecimal like `xxd`, the text files being printed as usual.
+    Hex,
+    /// The binary files are skipped with a warning telling that `--force` prints them, as they would be printed
+    /// as they are on a terminal.
+    Warn,
+}
+
+/// Returns `true` if data read from the start of a file looks binary.
+pub fn is_binary_data(data: &[u8]) -> bool {
+    data[..data.len().min(PEEK)].contains(&0)
+}
+
+/// Returns `true` if the file looks binary, from its first bytes. A file that cannot be read is not binary, the
+/// error being reported when it is printed, and neither is anything but a regular file, which may block.
+pub fn is_binary(filename: &str) -> bool {
+    if !fs::metadata(filename).is_ok_and(|metadata| metadata.is_file()) {
+        return false;
+    }
+    let mut data = Vec::with_capacity(PEEK);
+    let read = File::open(filename).and_then(|file| file.take(PEEK as u64).read_to_end(&mut data));
+    read.is_ok() && is_binary_data(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn nul_bytes_in_the_peek_make_the_data_binary() {
+        assert!(!is_binary_data(b"text\n"));
+        assert!(is_binary_data(b"te\0xt"));
+        let mut late = vec![b'a'; PEEK];
+        late.push(0);
+        assert!(!is_binary_data(&late));
+    }
+
+    #[test]
+    fn only_readable_regular_files_are_binary() {
+        let directory = tempfile::tempdir().unwrap();
+        let binary = directory.path().join("binary");
+        let text = directory.path().join("text");
+        fs::write(&binary, b"\x7fELF\0\0").unwrap();
+        fs::write(&text, b"text\n").unwrap();
+        assert!(is_binary(binary.to_str().unwrap()));
+        assert!(!is_binary(text.to_str().unwrap()));
+        assert!(!is_binary(directory.path().join("missing").to_str().unwrap()));
+        assert!(!is_binary(directory.path().to_str().unwrap()));
+    }
+}
@@ -0,0 +1,95 @@
+//! Stripping the UTF-8 and UTF-16 byte order marks the inputs start with, unless `--keep-bom` is given.
+//!
+//! Editors on Windows often start text files with a byte order mark. Once the files are concatenated, the marks
+//! of all but the first file end up in the middle of the output, where they are stray invisible characters, so
+//! they are taken off the start of the inputs. The mark of the first file is kept when it starts the output, so
+//! that a plain copy of a file is the same byte for byte. With `--from-encoding`, the decoder takes the mark of
+//! the encoding off itself.
+//!
+//! A mark is only looked for at the very start of an input, so not with `--skip-bytes`, and not in the dumps of
+//! `--hex`, `--dump` or `--base64-encode`, which show the bytes as they are. A file taken for binary, by
+//! `--binary` or because the bytes after the mark look binary, is left as it is too. The NUL bytes UTF-16 is full
+//! of are not binary there: only a NUL code unit is. On a pipe, the mark is only
+//! seen if it comes in the first read, as it does unless the writer sends it a byte at a time.
+
+use std::fs::File;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use crate::binary;
+
+/// The byte order mark of UTF-8.
+const UTF8: &[u8] = b"\xef\xbb\xbf";
+
+/// The byte order mark of UTF-16 little endian.
+const UTF16_LE: &[u8] = b"\xff\xfe";
+
+/// The byte order mark of UTF-16 big endian.
+const UTF16_BE: &[u8] = b"\xfe\xff";
+
+/// Returns the length of the byte order mark `data` starts with, 0 if it does not start with one or if what
+/// follows the mark looks binary.
+pub fn length(data: &[u8]) -> usize {
+    if let Some(rest) = data.strip_prefix(UTF8) {
+        return if binary::is_binary_data(rest) { 0 } else { UTF8.len() };
+    }
+    match data.strip_prefix(UTF16_LE).or_else(|| data.strip_prefix(UTF16_BE)) {
+        Some(rest) if !is_binary_utf16(rest) => UTF16_LE.len(),
+        _ => 0,
+    }
+}
+
+/// Returns `true` if UTF-16 data looks binary, holding a NUL code unit in the bytes `binary` looks at.
+fn is_binary_utf16(data: &[u8]) -> bool {
+    data[..data.len().min(binary::PEEK)].chunks_exact(2).any(|unit| unit == [0, 0])
+}
+
+/// Takes the byte order mark off the start of `reader`, if there is one.
+///
+/// # Errors
+/// The function will return an error if reading from `reader` fails.
+pub fn skip(reader: &mut dyn BufRead) -> io::Result<()> {
+    let mark = length(reader.fill_buf()?);
+    reader.consume(mark);
+    Ok(())
+}
+
+/// Moves the offset of a file past the byte order mark it starts with, or back to its start if there is none.
+///
+/// # Errors
+/// The function will return an error if reading or seeking the file fails.
+pub fn skip_file(file: &mut File) -> io::Result<()> {
+    let mut start = Vec::new();
+    file.by_ref().take((UTF8.len() + binary::PEEK) as u64).read_to_end(&mut start)?;
+    file.seek(SeekFrom::Start(length(&start) as u64))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_utf8_and_utf16_marks_are_stripped() {
+        assert_eq!(length(b"\xef\xbb\xbftext"), 3);
+        assert_eq!(length(b"\xff\xfet\x00"), 2);
+        assert_eq!(length(b"\xfe\xff\x00t"), 2);
+        assert_eq!(length(b"\xef\xbb"), 0);
+        assert_eq!(length(b"text"), 0);
+    }
+
+    #[test]
+    fn the_mark_of_binary_data_is_kept() {
+        assert_eq!(length(b"\xef\xbb\xbf\x00\x01"), 0);
+        assert_eq!(length(b"\xef\xbb\xbf"), 3);
+        assert_eq!(length(b"\xff\xfet\x00\x00\x00"), 0);
+        assert_eq!(length(b"\xfe\xff\x00\x00"), 0);
+        assert_eq!(length(b"\xff\xfe"), 2);
+    }
+
+    #[test]
+    fn skip_consumes_the_mark() {
+        let mut reader: &[u8] = b"\xef\xbb\xbfline\n";
+        skip(&mut reader).unwrap();
+        assert_eq!(reader, b"line\n");
+    }
+}
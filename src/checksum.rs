@@ -0,0 +1,502 @@
+//! Computing checksums of the output while it is written, for `--checksum`.
+//!
+//! The output goes through a `Checksummed` writer, which feeds every byte written to the hashers of the file
+//! being printed and to the ones of the whole output. Once everything has been printed, the digests are
+//! reported on the standard error in the tagged format of `sha256sum --tag`, `SHA256 (file) = digest`, so a
+//! large transfer can be verified without reading the files a second time. The hashes need the `checksum`
+//! feature.
+//!
+//! With `--verify`, the checksums of every file are also checked against the ones listed in a sums file, as
+//! written by `sha256sum` and the like, in either the plain or the tagged format. The files are then printed
+//! exactly as they are, so that what is hashed is their content: the options changing it are refused, and the
+//! byte order marks, the colors and the automatic decompression are left out.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "checksum")]
+use clap::{Arg, ArgAction};
+#[cfg(feature = "checksum")]
+use digest::DynDigest;
+
+use crate::error::MinicatError;
+
+/// `Algorithm` enum lists the hash functions the checksums can be computed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(feature = "checksum"), allow(dead_code))]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+#[cfg_attr(not(feature = "checksum"), allow(dead_code))]
+impl Algorithm {
+    /// Returns the name of the algorithm, as in the tagged format.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha512 => "SHA512",
+        }
+    }
+
+    /// Parses the name of an algorithm, in any case and with or without a dash, like `sha256` or `SHA-256`.
+    ///
+    /// # Errors
+    /// The function will return an error if the algorithm is not known.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(Algorithm::Md5),
+            "sha1" => Ok(Algorithm::Sha1),
+            "sha256" => Ok(Algorithm::Sha256),
+            "sha512" => Ok(Algorithm::Sha512),
+            _ => Err(format!("unknown checksum algorithm `{}`, expected md5, sha1, sha256 or sha512", name)),
+        }
+    }
+
+    /// Returns the number of bytes of the digests of the algorithm.
+    fn length(self) -> usize {
+        match self {
+            Algorithm::Md5 => 16,
+            Algorithm::Sha1 => 20,
+            Algorithm::Sha256 => 32,
+            Algorithm::Sha512 => 64,
+        }
+    }
+
+    /// Returns the algorithm whose digests are written with `length` hexadecimal digits, since the plain format
+    /// of the sums files does not name it.
+    fn from_hex_length(length: usize) -> Option<Self> {
+        [Algorithm::Md5, Algorithm::Sha1, Algorithm::Sha256, Algorithm::Sha512]
+            .into_iter()
+            .find(|algorithm| algorithm.length() * 2 == length)
+    }
+
+    /// Returns a new hasher of the algorithm.
+    #[cfg(feature = "checksum")]
+    fn hasher(self) -> Box<dyn DynDigest> {
+        match self {
+            Algorithm::Md5 => Box::new(md5::Md5::default()),
+            Algorithm::Sha1 => Box::new(sha1::Sha1::default()),
+            Algorithm::Sha256 => Box::new(sha2::Sha256::default()),
+            Algorithm::Sha512 => Box::new(sha2::Sha512::default()),
+        }
+    }
+}
+
+/// `Digest` struct is a checksum computed over the output.
+///
+/// # Fields
+///
+/// * `algorithm`: The `Algorithm` it was computed with.
+/// * `name`: The name of the file it is the checksum of, `None` for the whole output.
+/// * `hex`: The checksum, in lowercase hexadecimal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub algorithm: Algorithm,
+    pub name: Option<String>,
+    pub hex: String,
+}
+
+/// `Hashers` struct feeds the same bytes to a hasher of every algorithm.
+#[cfg(feature = "checksum")]
+struct Hashers(Vec<(Algorithm, Box<dyn DynDigest>)>);
+
+#[cfg(feature = "checksum")]
+impl Hashers {
+    fn new(algorithms: &[Algorithm]) -> Self {
+        Hashers(algorithms.iter().map(|&algorithm| (algorithm, algorithm.hasher())).collect())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for (_, hasher) in &mut self.0 {
+            hasher.update(data);
+        }
+    }
+
+    /// Appends the digests of the bytes fed so far to `digests`, and starts over.
+    fn finalize_into(&mut self, name: Option<&str>, digests: &mut Vec<Digest>) {
+        for (algorithm, hasher) in &mut self.0 {
+            let hex = hasher.finalize_reset().iter().map(|byte| format!("{:02x}", byte)).collect();
+            digests.push(Digest { algorithm: *algorithm, name: name.map(str::to_string), hex });
+        }
+    }
+}
+
+/// `Checksummed` struct is a writer computing the checksums of what is written through it.
+///
+/// Without the `checksum` feature, or when no algorithm is given, it simply passes the bytes through.
+///
+/// # Fields
+///
+/// * `inner`: The writer the output goes to.
+/// * `file`: The hashers of the file being printed, `None` between two files and when nothing is hashed.
+/// * `total`: The hashers of the whole output, `None` when nothing is hashed.
+/// * `digests`: The checksums of the files printed so far.
+pub struct Checksummed<W> {
+    inner: W,
+    #[cfg(feature = "checksum")]
+    algorithms: Vec<Algorithm>,
+    #[cfg(feature = "checksum")]
+    file: Option<Hashers>,
+    #[cfg(feature = "checksum")]
+    total: Option<Hashers>,
+    digests: Vec<Digest>,
+}
+
+impl<W: Write> Checksummed<W> {
+    /// Wraps `inner`, computing the checksums of the given algorithms, if any.
+    #[cfg(feature = "checksum")]
+    pub fn new(inner: W, algorithms: &[Algorithm]) -> Self {
+        let total = (!algorithms.is_empty()).then(|| Hashers::new(algorithms));
+        Checksummed { inner, algorithms: algorithms.to_vec(), file: None, total, digests: Vec::new() }
+    }
+
+    /// Wraps `inner`, nothing is hashed without the `checksum` feature.
+    #[cfg(not(feature = "checksum"))]
+    pub fn new(inner: W, _algorithms: &[Algorithm]) -> Self {
+        Checksummed { inner, digests: Vec::new() }
+    }
+
+    /// Returns a mutable reference to the writer the output goes to.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns `true` if checksums are computed, in which case every byte has to be written through this writer.
+    #[cfg(feature = "checksum")]
+    pub fn is_active(&self) -> bool {
+        self.total.is_some()
+    }
+
+    /// Returns `true` if checksums are computed, which never happens without the `checksum` feature.
+    #[cfg(not(feature = "checksum"))]
+    pub fn is_active(&self) -> bool {
+        false
+    }
+
+    /// Starts the checksums of a file, the bytes written from now on belong to it.
+    pub fn start_file(&mut self) {
+        #[cfg(feature = "checksum")]
+        if self.is_active() {
+            self.file = Some(Hashers::new(&self.algorithms));
+        }
+    }
+
+    /// Ends the checksums of the file started last, and keeps its digests.
+    ///
+    /// ## Returns
+    /// The digests of the file.
+    pub fn end_file(&mut self, filename: &str) -> &[Digest] {
+        let start = self.digests.len();
+        #[cfg(feature = "checksum")]
+        if let Some(mut file) = self.file.take() {
+            file.finalize_into(Some(filename), &mut self.digests);
+        }
+        #[cfg(not(feature = "checksum"))]
+        let _ = filename;
+        &self.digests[start..]
+    }
+
+    /// Returns the digests of the files, followed by the ones of the whole output.
+    pub fn finish(&mut self) -> Vec<Digest> {
+        #[cfg(feature = "checksum")]
+        if let Some(total) = self.total.as_mut() {
+            total.finalize_into(None, &mut self.digests);
+        }
+        std::mem::take(&mut self.digests)
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        #[cfg(feature = "checksum")]
+        {
+            if let Some(file) = self.file.as_mut() {
+                file.update(&buf[..written]);
+            }
+            if let Some(total) = self.total.as_mut() {
+                total.update(&buf[..written]);
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the digests of the given algorithms into `out`, one per line in the tagged format, the whole output
+/// being named `total`.
+///
+/// # Errors
+/// The function will return an error if writing to `out` fails.
+pub fn report<W: Write>(digests: &[Digest], algorithms: &[Algorithm], out: &mut W) -> io::Result<()> {
+    for digest in digests.iter().filter(|digest| algorithms.contains(&digest.algorithm)) {
+        let name = digest.name.as_deref().unwrap_or("total");
+        writeln!(out, "{} ({}) = {}", digest.algorithm.name(), name, digest.hex)?;
+    }
+    Ok(())
+}
+
+/// `Sums` struct is the content of a sums file: the expected digests of the files, by name.
+///
+/// # Fields
+///
+/// * `path`: The path of the sums file, used in error messages.
+/// * `entries`: The expected digests of every file listed, in lowercase hexadecimal.
+#[derive(Debug, Default)]
+pub struct Sums {
+    path: String,
+    entries: HashMap<String, Vec<(Algorithm, String)>>,
+}
+
+impl Sums {
+    /// Reads a sums file. Its lines are either in the plain format, `digest  name` (or `digest *name` for a file
+    /// hashed in binary mode), the algorithm being told by the length of the digest, or in the tagged format,
+    /// `SHA256 (name) = digest`. Blank lines and lines starting with `#` are skipped.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+    /// opened or read, and `MinicatError::InvalidData` if one of its lines is not a checksum.
+    #[cfg_attr(not(feature = "checksum"), allow(dead_code))]
+    pub fn load(path: &str) -> Result<Self, MinicatError> {
+        let mut content = String::new();
+        File::open(path)
+            .map_err(|source| MinicatError::OpenFailed { path: path.to_string(), source })?
+            .read_to_string(&mut content)
+            .map_err(|source| MinicatError::ReadFailed { path: path.to_string(), line: None, source })?;
+        let mut sums = Sums { path: path.to_string(), entries: HashMap::new() };
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (name, algorithm, digest) = parse_line(line).ok_or_else(|| MinicatError::InvalidData {
+                path: path.to_string(),
+                message: format!("line {}: improperly formatted checksum line", index + 1),
+            })?;
+            sums.entries.entry(normalize(&name).to_string()).or_default().push((algorithm, digest));
+        }
+
+        Ok(sums)
+    }
+
+    /// Returns the algorithms of the digests listed, each of them once.
+    pub fn algorithms(&self) -> Vec<Algorithm> {
+        let mut algorithms = Vec::new();
+        for &(algorithm, _) in self.entries.values().flatten() {
+            if !algorithms.contains(&algorithm) {
+                algorithms.push(algorithm);
+            }
+        }
+        algorithms
+    }
+
+    /// Checks the digests of a printed file against the ones listed for it.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::ChecksumMismatch` if a digest differs from the listed one, and
+    /// `MinicatError::ChecksumMissing` if the file is not listed.
+    pub fn check(&self, filename: &str, digests: &[Digest]) -> Result<(), MinicatError> {
+        let expected = self.entries.get(normalize(filename)).ok_or_else(|| MinicatError::ChecksumMissing {
+            path: filename.to_string(),
+            sums: self.path.clone(),
+        })?;
+        for (algorithm, expected) in expected {
+            let Some(digest) = digests.iter().find(|digest| digest.algorithm == *algorithm) else {
+                continue;
+            };
+            if digest.hex != *expected {
+                return Err(MinicatError::ChecksumMismatch {
+                    path: filename.to_string(),
+                    algorithm: algorithm.name(),
+                    expected: expected.clone(),
+                    actual: digest.hex.clone(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a line of a sums file into the name of the file, the algorithm and the digest.
+///
+/// A line starting with a backslash has the backslashes and newlines of its name escaped, as GNU tools write
+/// them.
+#[cfg_attr(not(feature = "checksum"), allow(dead_code))]
+fn parse_line(line: &str) -> Option<(String, Algorithm, String)> {
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let (name, algorithm, digest) = match line.split_once(" (") {
+        Some((tag, rest)) if !tag.contains(' ') => {
+            let (name, digest) = rest.rsplit_once(") = ")?;
+            (name, Algorithm::parse(tag).ok()?, digest)
+        }
+        _ => {
+            let (digest, name) = line.split_once(' ')?;
+            let name = name.strip_prefix([' ', '*'])?;
+            (name, Algorithm::from_hex_length(digest.len())?, digest)
+        }
+    };
+    if digest.len() != 2 * algorithm.length() || !digest.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let name = if escaped { unescape(name) } else { name.to_string() };
+    Some((name, algorithm, digest.to_ascii_lowercase()))
+}
+
+/// Undoes the escaping of a name in a sums file, `\\` standing for a backslash and `\n` for a newline.
+#[cfg_attr(not(feature = "checksum"), allow(dead_code))]
+fn unescape(name: &str) -> String {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut characters = name.chars();
+    while let Some(character) = characters.next() {
+        match (character, characters.clone().next()) {
+            ('\\', Some('\\')) => {
+                characters.next();
+                unescaped.push('\\');
+            }
+            ('\\', Some('n')) => {
+                characters.next();
+                unescaped.push('\n');
+            }
+            _ => unescaped.push(character),
+        }
+    }
+    unescaped
+}
+
+/// Returns the name of a file without a leading `./`, so `./a.txt` and `a.txt` are the same file.
+fn normalize(name: &str) -> &str {
+    name.strip_prefix("./").unwrap_or(name)
+}
+
+/// Returns the algorithms given with `--checksum`, added by `args`.
+#[cfg(feature = "checksum")]
+pub fn from_matches(matches: &ArgMatches) -> Vec<Algorithm> {
+    matches.get_many::<Algorithm>("checksum").map(|algorithms| algorithms.copied().collect()).unwrap_or_default()
+}
+
+/// Returns the algorithms given with `--checksum`, which is not offered without the `checksum` feature.
+#[cfg(not(feature = "checksum"))]
+pub fn from_matches(_matches: &ArgMatches) -> Vec<Algorithm> {
+    Vec::new()
+}
+
+/// Reads the sums file given with `--verify`, added by `args`.
+///
+/// # Errors
+/// The function will return an error if the sums file cannot be read, see `Sums::load`.
+#[cfg(feature = "checksum")]
+pub fn sums_from_matches(matches: &ArgMatches) -> Result<Option<Sums>, MinicatError> {
+    matches.get_one::<String>("verify").map(|path| Sums::load(path)).transpose()
+}
+
+/// Reads the sums file given with `--verify`, which is not offered without the `checksum` feature.
+///
+/// # Errors
+/// The function never fails without the `checksum` feature.
+#[cfg(not(feature = "checksum"))]
+pub fn sums_from_matches(_matches: &ArgMatches) -> Result<Option<Sums>, MinicatError> {
+    Ok(None)
+}
+
+/// Adds the checksum options to the command, if the `checksum` feature is enabled.
+///
+/// * `checksum` ('--checksum'): this option will compute the checksums of every printed file and of the whole
+///   output, with a comma separated list of algorithms, and report them on the standard error.
+/// * `verify` ('--verify'): this option will check the checksums of every printed file against a sums file,
+///   a mismatch being a failure of the file.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "checksum")]
+    let command = command
+        .arg(Arg::new("checksum")
+            .action(ArgAction::Append)
+            .long("checksum")
+            .value_name("ALGORITHMS")
+            .value_delimiter(',')
+            .value_parser(Algorithm::parse)
+            .help("Report the md5, sha1, sha256 or sha512 checksums of the output on stderr, e.g. sha256,md5"))
+        .arg(verify_arg());
+    command
+}
+
+/// The options changing what is printed of the files, which cannot be checked against their checksums then.
+#[cfg(feature = "checksum")]
+const CHANGES_CONTENT: [&str; 42] = [
+    "number", "nonblank", "show_ends", "show_tabs", "expand_tabs", "unexpand", "wrap", "truncate",
+    "show_nonprinting", "show_all", "e", "t", "squeeze_blank", "max_bytes", "max_output_lines", "lossy",
+    "max_lines", "skip_lines", "lines", "skip_bytes", "count_bytes", "from_encoding", "to_encoding", "reverse",
+    "squeeze_repeats", "count_repeats", "strip_ansi", "lf", "crlf", "with_filename", "timestamps", "pretty_json",
+    "hex", "dump", "base64_encode", "base64_decode", "binary", "decompress", "archive_member", "list_members",
+    "follow", "follow_name",
+];
+
+/// Returns the `--verify` option, which conflicts with every option changing what is printed of the files.
+#[cfg(feature = "checksum")]
+fn verify_arg() -> Arg {
+    let arg = Arg::new("verify")
+        .action(ArgAction::Set)
+        .long("verify")
+        .value_name("SUMS")
+        .conflicts_with_all(CHANGES_CONTENT)
+        .help("Check the files against the checksums of a sums file, as written by sha256sum");
+    // the filters only exist with the `regex` feature
+    #[cfg(feature = "regex")]
+    let arg = arg.conflicts_with_all(["match", "exclude", "replace"]);
+    arg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MD5: &str = "d41d8cd98f00b204e9800998ecf8427e";
+
+    #[test]
+    fn parse_line_reads_the_plain_format() {
+        let parsed = parse_line(&format!("{}  a.txt", MD5));
+        assert_eq!(parsed, Some(("a.txt".to_string(), Algorithm::Md5, MD5.to_string())));
+        let parsed = parse_line(&format!("{} *name with spaces", MD5.to_uppercase()));
+        assert_eq!(parsed, Some(("name with spaces".to_string(), Algorithm::Md5, MD5.to_string())));
+    }
+
+    #[test]
+    fn parse_line_reads_the_tagged_format() {
+        let parsed = parse_line(&format!("MD5 (a (1).txt) = {}", MD5));
+        assert_eq!(parsed, Some(("a (1).txt".to_string(), Algorithm::Md5, MD5.to_string())));
+    }
+
+    #[test]
+    fn parse_line_unescapes_the_names() {
+        let parsed = parse_line(&format!("\\{}  back\\\\slash\\nnewline", MD5));
+        assert_eq!(parsed, Some(("back\\slash\nnewline".to_string(), Algorithm::Md5, MD5.to_string())));
+        assert_eq!(unescape("a\\b"), "a\\b");
+    }
+
+    #[test]
+    fn parse_line_rejects_the_malformed_lines() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line(&format!("{}a.txt", MD5)), None);
+        assert_eq!(parse_line("abc  a.txt"), None);
+        assert_eq!(parse_line(&format!("SHA256 (a.txt) = {}", MD5)), None);
+        assert_eq!(parse_line(&format!("{}  a.txt", MD5.replace('d', "g"))), None);
+    }
+
+    #[test]
+    fn normalize_drops_the_leading_dot() {
+        assert_eq!(normalize("./a.txt"), "a.txt");
+        assert_eq!(normalize("a.txt"), "a.txt");
+    }
+}
@@ -0,0 +1,77 @@
+//! Copying the output to the system clipboard with `--clipboard`, instead of piping it through `xclip` or
+//! `pbcopy`.
+//!
+//! The output is collected in memory while it is printed, and placed on the clipboard as text once everything
+//! has been printed, the bytes that are not valid UTF-8 being replaced with U+FFFD. The clipboard is reached
+//! with the `arboard` crate, behind the `clipboard` feature. On Linux, the clipboard belongs to the program
+//! that set it, so the text only outlives minicat if a clipboard manager is running.
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "clipboard")]
+use clap::{Arg, ArgAction};
+
+use crate::error::MinicatError;
+
+/// Places the text on the clipboard.
+///
+/// # Errors
+/// The function will return `MinicatError::ClipboardFailed` if the clipboard cannot be reached or set.
+#[cfg(feature = "clipboard")]
+pub fn set(data: &[u8]) -> Result<(), MinicatError> {
+    let failed = |e: arboard::Error| MinicatError::ClipboardFailed(e.to_string());
+    arboard::Clipboard::new().map_err(failed)?.set_text(String::from_utf8_lossy(data)).map_err(failed)
+}
+
+/// Refuses to set the clipboard, since the `clipboard` feature is not enabled.
+///
+/// # Errors
+/// The function always returns `MinicatError::ClipboardFailed`.
+#[cfg(not(feature = "clipboard"))]
+pub fn set(_data: &[u8]) -> Result<(), MinicatError> {
+    Err(MinicatError::ClipboardFailed("copying to the clipboard needs the `clipboard` feature".to_string()))
+}
+
+/// Returns `true` if `--clipboard`, added by `args`, is given.
+#[cfg(feature = "clipboard")]
+pub fn from_matches(matches: &ArgMatches) -> bool {
+    matches.get_flag("clipboard")
+}
+
+/// Returns `false`, `--clipboard` is not offered without the `clipboard` feature.
+#[cfg(not(feature = "clipboard"))]
+pub fn from_matches(_matches: &ArgMatches) -> bool {
+    false
+}
+
+/// Adds the clipboard option to the command, if the `clipboard` feature is enabled.
+///
+/// * `clipboard` ('--clipboard'): this option will place the output on the system clipboard once everything has
+///   been printed, in addition to printing it.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "clipboard")]
+    let command = command.arg(Arg::new("clipboard")
+        .action(ArgAction::SetTrue)
+        .long("clipboard")
+        .help("Also copy the output to the system clipboard"));
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn clipboard_option_is_a_flag() {
+        let matches = args(Command::new("minicat")).try_get_matches_from(["minicat", "--clipboard"]).unwrap();
+        assert!(from_matches(&matches));
+        assert!(!from_matches(&args(Command::new("minicat")).get_matches_from(["minicat"])));
+    }
+
+    #[cfg(not(feature = "clipboard"))]
+    #[test]
+    fn clipboard_is_refused_without_the_feature() {
+        assert!(args(Command::new("minicat")).try_get_matches_from(["minicat", "--clipboard"]).is_err());
+        assert!(matches!(set(b"text"), Err(MinicatError::ClipboardFailed(_))));
+    }
+}
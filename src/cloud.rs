@@ -0,0 +1,148 @@
+//! Reading objects of cloud storage given as input files, `s3://bucket/key` on Amazon S3 and `gs://bucket/key`
+//! on Google Cloud Storage.
+//!
+//! The objects are read with the `object_store` crate, in ranges of `CHUNK` bytes requested one after the other,
+//! so a large object is streamed instead of being held in memory. The credentials and the region come from the
+//! environment, the same variables the cloud command line tools use. Reading objects needs the `object-store`
+//! feature, without which they are refused.
+
+use std::io::{self, Read};
+
+/// The number of bytes requested at once.
+#[cfg(feature = "object-store")]
+const CHUNK: u64 = 8 * 1024 * 1024;
+
+/// Returns `true` if the input is an `s3://` or `gs://` URL.
+pub fn is_object_url(filename: &str) -> bool {
+    filename.starts_with("s3://") || filename.starts_with("gs://")
+}
+
+/// Opens the object at the URL, after checking that it exists.
+///
+/// ## Returns
+/// A reader giving the content of the object.
+///
+/// # Errors
+/// The function will return an error of kind `io::ErrorKind::NotFound` if there is no such object, and any other
+/// error if the URL is malformed, the credentials are missing, or the request fails.
+#[cfg(feature = "object-store")]
+pub fn open(url: &str) -> io::Result<Box<dyn Read>> {
+    use object_store::aws::AmazonS3Builder;
+    use object_store::gcp::GoogleCloudStorageBuilder;
+    use object_store::ObjectStore;
+
+    let parsed = url::Url::parse(url).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let store: Box<dyn ObjectStore> = if parsed.scheme() == "s3" {
+        Box::new(AmazonS3Builder::from_env().with_url(url).build().map_err(store_error)?)
+    } else {
+        Box::new(GoogleCloudStorageBuilder::from_env().with_url(url).build().map_err(store_error)?)
+    };
+    let path = object_store::path::Path::from_url_path(parsed.path()).map_err(|e| io::Error::other(e.to_string()))?;
+    // the requests are asynchronous, they are waited for on a runtime of the reading thread
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let size = runtime.block_on(store.head(&path)).map_err(store_error)?.size;
+
+    Ok(Box::new(ObjectReader { runtime, store, path, size, position: 0, chunk: bytes::Bytes::new() }))
+}
+
+/// Refuses to open the object, since the `object-store` feature is not enabled.
+///
+/// # Errors
+/// The function always returns an error of kind `io::ErrorKind::Unsupported`.
+#[cfg(not(feature = "object-store"))]
+pub fn open(_url: &str) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reading s3:// and gs:// URLs needs the `object-store` feature"))
+}
+
+/// `ObjectReader` struct reads an object range after range.
+///
+/// # Fields
+///
+/// * `runtime`: The runtime the requests are run on.
+/// * `store`: The storage the object is in.
+/// * `path`: The path of the object in `store`.
+/// * `size`: The size of the object.
+/// * `position`: The offset of the next range to request.
+/// * `chunk`: What is left of the last range received.
+#[cfg(feature = "object-store")]
+struct ObjectReader {
+    runtime: tokio::runtime::Runtime,
+    store: Box<dyn object_store::ObjectStore>,
+    path: object_store::path::Path,
+    size: u64,
+    position: u64,
+    chunk: bytes::Bytes,
+}
+
+#[cfg(feature = "object-store")]
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.chunk.is_empty() {
+            if self.position >= self.size {
+                return Ok(0);
+            }
+            let end = (self.position + CHUNK).min(self.size);
+            let range = self.store.get_range(&self.path, self.position..end);
+            self.chunk = self.runtime.block_on(range).map_err(store_error)?;
+            self.position = end;
+        }
+        let length = buf.len().min(self.chunk.len());
+        buf[..length].copy_from_slice(&self.chunk[..length]);
+        self.chunk = self.chunk.slice(length..);
+        Ok(length)
+    }
+}
+
+/// Converts an error of the storage, keeping a missing object apart so it is reported like a missing file.
+#[cfg(feature = "object-store")]
+fn store_error(error: object_store::Error) -> io::Error {
+    match error {
+        object_store::Error::NotFound { .. } => io::Error::new(io::ErrorKind::NotFound, error.to_string()),
+        _ => io::Error::other(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_urls_are_told_by_their_scheme() {
+        assert!(is_object_url("s3://bucket/key"));
+        assert!(is_object_url("gs://bucket/dir/key"));
+        assert!(!is_object_url("https://bucket/key"));
+        assert!(!is_object_url("s3.txt"));
+    }
+
+    #[cfg(feature = "object-store")]
+    #[test]
+    fn objects_are_read_range_after_range() {
+        use object_store::memory::InMemory;
+        use object_store::ObjectStore;
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let store = InMemory::new();
+        let path = object_store::path::Path::from("dir/object");
+        let data: Vec<u8> = (0..CHUNK + 1000).map(|index| (index % 251) as u8).collect();
+        runtime.block_on(store.put(&path, data.clone().into())).unwrap();
+        let size = data.len() as u64;
+        let store: Box<dyn ObjectStore> = Box::new(store);
+        let mut reader = ObjectReader { runtime, store, path, size, position: 0, chunk: bytes::Bytes::new() };
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert!(read == data);
+    }
+
+    #[cfg(feature = "object-store")]
+    #[test]
+    fn missing_objects_are_not_found() {
+        let error = object_store::Error::NotFound { path: "key".to_string(), source: "gone".into() };
+        assert_eq!(store_error(error).kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(not(feature = "object-store"))]
+    #[test]
+    fn objects_are_refused_without_the_feature() {
+        assert_eq!(open("s3://bucket/key").err().unwrap().kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,215 @@
+//! Decompressing the inputs on the fly, for `--decompress`, so `minicat access.log.gz` works like `zcat`.
+//!
+//! Every supported format is a `Decompressor`: gzip always, and zstd, xz and bzip2 behind the `zstd`, `xz` and
+//! `bzip2` features, which covers `zcat`, `zstdcat`, `xzcat` and `bzcat`. An input is only decompressed if it
+//! starts with the magic number of one of them, whatever its name, so a file that is not compressed after all
+//! is printed as it is. The files with the extension of a format are looked at by default, and every input,
+//! the standard input included, with `--decompress`. A compressed file cannot be copied or mapped as it is, so
+//! it always goes through the buffered reader returned by `decompress`.
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use flate2::bufread::MultiGzDecoder;
+
+/// `Decompression` enum decides which inputs are looked at for compressed data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Decompression {
+    /// The inputs are printed as they are.
+    Never,
+    /// The files with the extension of a `Decompressor` are decompressed.
+    #[default]
+    Auto,
+    /// Every input is decompressed, if it is compressed.
+    Always,
+}
+
+impl Decompression {
+    /// Returns `true` if the input may be compressed, in which case it has to be read through `decompress`.
+    pub fn applies(self, filename: &str) -> bool {
+        match self {
+            Decompression::Never => false,
+            Decompression::Auto => Decompressor::from_extension(filename).is_some(),
+            Decompression::Always => true,
+        }
+    }
+}
+
+/// `Decompressor` enum lists the compression formats, those whose feature is not enabled being left out when
+/// looking for one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decompressor {
+    Gzip,
+    Zstd,
+    Xz,
+    Bzip2,
+}
+
+impl Decompressor {
+    /// The formats, in the order they are looked for.
+    const ALL: [Decompressor; 4] = [Decompressor::Gzip, Decompressor::Zstd, Decompressor::Xz, Decompressor::Bzip2];
+
+    /// Returns the format of the file with the given name, known from its extension.
+    pub fn from_extension(filename: &str) -> Option<Self> {
+        let extension = Path::new(filename).extension()?;
+        Self::available().find(|decompressor| {
+            decompressor.extensions().iter().any(|known| extension.eq_ignore_ascii_case(known))
+        })
+    }
+
+    /// Returns the format of the data starting with the given bytes, known from its magic number.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        Self::available().find(|decompressor| match decompressor {
+            // the magic number of bzip2 goes on with the block size, a digit
+            Decompressor::Bzip2 => data.starts_with(b"BZh") && data.get(3).is_some_and(u8::is_ascii_digit),
+            _ => data.starts_with(decompressor.magic()),
+        })
+    }
+
+    /// Returns the formats whose feature is enabled.
+    fn available() -> impl Iterator<Item = Self> {
+        Self::ALL.into_iter().filter(|decompressor| match decompressor {
+            Decompressor::Gzip => true,
+            Decompressor::Zstd => cfg!(feature = "zstd"),
+            Decompressor::Xz => cfg!(feature = "xz"),
+            Decompressor::Bzip2 => cfg!(feature = "bzip2"),
+        })
+    }
+
+    /// Returns the first bytes of every stream of the format.
+    fn magic(self) -> &'static [u8] {
+        match self {
+            Decompressor::Gzip => &[0x1f, 0x8b],
+            Decompressor::Zstd => &[0x28, 0xb5, 0x2f, 0xfd],
+            Decompressor::Xz => &[0xfd, b'7', b'z', b'X', b'Z', 0x00],
+            Decompressor::Bzip2 => b"BZh",
+        }
+    }
+
+    /// Returns the extensions of the files compressed in the format.
+    fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Decompressor::Gzip => &["gz", "tgz"],
+            Decompressor::Zstd => &["zst", "tzst"],
+            Decompressor::Xz => &["xz", "txz"],
+            Decompressor::Bzip2 => &["bz2", "tbz2"],
+        }
+    }
+
+    /// Returns a reader giving the decompressed content of `reader`. Several streams in a row, as written by
+    /// `cat a.gz b.gz`, are all decompressed, like the `*cat` tools do.
+    ///
+    /// # Errors
+    /// The function will return an error if the decoder cannot be set up.
+    fn reader(self, reader: Box<dyn BufRead>) -> io::Result<Box<dyn Read>> {
+        match self {
+            Decompressor::Gzip => Ok(Box::new(MultiGzDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            Decompressor::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)),
+            #[cfg(feature = "xz")]
+            Decompressor::Xz => Ok(Box::new(xz2::bufread::XzDecoder::new_multi_decoder(reader))),
+            #[cfg(feature = "bzip2")]
+            Decompressor::Bzip2 => Ok(Box::new(bzip2::bufread::MultiBzDecoder::new(reader))),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("`available` leaves out the formats whose feature is not enabled"),
+        }
+    }
+}
+
+/// Returns a reader giving the decompressed content of `reader` if it starts with the magic number of a
+/// `Decompressor`, or `reader` itself otherwise.
+///
+/// # Errors
+/// The function will return an error if reading the first bytes of `reader` fails.
+pub fn decompress(mut reader: Box<dyn BufRead>, buffer_size: usize) -> io::Result<Box<dyn BufRead>> {
+    let Some(decompressor) = Decompressor::detect(reader.fill_buf()?) else {
+        return Ok(reader);
+    };
+    Ok(Box::new(BufReader::with_capacity(buffer_size, decompressor.reader(reader)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    use super::*;
+
+    /// Returns `data` compressed with gzip.
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Reads `data` through `decompress`, and returns what comes out.
+    fn read(data: Vec<u8>) -> Vec<u8> {
+        let mut output = Vec::new();
+        decompress(Box::new(io::Cursor::new(data)), 4096).unwrap().read_to_end(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn gzip_is_known_by_its_extension_and_magic_number() {
+        assert_eq!(Decompressor::from_extension("access.log.gz"), Some(Decompressor::Gzip));
+        assert_eq!(Decompressor::from_extension("backup.TGZ"), Some(Decompressor::Gzip));
+        assert_eq!(Decompressor::from_extension("notes.txt"), None);
+        assert_eq!(Decompressor::detect(&gzip(b"x")), Some(Decompressor::Gzip));
+        assert_eq!(Decompressor::detect(b"plain"), None);
+    }
+
+    #[test]
+    fn modes_decide_which_inputs_are_looked_at() {
+        assert!(Decompression::Auto.applies("a.gz"));
+        assert!(!Decompression::Auto.applies("-"));
+        assert!(Decompression::Always.applies("-"));
+        assert!(!Decompression::Never.applies("a.gz"));
+    }
+
+    #[test]
+    fn compressed_data_is_decompressed_and_the_rest_left_alone() {
+        assert_eq!(read(gzip(b"zipped\n")), b"zipped\n");
+        assert_eq!(read([gzip(b"one\n"), gzip(b"two\n")].concat()), b"one\ntwo\n");
+        assert_eq!(read(b"plain\n".to_vec()), b"plain\n");
+        assert_eq!(read(Vec::new()), b"");
+    }
+
+    #[test]
+    fn other_formats_are_known_when_their_feature_is_enabled() {
+        assert_eq!(Decompressor::from_extension("a.zst").is_some(), cfg!(feature = "zstd"));
+        assert_eq!(Decompressor::from_extension("a.xz").is_some(), cfg!(feature = "xz"));
+        assert_eq!(Decompressor::from_extension("a.bz2").is_some(), cfg!(feature = "bzip2"));
+        assert_eq!(Decompressor::detect(b"BZh9").is_some(), cfg!(feature = "bzip2"));
+        assert_eq!(Decompressor::detect(b"BZhx"), None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_data_is_decompressed() {
+        let compressed = zstd::encode_all(&b"zstd\n"[..], 0).unwrap();
+        assert_eq!(Decompressor::detect(&compressed), Some(Decompressor::Zstd));
+        assert_eq!(read([compressed.clone(), compressed].concat()), b"zstd\nzstd\n");
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_data_is_decompressed() {
+        let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(b"xz\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(Decompressor::detect(&compressed), Some(Decompressor::Xz));
+        assert_eq!(read([compressed.clone(), compressed].concat()), b"xz\nxz\n");
+    }
+
+    #[cfg(feature = "bzip2")]
+    #[test]
+    fn bzip2_data_is_decompressed() {
+        let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder.write_all(b"bz\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert_eq!(Decompressor::detect(&compressed), Some(Decompressor::Bzip2));
+        assert_eq!(read([compressed.clone(), compressed].concat()), b"bz\nbz\n");
+    }
+}
@@ -0,0 +1,212 @@
+//! Dumping the bytes of the files instead of printing them, for `--hex` and `--dump`.
+//!
+//! A dump is not made of the lines of the files, so it does not go through the `LinePrinter`: the files are
+//! read as raw chunks and handed to a `Renderer`, which keeps the bytes of an unfinished row from one chunk to
+//! the next. The offsets go on from one file to the next, the same as dumping the output of `cat` does.
+
+use std::io::Write;
+
+/// The number of bytes shown on every row.
+const ROW: usize = 16;
+
+/// `DumpFormat` enum is the base the bytes are dumped in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Hexadecimal, the format of `xxd`.
+    Hex,
+    /// Octal, the format of `od`.
+    Octal,
+    /// Decimal, the format of `od -A d -t u`.
+    Decimal,
+}
+
+impl DumpFormat {
+    /// Returns the number of bytes in a group when none is given: the pairs of `xxd`, or single bytes.
+    pub fn default_group(self) -> usize {
+        match self {
+            DumpFormat::Hex => 2,
+            DumpFormat::Octal | DumpFormat::Decimal => 1,
+        }
+    }
+
+    /// Returns a new `Renderer` of the format, starting at offset 0.
+    ///
+    /// ## Parameters
+    /// * `group` - The number of bytes shown as a single number, 1, 2, 4 or 8.
+    pub fn renderer(self, group: usize) -> Box<dyn Renderer> {
+        Box::new(Dump { format: self, group, offset: 0, row: Vec::with_capacity(ROW) })
+    }
+
+    /// Returns the number of digits of a group of `group` bytes.
+    fn digits(self, group: usize) -> usize {
+        let bits = group * 8;
+        match self {
+            DumpFormat::Hex => bits / 4,
+            DumpFormat::Octal => bits.div_ceil(3),
+            DumpFormat::Decimal => (u64::MAX >> (64 - bits)).to_string().len(),
+        }
+    }
+}
+
+/// `Renderer` trait turns the bytes of the files into the output, for the modes that do not print the files
+/// line by line.
+///
+/// The errors are messages telling what is wrong with the data, and where.
+pub trait Renderer {
+    /// Tells the renderer that the data of another file follows.
+    fn start_file(&mut self) {}
+
+    /// Renders a chunk of the data into `output`, keeping what does not fill a whole row for the next chunk.
+    fn render(&mut self, data: &[u8], output: &mut Vec<u8>) -> Result<(), String>;
+
+    /// Renders what is left once all the data has been rendered.
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), String>;
+}
+
+/// `Dump` struct renders rows of 16 bytes: the offset, the bytes in groups, and the printable bytes as text.
+///
+/// The bytes of a group are read in the order of the file (big-endian), like `xxd` does, so a hexadecimal group
+/// is simply the digits of its bytes one after the other.
+///
+/// # Fields
+///
+/// * `format`: The `DumpFormat` of the offsets and the groups.
+/// * `group`: The number of bytes of a group.
+/// * `offset`: The offset of the first byte of `row` in the data.
+/// * `row`: The bytes of the row being filled.
+#[derive(Debug)]
+struct Dump {
+    format: DumpFormat,
+    group: usize,
+    offset: u64,
+    row: Vec<u8>,
+}
+
+impl Dump {
+    /// Writes the bytes of `row` as a row of the dump, padded when it is the last, shorter one.
+    fn write_row(&mut self, output: &mut Vec<u8>) {
+        let digits = self.format.digits(self.group);
+        // writing into a vector cannot fail
+        let _ = match self.format {
+            DumpFormat::Hex => write!(output, "{:08x}:", self.offset),
+            DumpFormat::Octal => write!(output, "{:07o}:", self.offset),
+            DumpFormat::Decimal => write!(output, "{:07}:", self.offset),
+        };
+        for start in (0..ROW).step_by(self.group) {
+            output.push(b' ');
+            let bytes = &self.row[start.min(self.row.len())..(start + self.group).min(self.row.len())];
+            if bytes.is_empty() {
+                output.extend(std::iter::repeat_n(b' ', digits));
+                continue;
+            }
+            let _ = match self.format {
+                // the bytes of an incomplete group are shown as they are, like `xxd` does
+                DumpFormat::Hex => bytes.iter().try_for_each(|byte| write!(output, "{:02x}", byte)),
+                // an incomplete group is padded with zeros, like `od` does
+                DumpFormat::Octal => write!(output, "{:0digits$o}", group_value(bytes, self.group)),
+                DumpFormat::Decimal => write!(output, "{:>digits$}", group_value(bytes, self.group)),
+            };
+            if self.format == DumpFormat::Hex {
+                output.extend(std::iter::repeat_n(b' ', digits - bytes.len() * 2));
+            }
+        }
+        output.extend_from_slice(b"  ");
+        output.extend(self.row.iter().map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte } else { b'.' }));
+        output.push(b'\n');
+        self.offset += self.row.len() as u64;
+        self.row.clear();
+    }
+}
+
+/// Reads the bytes of a group as a big-endian number, the missing bytes of an incomplete one being zeros.
+fn group_value(bytes: &[u8], group: usize) -> u64 {
+    let value = bytes.iter().fold(0u64, |value, &byte| (value << 8) | u64::from(byte));
+    value << (8 * (group - bytes.len()))
+}
+
+impl Renderer for Dump {
+    fn render(&mut self, mut data: &[u8], output: &mut Vec<u8>) -> Result<(), String> {
+        while !data.is_empty() {
+            let length = (ROW - self.row.len()).min(data.len());
+            self.row.extend_from_slice(&data[..length]);
+            data = &data[length..];
+            if self.row.len() == ROW {
+                self.write_row(output);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), String> {
+        if !self.row.is_empty() {
+            self.write_row(output);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders the chunks one after the other, and returns the whole dump.
+    fn dump(format: DumpFormat, group: usize, chunks: &[&[u8]]) -> String {
+        let mut renderer = format.renderer(group);
+        let mut output = Vec::new();
+        for chunk in chunks {
+            renderer.render(chunk, &mut output).unwrap();
+        }
+        renderer.finish(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn hex_rows_are_those_of_xxd() {
+        let expected = concat!(
+            "00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 0001  Hello, world!...\n",
+            "00000010: ff                                       .\n",
+        );
+        assert_eq!(dump(DumpFormat::Hex, 2, &[b"Hello, world!\n\x00\x01\xff"]), expected);
+    }
+
+    #[test]
+    fn rows_go_on_across_the_chunks() {
+        let whole = dump(DumpFormat::Hex, 2, &[b"0123456789abcdefghij"]);
+        assert_eq!(dump(DumpFormat::Hex, 2, &[b"0123", b"456789abcdefg", b"", b"hij"]), whole);
+        assert_eq!(dump(DumpFormat::Hex, 2, &[]), "");
+    }
+
+    #[test]
+    fn offsets_go_on_across_the_files() {
+        let mut renderer = DumpFormat::Hex.renderer(4);
+        let mut output = Vec::new();
+        renderer.render(&[b'a'; 16], &mut output).unwrap();
+        renderer.start_file();
+        renderer.render(b"b", &mut output).unwrap();
+        renderer.finish(&mut output).unwrap();
+        let last = format!("00000010: 62{}b\n", " ".repeat(35));
+        assert!(String::from_utf8(output).unwrap().ends_with(&last));
+    }
+
+    #[test]
+    fn octal_and_decimal_groups_are_padded_numbers() {
+        let octal = dump(DumpFormat::Octal, 1, &[b"ab\n"]);
+        assert_eq!(octal, format!("0000000: 141 142 012{}ab.\n", " ".repeat(13 * 4 + 2)));
+        let decimal = dump(DumpFormat::Decimal, 2, &[b"abc"]);
+        assert!(decimal.starts_with("0000000: 24930 25344      "), "{decimal}");
+        let octal = dump(DumpFormat::Octal, 4, &[b"\xff\xff\xff\xff"]);
+        assert!(octal.starts_with("0000000: 37777777777 "), "{octal}");
+    }
+
+    #[test]
+    fn numbers_of_a_group_have_room_for_its_largest_value() {
+        assert_eq!(DumpFormat::Hex.digits(8), 16);
+        assert_eq!(DumpFormat::Octal.digits(1), 3);
+        assert_eq!(DumpFormat::Octal.digits(8), 22);
+        assert_eq!(DumpFormat::Decimal.digits(1), 3);
+        assert_eq!(DumpFormat::Decimal.digits(8), 20);
+        assert_eq!(group_value(b"\x01", 2), 256);
+    }
+}
@@ -0,0 +1,326 @@
+//! Transcoding the inputs from a legacy encoding to UTF-8, for `--from-encoding`, and the output from UTF-8 to
+//! another encoding, for `--to-encoding`.
+//!
+//! The inputs are decoded with `encoding_rs` as they are read, in a `Decoded` reader `open_file` puts last in
+//! front of them, so everything that handles the lines afterwards sees UTF-8: a file in UTF-16, Latin-1 or
+//! Shift_JIS prints correctly instead of coming out as garbage, or having its lines dropped as invalid UTF-8.
+//! The encodings are named by their WHATWG labels, `utf-16le`, `latin1` or `shift_jis` for instance, where
+//! `latin1` stands for windows-1252, its superset. The byte order mark of the encoding is stripped the same as
+//! the other ones, unless `--keep-bom` is given, and the bytes that are not valid in the encoding are replaced
+//! with U+FFFD REPLACEMENT CHARACTER.
+//!
+//! The output goes the other way through an `Encoded` writer, the first one it goes through, so every way a file
+//! can be printed is transcoded alike, and the checksums, the counts and the caps of the output are the ones of
+//! the bytes actually written. A character cut between two writes is kept until the rest of it comes,
+//! the bytes that are not valid UTF-8 become U+FFFD, and the characters the encoding has no room for are printed
+//! as `?`. `encoding_rs` only decodes UTF-16, which is encoded here, and starts with a byte order mark, the way
+//! the Windows programs expect it.
+
+use std::io::{self, BufRead, Read, Write};
+
+use encoding_rs::{Decoder, Encoder, EncoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Parses the name of an encoding.
+///
+/// # Errors
+/// The function will return an error if the name is not one of the labels of an encoding.
+pub fn parse(value: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(value.as_bytes()).ok_or_else(|| format!("`{}` is not a known encoding", value))
+}
+
+/// `Decoded` struct is a reader giving the content of the reader it wraps transcoded to UTF-8.
+///
+/// # Fields
+///
+/// * `inner`: The reader the encoded bytes come from.
+/// * `decoder`: The `encoding_rs` decoder, which keeps the characters cut between two reads.
+/// * `decoded`: The UTF-8 bytes decoded last.
+/// * `position`: How many of the `decoded` bytes have been read.
+/// * `finished`: Whether the end of `inner` has been decoded.
+pub struct Decoded<R> {
+    inner: R,
+    decoder: Decoder,
+    decoded: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: BufRead> Decoded<R> {
+    /// Wraps `inner`, whose content is in `encoding`, taking its byte order mark off if `strip_bom` is `true`.
+    pub fn new(inner: R, encoding: &'static Encoding, strip_bom: bool) -> Self {
+        let decoder = if strip_bom {
+            encoding.new_decoder_with_bom_removal()
+        } else {
+            encoding.new_decoder_without_bom_handling()
+        };
+        Decoded { inner, decoder, decoded: Vec::new(), position: 0, finished: false }
+    }
+
+    /// Decodes the next bytes of `inner` into `decoded`, which is left empty at its end.
+    fn decode(&mut self) -> io::Result<()> {
+        let input = self.inner.fill_buf()?;
+        let last = input.is_empty();
+        // the longest the input can get once decoded, so that it is decoded in one go
+        let length = self.decoder.max_utf8_buffer_length(input.len()).unwrap_or(input.len() * 3 + 16);
+        self.decoded.resize(length, 0);
+        let (_, read, written, _) = self.decoder.decode_to_utf8(input, &mut self.decoded, last);
+        self.decoded.truncate(written);
+        self.position = 0;
+        self.inner.consume(read);
+        self.finished = last;
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Read for Decoded<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.decode()?;
+        }
+        let length = buf.len().min(self.decoded.len() - self.position);
+        buf[..length].copy_from_slice(&self.decoded[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+/// `Target` enum is the encoding an `Encoded` writer transcodes to.
+enum Target {
+    /// UTF-8, which is written as it is.
+    Utf8,
+    /// UTF-16, little-endian or big-endian.
+    Utf16 { big_endian: bool },
+    /// Any other encoding, with the `encoding_rs` encoder of it.
+    Other(Encoder),
+}
+
+impl Target {
+    fn new(encoding: Option<&'static Encoding>) -> Self {
+        match encoding {
+            Some(encoding) if encoding == UTF_16LE => Target::Utf16 { big_endian: false },
+            Some(encoding) if encoding == UTF_16BE => Target::Utf16 { big_endian: true },
+            // the encodings that can only be decoded are encoded as UTF-8, like `encoding_rs` does
+            Some(encoding) if encoding.output_encoding() != UTF_8 => Target::Other(encoding.new_encoder()),
+            _ => Target::Utf8,
+        }
+    }
+
+    /// Appends `text` encoded to `output`, `last` telling whether it is the end of the output.
+    fn encode(&mut self, mut text: &str, last: bool, output: &mut Vec<u8>) {
+        match self {
+            Target::Utf8 => output.extend_from_slice(text.as_bytes()),
+            Target::Utf16 { big_endian } => {
+                for unit in text.encode_utf16() {
+                    output.extend_from_slice(&if *big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() });
+                }
+            }
+            Target::Other(encoder) => loop {
+                let start = output.len();
+                let length = encoder.max_buffer_length_from_utf8_without_replacement(text.len());
+                output.resize(start + length.unwrap_or(text.len() * 4 + 16), 0);
+                let (result, read, written) =
+                    encoder.encode_from_utf8_without_replacement(text, &mut output[start..], last);
+                output.truncate(start + written);
+                text = &text[read..];
+                match result {
+                    EncoderResult::InputEmpty => break,
+                    EncoderResult::OutputFull => {}
+                    EncoderResult::Unmappable(_) => output.push(b'?'),
+                }
+            },
+        }
+    }
+
+    /// Starts the encoding over once the end of the output has been encoded, in case more is written.
+    fn restart(&mut self) {
+        if let Target::Other(encoder) = self {
+            *encoder = encoder.encoding().new_encoder();
+        }
+    }
+}
+
+/// Returns the length of the part of `data` made of whole characters, leaving out the start of a character cut
+/// short at its end.
+fn complete_length(data: &[u8]) -> usize {
+    let start = data.len().saturating_sub(3);
+    let Some(lead) = data[start..].iter().rposition(|&byte| byte & 0xc0 != 0x80).map(|position| start + position)
+    else {
+        return data.len();
+    };
+    let length = match data[lead] {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    if lead + length > data.len() { lead } else { data.len() }
+}
+
+/// `Encoded` struct is a writer transcoding the UTF-8 written into it to another encoding.
+///
+/// # Fields
+///
+/// * `inner`: The writer the transcoded output goes to.
+/// * `target`: The encoding of the output.
+/// * `pending`: The start of a character cut short at the end of the last write.
+/// * `started`: Whether anything has been written yet, the byte order mark of UTF-16 coming first.
+pub struct Encoded<W: Write> {
+    inner: W,
+    target: Target,
+    pending: Vec<u8>,
+    started: bool,
+}
+
+impl<W: Write> Encoded<W> {
+    /// Wraps `inner`, the output being transcoded to `encoding`, or written as it is if it is `None`.
+    pub fn new(inner: W, encoding: Option<&'static Encoding>) -> Self {
+        Encoded { inner, target: Target::new(encoding), pending: Vec::new(), started: false }
+    }
+
+    /// Returns the writer the transcoded output goes to.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes the end of the output: a character cut short at the end of the last write is replaced, and a
+    /// stateful encoding is switched back to ASCII. What is written afterwards is encoded from scratch.
+    ///
+    /// # Errors
+    /// The function will return an error if writing into the inner writer fails.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if matches!(self.target, Target::Utf8) || !self.started {
+            return Ok(());
+        }
+        let mut encoded = Vec::new();
+        let text = if self.pending.is_empty() { "" } else { "\u{fffd}" };
+        self.pending.clear();
+        self.target.encode(text, true, &mut encoded);
+        self.target.restart();
+        self.inner.write_all(&encoded)
+    }
+}
+
+impl<W: Write> Write for Encoded<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if matches!(self.target, Target::Utf8) {
+            return self.inner.write(buf);
+        }
+        let mut encoded = Vec::new();
+        if !self.started && !buf.is_empty() {
+            if let Target::Utf16 { .. } = self.target {
+                self.target.encode("\u{feff}", false, &mut encoded);
+            }
+            self.started = true;
+        }
+        self.pending.extend_from_slice(buf);
+        let complete = complete_length(&self.pending);
+        for chunk in self.pending[..complete].utf8_chunks() {
+            self.target.encode(chunk.valid(), false, &mut encoded);
+            if !chunk.invalid().is_empty() {
+                self.target.encode("\u{fffd}", false, &mut encoded);
+            }
+        }
+        self.pending.drain(..complete);
+        self.inner.write_all(&encoded)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for Encoded<W> {
+    fn drop(&mut self) {
+        // the output only ends here when following the files is interrupted, and if it cannot be written anymore,
+        // it has failed already and the error was reported then
+        let _ = self.finish().and_then(|()| self.inner.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `writes` one after the other into an `Encoded` writer, and returns what came out of it.
+    fn encode(encoding: &'static Encoding, writes: &[&[u8]]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut encoded = Encoded::new(&mut output, Some(encoding));
+        for write in writes {
+            encoded.write_all(write).unwrap();
+        }
+        encoded.finish().unwrap();
+        drop(encoded);
+        output
+    }
+
+    #[test]
+    fn complete_length_leaves_out_a_character_cut_short() {
+        assert_eq!(complete_length(b"abc"), 3);
+        assert_eq!(complete_length("h\u{e9}".as_bytes()), 3);
+        assert_eq!(complete_length(&"h\u{e9}".as_bytes()[..2]), 1);
+        assert_eq!(complete_length(&"\u{1f600}".as_bytes()[..3]), 0);
+        assert_eq!(complete_length(b""), 0);
+        // a byte that cannot start a character is left to the replacement of the invalid bytes
+        assert_eq!(complete_length(b"a\xff"), 2);
+    }
+
+    #[test]
+    fn utf16_starts_with_a_byte_order_mark() {
+        assert_eq!(encode(UTF_16LE, &[b"h\xc3\xa9\n"]), b"\xff\xfeh\x00\xe9\x00\n\x00");
+        assert_eq!(encode(UTF_16BE, &[b"h"]), b"\xfe\xff\x00h");
+        assert_eq!(encode(UTF_16LE, &[]), b"");
+    }
+
+    #[test]
+    fn a_character_cut_between_two_writes_is_kept() {
+        assert_eq!(encode(UTF_16LE, &[b"\xc3", b"\xa9"]), b"\xff\xfe\xe9\x00");
+        assert_eq!(encode(encoding_rs::WINDOWS_1252, &[b"\xe2\x82", b"\xac"]), b"\x80");
+    }
+
+    #[test]
+    fn invalid_and_unmappable_characters_are_replaced() {
+        assert_eq!(encode(UTF_16LE, &[b"a\xff"]), b"\xff\xfea\x00\xfd\xff");
+        assert_eq!(encode(encoding_rs::WINDOWS_1252, &["\u{3042}".as_bytes()]), b"?");
+        // the start of a character left at the very end is replaced once the output is finished
+        assert_eq!(encode(UTF_16LE, &[b"\xc3"]), b"\xff\xfe\xfd\xff");
+    }
+
+    #[test]
+    fn stateful_encodings_switch_back_to_ascii_at_the_end() {
+        assert_eq!(encode(encoding_rs::ISO_2022_JP, &["a\u{3042}".as_bytes()]), b"a\x1b$B$\"\x1b(B");
+    }
+
+    #[test]
+    fn utf8_is_written_as_it_is() {
+        assert_eq!(encode(UTF_8, &[b"\xc3", b"\xff"]), b"\xc3\xff");
+    }
+
+    /// Reads `input` in `encoding` through a `Decoded` reader taking a byte at a time from it.
+    fn decode(input: &[u8], encoding: &'static Encoding, strip_bom: bool) -> String {
+        let mut decoded = String::new();
+        let inner = io::BufReader::with_capacity(1, input);
+        Decoded::new(inner, encoding, strip_bom).read_to_string(&mut decoded).unwrap();
+        decoded
+    }
+
+    #[test]
+    fn encodings_are_named_by_their_labels() {
+        assert_eq!(parse("latin1").unwrap(), encoding_rs::WINDOWS_1252);
+        assert_eq!(parse("Shift_JIS").unwrap(), encoding_rs::SHIFT_JIS);
+        assert_eq!(parse("klingon").unwrap_err(), "`klingon` is not a known encoding");
+    }
+
+    #[test]
+    fn inputs_are_decoded_to_utf8() {
+        assert_eq!(decode(b"\xff\xfeh\x00\xe9\x00\n\x00", UTF_16LE, true), "h\u{e9}\n");
+        assert_eq!(decode(b"\xff\xfeh\x00", UTF_16LE, false), "\u{feff}h");
+        assert_eq!(decode(b"caf\xe9", encoding_rs::WINDOWS_1252, true), "caf\u{e9}");
+        assert_eq!(decode(b"\x82\xa0", encoding_rs::SHIFT_JIS, true), "\u{3042}");
+        // the odd byte left at the end is not a whole character
+        assert_eq!(decode(b"a\x00b", UTF_16LE, true), "a\u{fffd}");
+    }
+}
@@ -0,0 +1,155 @@
+//! Checking the line endings of the files, for `--check-line-endings`, and converting them for `--lf` and
+//! `--crlf`.
+//!
+//! Every line read is looked at as it is, before anything else happens to it, and the LF, CRLF and bare CR
+//! endings of the file are counted, along with the first line each of them shows up on. Once the file has been
+//! printed, a file mixing LF and CRLF, or having bare CRs, which an editor or a diff may show as a single line,
+//! gets a warning on the standard error. The output is left as it is.
+//!
+//! The conversion happens on the lines as they are read, terminators included, so it is exact: the carriage
+//! return before the line feed of a CRLF ending is dropped, and printed back before every line feed with
+//! `--crlf`. The bare CRs are left alone, and so is the last line of a file without a newline, which does not get
+//! one.
+
+/// `LineEnding` enum decides the line endings of the output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// The lines end the way they do in the files.
+    #[default]
+    Keep,
+    /// The lines end with a line feed alone, like on Unix.
+    Lf,
+    /// The lines end with a carriage return and a line feed, like on Windows.
+    Crlf,
+}
+
+impl LineEnding {
+    /// Returns `true` if the line endings are converted.
+    pub fn is_active(self) -> bool {
+        self != LineEnding::Keep
+    }
+
+    /// Returns the content of a line that ends with a line feed, without the carriage return of its ending if
+    /// the endings are converted.
+    pub fn strip(self, content: &[u8]) -> &[u8] {
+        match self {
+            LineEnding::Keep => content,
+            LineEnding::Lf | LineEnding::Crlf => content.strip_suffix(b"\r").unwrap_or(content),
+        }
+    }
+
+    /// Returns what is printed at the end of a line that ends with a line feed, after what `strip` leaves of it.
+    pub fn newline(self) -> &'static [u8] {
+        match self {
+            LineEnding::Keep | LineEnding::Lf => b"\n",
+            LineEnding::Crlf => b"\r\n",
+        }
+    }
+}
+
+/// `Ending` struct is the count of one kind of line ending in a file.
+///
+/// # Fields
+///
+/// * `count`: How many lines end with it.
+/// * `first`: The number of the first line ending with it, `None` if there is none.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Ending {
+    count: usize,
+    first: Option<usize>,
+}
+
+impl Ending {
+    fn add(&mut self, line_number: usize) {
+        self.count += 1;
+        self.first.get_or_insert(line_number);
+    }
+}
+
+/// `LineEndings` struct counts the line endings of a file fed to it one line at a time.
+///
+/// # Fields
+///
+/// * `lf`: The lines ending with a line feed alone.
+/// * `crlf`: The lines ending with a carriage return and a line feed.
+/// * `cr`: The carriage returns followed by anything but a line feed, which end a line on old Macs.
+#[derive(Debug, Default)]
+pub struct LineEndings {
+    lf: Ending,
+    crlf: Ending,
+    cr: Ending,
+}
+
+impl LineEndings {
+    /// Counts the endings of a line, given with its terminator.
+    pub fn update(&mut self, line_number: usize, line: &[u8]) {
+        let content = match line.strip_suffix(b"\n") {
+            Some(content) => match content.strip_suffix(b"\r") {
+                Some(content) => {
+                    self.crlf.add(line_number);
+                    content
+                }
+                None => {
+                    self.lf.add(line_number);
+                    content
+                }
+            },
+            None => line,
+        };
+        for _ in content.iter().filter(|&&byte| byte == b'\r') {
+            self.cr.add(line_number);
+        }
+    }
+
+    /// Returns the warning about the endings counted so far, `None` if they are consistent.
+    pub fn warning(&self, filename: &str) -> Option<String> {
+        if self.cr.count == 0 && (self.lf.count == 0 || self.crlf.count == 0) {
+            return None;
+        }
+        let counts: Vec<String> = [("LF", self.lf), ("CRLF", self.crlf), ("CR", self.cr)]
+            .into_iter()
+            .filter_map(|(name, ending)| {
+                ending.first.map(|first| format!("{} {} (first on line {})", ending.count, name, first))
+            })
+            .collect();
+        Some(format!("{}: inconsistent line endings: {}", filename, counts.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts the endings of the lines, numbered from 1, and returns the warning about them.
+    fn warning(lines: &[&[u8]]) -> Option<String> {
+        let mut endings = LineEndings::default();
+        for (index, line) in lines.iter().enumerate() {
+            endings.update(index + 1, line);
+        }
+        endings.warning("file")
+    }
+
+    #[test]
+    fn consistent_endings_get_no_warning() {
+        assert_eq!(warning(&[b"a\n", b"b\n", b"no newline"]), None);
+        assert_eq!(warning(&[b"a\r\n", b"b\r\n"]), None);
+        assert_eq!(warning(&[]), None);
+    }
+
+    #[test]
+    fn mixed_and_bare_endings_are_counted_with_their_first_line() {
+        let expected = "file: inconsistent line endings: 1 LF (first on line 2), 2 CRLF (first on line 1)";
+        assert_eq!(warning(&[b"a\r\n", b"b\n", b"c\r\n"]).as_deref(), Some(expected));
+        let expected = "file: inconsistent line endings: 2 LF (first on line 1), 3 CR (first on line 1)";
+        assert_eq!(warning(&[b"a\rb\n", b"c\n", b"d\re\r"]).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn converted_lines_lose_their_carriage_return() {
+        assert_eq!(LineEnding::Keep.strip(b"a\r"), b"a\r");
+        assert_eq!(LineEnding::Lf.strip(b"a\r"), b"a");
+        assert_eq!(LineEnding::Crlf.strip(b"a"), b"a");
+        assert_eq!(LineEnding::Lf.newline(), b"\n");
+        assert_eq!(LineEnding::Crlf.newline(), b"\r\n");
+    }
+}
@@ -0,0 +1,166 @@
+//! The error type of the library.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+/// `MinicatError` enum lists everything that can go wrong while concatenating files.
+///
+/// `OpenFailed`, `ReadFailed`, `InputIsOutput`, `InvalidData`, `ChecksumMismatch` and `ChecksumMissing` only
+/// concern a single file: they are reported as they happen and the remaining files are still printed, after which
+/// `FilesFailed` is returned. All the other variants stop the program right away.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MinicatError {
+    /// A file could not be opened.
+    OpenFailed { path: String, source: io::Error },
+    /// Reading a file failed, `line` is the line being read if the file was read line by line.
+    ReadFailed { path: String, line: Option<usize>, source: io::Error },
+    /// The input file is the file the output is written into, so printing it would never end.
+    InputIsOutput { path: String },
+    /// The data of a file cannot be converted, like malformed base64 being decoded. The message tells what is
+    /// wrong with it and where.
+    InvalidData { path: String, message: String },
+    /// The checksum of a printed file differs from the one listed in the sums file of `--verify`.
+    ChecksumMismatch { path: String, algorithm: &'static str, expected: String, actual: String },
+    /// A printed file is not listed in the sums file `sums` of `--verify`.
+    ChecksumMissing { path: String, sums: String },
+    /// A line is not valid UTF-8 while the `Utf8Mode::Strict` mode is used.
+    InvalidUtf8 { path: String, line: usize, source: Utf8Error },
+    /// Copying a file straight to the output failed. The kernel does not tell whether it was the reading
+    /// or the writing side, so this stops the program like a write error would.
+    CopyFailed { path: String, source: io::Error },
+    /// Writing the output failed.
+    WriteFailed(io::Error),
+    /// The output could not be placed on the clipboard, the message telling why.
+    ClipboardFailed(String),
+    /// The given number of files could not be printed, each of them has been reported already, so the program
+    /// exits without a message of its own.
+    FilesFailed(usize),
+    /// The program was interrupted before it could finish.
+    Interrupted,
+}
+
+impl MinicatError {
+    /// Returns `true` if the error only concerns a single file, so the rest of the files can still be printed.
+    pub fn is_file_error(&self) -> bool {
+        matches!(
+            self,
+            MinicatError::OpenFailed { .. }
+                | MinicatError::ReadFailed { .. }
+                | MinicatError::InputIsOutput { .. }
+                | MinicatError::InvalidData { .. }
+                | MinicatError::ChecksumMismatch { .. }
+                | MinicatError::ChecksumMissing { .. }
+        )
+    }
+
+    /// Returns `true` if the error comes from writing into a pipe whose reading end has been closed,
+    /// like in `minicat file | head`, which is not a failure worth reporting.
+    pub fn is_broken_pipe(&self) -> bool {
+        match self {
+            MinicatError::WriteFailed(source) | MinicatError::CopyFailed { source, .. } => {
+                source.kind() == io::ErrorKind::BrokenPipe
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for MinicatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinicatError::OpenFailed { path, source } => write!(f, "{}: {}", path, describe(source)),
+            MinicatError::ReadFailed { path, line: Some(line), source } => {
+                write!(f, "{}: line {}: {}", path, line, describe(source))
+            }
+            MinicatError::ReadFailed { path, line: None, source } => write!(f, "{}: {}", path, describe(source)),
+            MinicatError::InputIsOutput { path } => write!(f, "{}: input file is output file", path),
+            MinicatError::InvalidData { path, message } => write!(f, "{}: {}", path, message),
+            MinicatError::ChecksumMismatch { path, algorithm, expected, actual } => {
+                write!(f, "{}: {} checksum mismatch: expected {}, got {}", path, algorithm, expected, actual)
+            }
+            MinicatError::ChecksumMissing { path, sums } => write!(f, "{}: no checksum listed in {}", path, sums),
+            MinicatError::InvalidUtf8 { path, line, source } => {
+                write!(f, "{}: line {}: invalid UTF-8: {}", path, line, source)
+            }
+            MinicatError::CopyFailed { path, source } => write!(f, "{}: {}", path, describe(source)),
+            MinicatError::WriteFailed(source) => write!(f, "write error: {}", describe(source)),
+            MinicatError::ClipboardFailed(message) => write!(f, "clipboard: {}", message),
+            MinicatError::FilesFailed(1) => write!(f, "1 file could not be read"),
+            MinicatError::FilesFailed(failed) => write!(f, "{} files could not be read", failed),
+            MinicatError::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+/// Describes an I/O error the way coreutils do, `No such file or directory` rather than
+/// `No such file or directory (os error 2)`.
+fn describe(error: &io::Error) -> String {
+    let message = error.to_string();
+    match error.raw_os_error() {
+        Some(code) => {
+            let suffix = format!(" (os error {})", code);
+            message.strip_suffix(&suffix).map(str::to_string).unwrap_or(message)
+        }
+        None => message,
+    }
+}
+
+impl Error for MinicatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MinicatError::OpenFailed { source, .. }
+            | MinicatError::ReadFailed { source, .. }
+            | MinicatError::CopyFailed { source, .. }
+            | MinicatError::WriteFailed(source) => Some(source),
+            MinicatError::InvalidUtf8 { source, .. } => Some(source),
+            MinicatError::InputIsOutput { .. }
+            | MinicatError::InvalidData { .. }
+            | MinicatError::ChecksumMismatch { .. }
+            | MinicatError::ChecksumMissing { .. }
+            | MinicatError::ClipboardFailed(_)
+            | MinicatError::FilesFailed(_)
+            | MinicatError::Interrupted => None,
+        }
+    }
+}
+
+/// Plain I/O errors come from writing the output, reading errors are always mapped to `ReadFailed`
+/// explicitly since they need the path.
+impl From<io::Error> for MinicatError {
+    fn from(error: io::Error) -> Self {
+        MinicatError::WriteFailed(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_leaves_the_os_error_code_out() {
+        let error = MinicatError::OpenFailed { path: "a".to_string(), source: io::Error::from_raw_os_error(2) };
+        assert_eq!(error.to_string(), "a: No such file or directory");
+        let source = io::Error::other("bad sector");
+        let error = MinicatError::ReadFailed { path: "a".to_string(), line: Some(3), source };
+        assert_eq!(error.to_string(), "a: line 3: bad sector");
+        assert_eq!(MinicatError::FilesFailed(1).to_string(), "1 file could not be read");
+        assert_eq!(MinicatError::FilesFailed(2).to_string(), "2 files could not be read");
+    }
+
+    #[test]
+    fn only_the_errors_of_a_file_let_the_others_be_printed() {
+        assert!(MinicatError::InputIsOutput { path: "a".to_string() }.is_file_error());
+        assert!(!MinicatError::from(io::Error::other("disk full")).is_file_error());
+        assert!(!MinicatError::Interrupted.is_file_error());
+    }
+
+    #[test]
+    fn broken_pipes_are_recognized() {
+        assert!(MinicatError::from(io::Error::from(io::ErrorKind::BrokenPipe)).is_broken_pipe());
+        let source = io::Error::from(io::ErrorKind::BrokenPipe);
+        assert!(!MinicatError::ReadFailed { path: "a".to_string(), line: None, source }.is_broken_pipe());
+    }
+}
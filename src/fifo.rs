@@ -0,0 +1,95 @@
+//! Opening named pipes (FIFOs), which blocks until something opens them for writing.
+//!
+//! Without a writer, opening a FIFO would hang with nothing telling why, so a FIFO is opened on a separate
+//! thread. If it is not open after a short while, minicat says on the standard error that it is waiting for a
+//! writer, gives up after `--open-timeout`, or right away with `--nonblocking`. A FIFO given up on is an error
+//! of the file, reported and skipped or stopping everything depending on `--fail-fast`. The thread still
+//! waiting on it is then released by opening the FIFO for writing, and closing it at once.
+
+use std::fs::Metadata;
+use std::io;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use crate::io_backend::InputFile;
+
+/// How long the FIFO is given to open before it is taken for one without a writer.
+const GRACE: Duration = Duration::from_millis(50);
+
+/// `FifoWait` enum decides how long to wait for a writer on a FIFO.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FifoWait {
+    /// The FIFO is waited on for as long as it takes, after a notice on the standard error.
+    #[default]
+    Forever,
+    /// The FIFO is given up on if it has no writer after the given duration.
+    Timeout(Duration),
+    /// The FIFO is given up on if it has no writer already.
+    Never,
+}
+
+/// Returns `true` if the metadata is the one of a FIFO.
+#[cfg(unix)]
+pub fn is_fifo(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    metadata.file_type().is_fifo()
+}
+
+/// Returns `false`, there are no named pipes in the file system on this platform.
+#[cfg(not(unix))]
+pub fn is_fifo(_metadata: &Metadata) -> bool {
+    false
+}
+
+/// Opens a FIFO for reading, waiting for a writer as `wait` tells.
+///
+/// ## Parameters
+/// * `path` - The path of the FIFO.
+/// * `wait` - The `FifoWait` deciding how long to wait for a writer.
+/// * `drop_cache` - Whether the page cache hint is given when the file is dropped, see `InputFile`.
+///
+/// # Errors
+/// The function will return an error of kind `io::ErrorKind::TimedOut` if there is no writer in time, and the
+/// error of `InputFile::open` if opening the FIFO fails.
+pub fn open(path: &str, wait: FifoWait, drop_cache: bool) -> io::Result<InputFile> {
+    let (sender, receiver) = mpsc::channel();
+    let owned = path.to_string();
+    thread::spawn(move || {
+        // the receiver is gone if the FIFO was given up on, the file is closed right away then
+        let _ = sender.send(InputFile::open(owned, drop_cache));
+    });
+    let received = match receiver.recv_timeout(GRACE) {
+        Err(RecvTimeoutError::Timeout) => match wait {
+            FifoWait::Forever => {
+                eprintln!("minicat: {}: waiting for a writer on the FIFO", path);
+                receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            }
+            FifoWait::Timeout(timeout) => receiver.recv_timeout(timeout.saturating_sub(GRACE)),
+            FifoWait::Never => Err(RecvTimeoutError::Timeout),
+        },
+        received => received,
+    };
+    match received {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            release(path);
+            Err(io::Error::new(io::ErrorKind::TimedOut, "no writer on the FIFO"))
+        }
+        Err(RecvTimeoutError::Disconnected) => Err(io::Error::other("the FIFO could not be opened")),
+    }
+}
+
+/// Opens the FIFO for writing without blocking, and closes it, so that the thread waiting to open it for
+/// reading gets it and ends. It fails if the thread got it in the meantime and closed it, which is fine.
+#[cfg(unix)]
+fn release(path: &str) {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let _ = std::fs::OpenOptions::new().write(true).custom_flags(libc::O_NONBLOCK).open(path);
+}
+
+/// Does nothing, there are no named pipes in the file system on this platform.
+#[cfg(not(unix))]
+fn release(_path: &str) {}
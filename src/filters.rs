@@ -0,0 +1,310 @@
+//! Selecting the lines to print and rewriting them with regular expressions.
+//!
+//! The patterns need the `regex` crate, which is only pulled in with the `regex` feature. Without it the
+//! options are not offered and every line is printed as it is.
+
+use std::borrow::Cow;
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "regex")]
+use clap::{Arg, ArgAction};
+#[cfg(feature = "regex")]
+use regex::bytes::{Regex, RegexSet};
+
+/// `Filters` struct decides which lines are printed, before they are numbered and rendered.
+///
+/// # Fields
+///
+/// * `include`: The pattern a line has to match to be printed, `None` to print every line.
+/// * `exclude`: The patterns none of which a line may match to be printed, all matched in a single pass.
+/// * `substitution`: The substitution applied to the printed lines, `None` to print them as they are.
+#[derive(Debug, Default)]
+pub struct Filters {
+    #[cfg(feature = "regex")]
+    include: Option<Regex>,
+    #[cfg(feature = "regex")]
+    exclude: Option<RegexSet>,
+    #[cfg(feature = "regex")]
+    substitution: Option<Substitution>,
+}
+
+/// `Substitution` struct is a sed-like `s/PATTERN/REPLACEMENT/` command.
+///
+/// # Fields
+///
+/// * `pattern`: The pattern to replace.
+/// * `replacement`: What the matches are replaced with, in the syntax of `regex::bytes::Regex::replace`.
+/// * `global`: Whether every match of a line is replaced, or only the first one.
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+struct Substitution {
+    pattern: Regex,
+    replacement: String,
+    global: bool,
+}
+
+impl Filters {
+    /// Builds the filters from the options added by `args`.
+    ///
+    /// The patterns have been checked one by one while parsing, so this only exits with a usage error if
+    /// the `--exclude` patterns are too big to be compiled together.
+    #[cfg(feature = "regex")]
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let exclude = matches.get_many::<String>("exclude").map(|patterns| {
+            RegexSet::new(patterns).unwrap_or_else(|e| {
+                clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+            })
+        });
+        Filters {
+            include: matches.get_one::<Regex>("match").cloned(),
+            exclude,
+            substitution: matches.get_one::<Substitution>("replace").cloned(),
+        }
+    }
+
+    /// Builds the filters from the options added by `args`, of which there are none without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn from_matches(_matches: &ArgMatches) -> Self {
+        Filters::default()
+    }
+
+    /// Returns `true` if some lines may be left out or rewritten.
+    #[cfg(feature = "regex")]
+    pub fn is_active(&self) -> bool {
+        self.include.is_some() || self.exclude.is_some() || self.substitution.is_some()
+    }
+
+    /// Returns `true` if some lines may be left out or rewritten, which never happens without the `regex`
+    /// feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn is_active(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if the line, without its terminator, is to be printed.
+    #[cfg(feature = "regex")]
+    pub fn keep(&self, line: &[u8]) -> bool {
+        self.include.as_ref().is_none_or(|include| include.is_match(line))
+            && self.exclude.as_ref().is_none_or(|exclude| !exclude.is_match(line))
+    }
+
+    /// Returns `true` if the line is to be printed, which is always the case without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn keep(&self, _line: &[u8]) -> bool {
+        true
+    }
+
+    /// Applies the substitution to a line without its terminator.
+    #[cfg(feature = "regex")]
+    pub fn rewrite<'l>(&self, line: &'l [u8]) -> Cow<'l, [u8]> {
+        match &self.substitution {
+            Some(Substitution { pattern, replacement, global: true }) => {
+                pattern.replace_all(line, replacement.as_bytes())
+            }
+            Some(Substitution { pattern, replacement, global: false }) => pattern.replace(line, replacement.as_bytes()),
+            None => Cow::Borrowed(line),
+        }
+    }
+
+    /// Returns the line as it is, there is no substitution without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn rewrite<'l>(&self, line: &'l [u8]) -> Cow<'l, [u8]> {
+        Cow::Borrowed(line)
+    }
+}
+
+/// Adds the filtering options to the command, if the `regex` feature is enabled.
+///
+/// * `match` ('--match'): this option will print only the lines matching the pattern.
+/// * `exclude` ('--exclude'): this option will drop the lines matching the pattern, it can be given several times.
+/// * `replace` ('--replace'): this option will apply a sed-like `s/PATTERN/REPLACEMENT/` substitution to every
+///   printed line.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "regex")]
+    let command = command
+        .arg(Arg::new("match")
+            .action(ArgAction::Set)
+            .long("match")
+            .value_name("PATTERN")
+            .value_parser(|value: &str| Regex::new(value).map_err(|e| e.to_string()))
+            .help("Print only the lines matching the regular expression PATTERN"))
+        .arg(Arg::new("exclude")
+            .action(ArgAction::Append)
+            .long("exclude")
+            .value_name("PATTERN")
+            .value_parser(|value: &str| match Regex::new(value) {
+                Ok(_) => Ok(value.to_string()),
+                Err(e) => Err(e.to_string()),
+            })
+            .help("Drop the lines matching the regular expression PATTERN, may be repeated"))
+        .arg(Arg::new("replace")
+            .action(ArgAction::Set)
+            .long("replace")
+            .value_name("s/PATTERN/REPLACEMENT/")
+            .value_parser(parse_substitution)
+            .help("Replace the first match of PATTERN in every line, or all of them with a trailing g"));
+    command
+}
+
+/// Parses a sed-like substitution, `s/PATTERN/REPLACEMENT/` or `s/PATTERN/REPLACEMENT/g` to replace every match
+/// rather than the first one.
+///
+/// Any other character than `/` may be used as the delimiter, and written escaped with a backslash inside the
+/// command. The replacement refers to the capture groups either the sed way, `\1` and `&` for the whole match, or
+/// the way of the `regex` crate, `$1` or `${name}`, see `sed_groups`.
+///
+/// # Errors
+/// The function will return an error if the command is malformed or the pattern is not a valid regular expression.
+#[cfg(feature = "regex")]
+fn parse_substitution(value: &str) -> Result<Substitution, String> {
+    let invalid = || format!("`{}` is not a valid substitution, expected s/PATTERN/REPLACEMENT/", value);
+    let mut chars = value.chars();
+    if chars.next() != Some('s') {
+        return Err(invalid());
+    }
+    let delimiter = chars.next().filter(|&c| !c.is_alphanumeric() && c != '\\').ok_or_else(invalid)?;
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in chars {
+        let part = parts.last_mut().expect("there is always a part");
+        if escaped {
+            // an escaped delimiter is the delimiter itself, any other escape is left to the regular expression
+            if c != delimiter {
+                part.push('\\');
+            }
+            part.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            parts.push(String::new());
+        } else {
+            part.push(c);
+        }
+    }
+    let [pattern, replacement, flags] = <[String; 3]>::try_from(parts).map_err(|_| invalid())?;
+    let global = match flags.as_str() {
+        "" => false,
+        "g" => true,
+        _ => return Err(format!("unknown substitution flag `{}`, only `g` is supported", flags)),
+    };
+    let pattern = Regex::new(&pattern).map_err(|e| e.to_string())?;
+
+    Ok(Substitution { pattern, replacement: sed_groups(&replacement), global })
+}
+
+/// Rewrites a sed-style replacement into the syntax of the `regex` crate, in a single pass.
+///
+/// `&` and `\0` are the whole match, `\1` to `\9` the capture groups, and `\&` and `\\` a literal `&` and
+/// backslash. The `$1` and `${name}` references of the `regex` crate are kept, with the number of `$1` ending
+/// at its last digit, and any other `$` is a literal one.
+#[cfg(feature = "regex")]
+fn sed_groups(replacement: &str) -> String {
+    let mut rewritten = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    rewritten.push_str("${");
+                    rewritten.push(digit);
+                    rewritten.push('}');
+                }
+                Some('&') => rewritten.push('&'),
+                Some('\\') => rewritten.push('\\'),
+                Some('$') => rewritten.push_str("$$"),
+                Some(other) => {
+                    rewritten.push('\\');
+                    rewritten.push(other);
+                }
+                None => rewritten.push('\\'),
+            },
+            '&' => rewritten.push_str("${0}"),
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                rewritten.push_str("${");
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    rewritten.push(digit);
+                }
+                rewritten.push('}');
+            }
+            '$' if chars.peek() == Some(&'{') && chars.clone().any(|c| c == '}') => {
+                rewritten.push('$');
+                for c in chars.by_ref() {
+                    rewritten.push(c);
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            '$' => rewritten.push_str("$$"),
+            _ => rewritten.push(c),
+        }
+    }
+
+    rewritten
+}
+
+#[cfg(all(test, feature = "regex"))]
+mod tests {
+    use super::*;
+
+    /// Returns a line rewritten by the substitution `command`.
+    fn rewrite(command: &str, line: &str) -> String {
+        let filters = Filters { substitution: Some(parse_substitution(command).unwrap()), ..Filters::default() };
+        String::from_utf8(filters.rewrite(line.as_bytes()).into_owned()).unwrap()
+    }
+
+    #[test]
+    fn substitution_replaces_the_first_or_every_match() {
+        assert_eq!(rewrite("s/an/AN/", "banana"), "bANana");
+        assert_eq!(rewrite("s/an/AN/g", "banana"), "bANANa");
+        assert_eq!(rewrite("s/x/y/", "banana"), "banana");
+    }
+
+    #[test]
+    fn substitution_takes_any_delimiter_and_escapes() {
+        assert_eq!(rewrite("s|/|-|g", "a/b/c"), "a-b-c");
+        assert_eq!(rewrite(r"s/\//-/g", "a/b/c"), "a-b-c");
+        assert_eq!(rewrite(r"s/\d+/N/g", "a1b22"), "aNbN");
+    }
+
+    #[test]
+    fn substitution_refers_to_the_groups_both_ways() {
+        assert_eq!(rewrite(r"s/(\w+)=(\w+)/\2=\1/", "key=value"), "value=key");
+        assert_eq!(rewrite("s/(?<k>\\w+)=(\\w+)/${k}:$2/", "key=value"), "key:value");
+        assert_eq!(rewrite(r"s/(a)/$1b/", "a"), "ab");
+        assert_eq!(sed_groups(r"\1x\9"), "${1}x${9}");
+    }
+
+    #[test]
+    fn substitution_writes_the_whole_match_for_an_ampersand() {
+        assert_eq!(rewrite("s/an/[&]/g", "banana"), "b[an][an]a");
+        assert_eq!(rewrite(r"s/an/\0\0/", "banana"), "bananana");
+        assert_eq!(rewrite(r"s/an/\&/", "banana"), "b&ana");
+    }
+
+    #[test]
+    fn substitution_unescapes_the_backslashes() {
+        assert_eq!(rewrite(r"s/a/\\/", "a"), r"\");
+        assert_eq!(rewrite(r"s/(a)/\\1/", "a"), r"\1");
+        assert_eq!(rewrite(r"s/a/\x/", "a"), r"\x");
+    }
+
+    #[test]
+    fn substitution_keeps_the_other_dollars() {
+        assert_eq!(rewrite("s/a/$x/", "a"), "$x");
+        assert_eq!(rewrite("s/a/${x/", "a"), "${x");
+        assert_eq!(rewrite(r"s/a/\$1/", "a"), "$1");
+        assert_eq!(rewrite("s/a/5$/", "a"), "5$");
+    }
+
+    #[test]
+    fn malformed_substitutions_are_rejected() {
+        assert!(parse_substitution("y/a/b/").is_err());
+        assert!(parse_substitution("s/a/b").is_err());
+        assert!(parse_substitution("s/a/b/c/").is_err());
+        assert!(parse_substitution("sxaxbx").is_err());
+        assert!(parse_substitution("s/a/b/i").is_err());
+        assert!(parse_substitution("s/(/b/").is_err());
+    }
+}
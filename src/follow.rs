@@ -0,0 +1,377 @@
+//! Following files as they grow, like `tail -f`.
+//!
+//! Once a file has been printed, it is kept open and polled for data appended to it, until the
+//! program is interrupted with Ctrl+C. Several files are polled in turn and their output is interleaved,
+//! with a `==> name <==` banner whenever it switches from one file to another. Every file has a `FileState` of its
+//! own, so the numbering, highlighting and the rest of what is carried from one line to the next is not mixed up
+//! between them. The interrupt is caught so that the output is flushed and the program exits cleanly instead of
+//! being killed halfway through a line.
+//!
+//! When following by name (`-F`), the path is also checked after every poll. A file that has been truncated,
+//! or replaced by another one like log rotation does, is reopened and printed from its start, and a notice
+//! is written to the standard error. A file that does not exist yet when the program starts is waited for the
+//! same way, and printed once it appears.
+
+use std::fs;
+use std::io;
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::thread;
+use std::time::Duration;
+
+use crate::io_backend::{self, FileId};
+use crate::{open_file, FileState, FollowMode, LinePrinter, MinicatError};
+
+/// How long to wait before looking for new data once the end of the file has been reached.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Set by the Ctrl+C handler, checked by the follow loop between two polls.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the Ctrl+C handler, at most once per process.
+///
+/// If another handler has been installed already, the default action is kept and Ctrl+C simply
+/// kills the program.
+fn catch_interrupts() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let _ = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst));
+    });
+}
+
+/// `Follower` struct reads a file that is still being written to, one poll at a time.
+///
+/// # Fields
+///
+/// * `filename`: The name of the file being followed, used in error messages.
+/// * `reader`: The open file. Reading past its end again picks up the data appended in the meantime.
+/// * `pending`: The start of a line whose newline has not been written yet. It is kept back when lines
+///   are processed, so a line written in two goes is still numbered and rendered as one.
+/// * `id`: The identity of the open file, compared with the one of the path to notice it being replaced.
+/// * `position`: The number of bytes read from the open file, compared with the size of the path to notice
+///   it being truncated.
+/// * `missing`: Whether the path has disappeared, so that it is only reported once.
+/// * `state`: The `FileState` of the `LinePrinter` for this file, kept here while another file is being printed.
+struct Follower<'a> {
+    filename: String,
+    reader: Box<dyn BufRead>,
+    pending: Vec<u8>,
+    id: Option<FileId>,
+    position: u64,
+    missing: bool,
+    state: FileState<'a>,
+}
+
+impl<'a> Follower<'a> {
+    /// Opens a file, taking its byte order mark off the way the `LinePrinter` does for the file being printed.
+    fn open(filename: &str, printer: &LinePrinter) -> Result<Self, MinicatError> {
+        let reader = open_file(filename, printer.config, printer.file.strip_bom)
+            .map_err(|source| MinicatError::OpenFailed { path: filename.to_string(), source })?;
+        let id = io_backend::input_id(filename);
+        Ok(Follower { reader, id, missing: false, ..Follower::missing(filename, printer) })
+    }
+
+    /// Returns the follower of a path that does not lead to a file yet, which is looked for again at every poll.
+    fn missing(filename: &str, printer: &LinePrinter) -> Self {
+        Follower {
+            filename: filename.to_string(),
+            reader: Box::new(io::empty()),
+            pending: Vec::new(),
+            id: None,
+            position: 0,
+            missing: true,
+            state: FileState { strip_bom: printer.file.strip_bom, ..FileState::default() },
+        }
+    }
+
+    /// Returns `true` if there is data to print, or an error to report, without consuming anything.
+    fn has_data(&mut self) -> bool {
+        !matches!(self.reader.fill_buf(), Ok([]))
+    }
+
+    /// Prints everything that has been appended to the file since the last poll, and flushes `out`.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::ReadFailed` if reading fails, or the error of
+    /// `LinePrinter::print_line`.
+    fn poll<W: Write>(&mut self, printer: &mut LinePrinter<'a>, out: &mut W) -> Result<(), MinicatError> {
+        let transforms_lines = printer.config.transforms_lines();
+        loop {
+            let chunk = match self.reader.fill_buf() {
+                Ok([]) => break,
+                Ok(chunk) => chunk,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(source) => {
+                    let line = transforms_lines.then_some(printer.file.lines_read + 1);
+                    return Err(MinicatError::ReadFailed { path: self.filename.clone(), line, source });
+                }
+            };
+            let length = chunk.len();
+            // past `--max-lines`, the appended data is skipped rather than printed
+            if transforms_lines && printer.file_done() {
+                self.reader.consume(length);
+                self.position += length as u64;
+                continue;
+            }
+            if !transforms_lines {
+                out.write_all(chunk)?;
+                self.reader.consume(length);
+                self.position += length as u64;
+                continue;
+            }
+            let length = match memchr::memchr(b'\n', chunk) {
+                Some(end) => {
+                    self.pending.extend_from_slice(&chunk[..=end]);
+                    printer.print_line(&self.filename, &self.pending, out)?;
+                    self.pending.clear();
+                    end + 1
+                }
+                None => {
+                    self.pending.extend_from_slice(chunk);
+                    length
+                }
+            };
+            self.reader.consume(length);
+            self.position += length as u64;
+        }
+        out.flush()?;
+
+        Ok(())
+    }
+
+    /// Checks whether the path still leads to the open file, and reopens it if the file has been truncated or
+    /// replaced, after printing what was kept back of the old one.
+    ///
+    /// A path that has disappeared is reported once and looked for again at the next poll, the same as a
+    /// new file that cannot be opened yet. The line counters go on where the old file left them.
+    ///
+    /// ## Returns
+    /// `true` if the file has been reopened, so that it is polled again right away.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails.
+    fn reopen_if_rotated<W: Write>(
+        &mut self,
+        printer: &mut LinePrinter<'a>,
+        out: &mut W,
+    ) -> Result<bool, MinicatError> {
+        let metadata = match fs::metadata(&self.filename) {
+            Ok(metadata) => metadata,
+            Err(_) => {
+                if !self.missing {
+                    self.missing = true;
+                    self.finish(printer, out)?;
+                    eprintln!("minicat: {}: file has become inaccessible", self.filename);
+                }
+                return Ok(false);
+            }
+        };
+        let notice = if self.missing {
+            "file has appeared, following it"
+        } else if io_backend::file_id(&metadata) != self.id {
+            "file has been replaced, following the new file"
+        } else if metadata.len() < self.position {
+            "file truncated"
+        } else {
+            return Ok(false);
+        };
+        let Ok(reopened) = Follower::open(&self.filename, printer) else {
+            self.missing = true;
+            return Ok(false);
+        };
+        self.finish(printer, out)?;
+        eprintln!("minicat: {}: {}", self.filename, notice);
+        self.reader = reopened.reader;
+        self.id = reopened.id;
+        self.position = 0;
+        self.missing = false;
+
+        Ok(true)
+    }
+
+    /// Prints the line still kept back, without a newline since it has not got one yet.
+    fn finish<W: Write>(&mut self, printer: &mut LinePrinter<'a>, out: &mut W) -> Result<(), MinicatError> {
+        if !self.pending.is_empty() {
+            printer.print_line(&self.filename, &self.pending, out)?;
+            self.pending.clear();
+        }
+        out.flush()?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if something is still owed once following ends: the line kept back, or the count of the
+    /// last run of repeated lines.
+    fn owes_output(&self, printer: &LinePrinter) -> bool {
+        !self.pending.is_empty() || (printer.config.count_repeats && self.state.repeats > 1)
+    }
+
+    /// Reports what is wrong with the line endings of the file, once following it ends.
+    fn end(&mut self, printer: &mut LinePrinter<'a>) {
+        printer.swap_file(&mut self.state);
+        printer.end_file(&self.filename);
+        printer.swap_file(&mut self.state);
+    }
+}
+
+/// `Followers` struct multiplexes the files being followed into a single output.
+///
+/// # Fields
+///
+/// * `followers`: The files being followed, in the order they were given.
+/// * `mode`: Whether the files are followed by descriptor or by name.
+/// * `banners`: Whether to print a `==> name <==` banner when the output switches to another file, which is
+///   the case as soon as more than one file is followed.
+/// * `current`: The index of the file printed last, `None` before anything has been printed.
+pub struct Followers<'a> {
+    followers: Vec<Follower<'a>>,
+    mode: FollowMode,
+    banners: bool,
+    current: Option<usize>,
+}
+
+impl<'a> Followers<'a> {
+    pub fn new(mode: FollowMode, banners: bool) -> Self {
+        Followers { followers: Vec::new(), mode, banners, current: None }
+    }
+
+    /// Returns `true` if no file is being followed.
+    pub fn is_empty(&self) -> bool {
+        self.followers.is_empty()
+    }
+
+    /// Opens a file, prints what it contains so far and adds it to the files being followed. The file is printed
+    /// with a `FileState` of its own, the one of the `LinePrinter` starts over for the files printed next.
+    ///
+    /// When following by name, a file that does not exist is reported and added all the same, to be printed once
+    /// it appears.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+    /// opened or read, and any other `MinicatError` if writing to `out` fails.
+    pub fn add<W: Write>(
+        &mut self,
+        filename: &str,
+        printer: &mut LinePrinter<'a>,
+        out: &mut W,
+    ) -> Result<(), MinicatError> {
+        printer.start_file();
+        let follower = match Follower::open(filename, printer) {
+            Err(MinicatError::OpenFailed { source, .. })
+                if self.mode == FollowMode::Name && source.kind() == io::ErrorKind::NotFound =>
+            {
+                eprintln!("minicat: {}: {}, waiting for it to appear", filename, source);
+                Follower::missing(filename, printer)
+            }
+            result => result?,
+        };
+        self.followers.push(follower);
+        self.poll(self.followers.len() - 1, printer, out)
+    }
+
+    /// Polls every file in turn and prints what is appended to them until the program is interrupted.
+    ///
+    /// # Errors
+    /// The function returns `MinicatError::Interrupted` once Ctrl+C has been pressed and the output has been
+    /// flushed, `MinicatError::ReadFailed` if a file cannot be read, and any other `MinicatError` if writing
+    /// to `out` fails.
+    pub fn run<W: Write>(mut self, printer: &mut LinePrinter<'a>, out: &mut W) -> Result<(), MinicatError> {
+        catch_interrupts();
+        while !INTERRUPTED.load(Ordering::SeqCst) {
+            let mut reopened = false;
+            for index in 0..self.followers.len() {
+                self.poll(index, printer, out)?;
+                if self.mode == FollowMode::Name {
+                    reopened |= self.switch_to(index, printer, out, |follower, printer, out| {
+                        follower.reopen_if_rotated(printer, out)
+                    })?;
+                }
+            }
+            if !reopened {
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        for index in 0..self.followers.len() {
+            if self.followers[index].owes_output(printer) {
+                self.switch_to(index, printer, out, |follower, printer, out| {
+                    follower.finish(printer, out)?;
+                    printer.finish(out)
+                })?;
+            }
+            self.followers[index].end(printer);
+        }
+        out.flush()?;
+
+        Err(MinicatError::Interrupted)
+    }
+
+    /// Prints what has been appended to a file, if anything.
+    fn poll<W: Write>(&mut self, index: usize, printer: &mut LinePrinter<'a>, out: &mut W) -> Result<(), MinicatError> {
+        if self.followers[index].has_data() {
+            self.switch_to(index, printer, out, Follower::poll)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs `action` on a file with its own `FileState` in the `LinePrinter`. If the output comes from another
+    /// file than the last time, a banner is printed before it, but only once the file prints something, so a
+    /// file merely checked for rotation does not get one.
+    fn switch_to<'w, W: Write, T>(
+        &mut self,
+        index: usize,
+        printer: &mut LinePrinter<'a>,
+        out: &'w mut W,
+        action: impl FnOnce(&mut Follower<'a>, &mut LinePrinter<'a>, &mut Bannered<'w, W>) -> Result<T, MinicatError>,
+    ) -> Result<T, MinicatError> {
+        let follower = &mut self.followers[index];
+        let mut banner = Vec::new();
+        if self.banners && self.current != Some(index) {
+            if self.current.is_some() {
+                banner.push(b'\n');
+            }
+            printer.config.palette.write_header(&mut banner, &format!("==> {} <==", follower.filename))?;
+        }
+        let mut out = Bannered { out, banner, written: false };
+        printer.swap_file(&mut follower.state);
+        let result = action(follower, printer, &mut out);
+        printer.swap_file(&mut follower.state);
+        if out.written {
+            self.current = Some(index);
+        }
+
+        result
+    }
+}
+
+/// `Bannered` struct is a writer printing a banner before the first bytes written into it, if any are.
+///
+/// # Fields
+///
+/// * `out`: The writer the output goes to.
+/// * `banner`: The banner still to be printed, empty once it has been or if there is none.
+/// * `written`: Whether anything has been written.
+struct Bannered<'w, W> {
+    out: &'w mut W,
+    banner: Vec<u8>,
+    written: bool,
+}
+
+impl<W: Write> Write for Bannered<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if !self.banner.is_empty() {
+            self.out.write_all(&self.banner)?;
+            self.banner.clear();
+        }
+        self.written = true;
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,290 @@
+//! Syntax highlighting of source files, with the `syntect` crate.
+//!
+//! The language of a file is taken from `--language` if it is given, or else detected from the name and
+//! extension of the file, and then from its first line for scripts starting with a shebang. Highlighting is
+//! only available with the `syntect` feature, and only used when the output may be colored.
+//!
+//! The colors come from a theme, picked with `--theme` among the built-in ones and the `.tmTheme` files of
+//! the `minicat/themes` folder of the user configuration directory. The theme also colors the line numbers
+//! and the matches of `--highlight`, even when the files are not highlighted.
+
+#[cfg(not(feature = "syntect"))]
+use std::marker::PhantomData;
+#[cfg(feature = "syntect")]
+use std::{env, path::PathBuf};
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "syntect")]
+use clap::{Arg, ArgAction};
+#[cfg(feature = "syntect")]
+use syntect::easy::HighlightLines;
+#[cfg(feature = "syntect")]
+use syntect::highlighting::{Color, Theme, ThemeSet};
+#[cfg(feature = "syntect")]
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+use crate::style::Palette;
+#[cfg(feature = "syntect")]
+use crate::style::Style;
+#[cfg(feature = "syntect")]
+use crate::MinicatError;
+
+/// The theme the files are highlighted with when `--theme` is not given.
+#[cfg(feature = "syntect")]
+const THEME: &str = "base16-ocean.dark";
+
+/// `Syntaxes` struct holds what is needed to highlight the files: the known languages and the theme.
+///
+/// # Fields
+///
+/// * `loaded`: The languages and the language given with `--language`, `None` if the files are not
+///   highlighted. The definitions take a while to load, so they are only loaded when they are needed.
+/// * `theme`: The theme of the colors, `None` if nothing is colored or neither the files are highlighted nor
+///   `--theme` is given.
+#[derive(Debug, Default)]
+pub struct Syntaxes {
+    #[cfg(feature = "syntect")]
+    loaded: Option<Box<Loaded>>,
+    #[cfg(feature = "syntect")]
+    theme: Option<Box<Theme>>,
+}
+
+#[cfg(feature = "syntect")]
+#[derive(Debug)]
+struct Loaded {
+    syntaxes: SyntaxSet,
+    language: Option<String>,
+}
+
+/// `FileHighlighter` struct highlights the lines of a single file, keeping track of the constructs spanning
+/// several lines, such as block comments.
+pub struct FileHighlighter<'a> {
+    #[cfg(feature = "syntect")]
+    lines: HighlightLines<'a>,
+    #[cfg(feature = "syntect")]
+    syntaxes: &'a SyntaxSet,
+    #[cfg(not(feature = "syntect"))]
+    _syntaxes: PhantomData<&'a Syntaxes>,
+}
+
+impl Syntaxes {
+    /// Loads the languages if the options added by `args` ask for highlighting, and the theme if they ask for
+    /// highlighting or give `--theme`. Nothing is loaded unless `color` is `true`.
+    ///
+    /// A language given with `--language` or a theme given with `--theme` that is not known exits with a
+    /// usage error.
+    #[cfg(feature = "syntect")]
+    pub fn from_matches(matches: &ArgMatches, color: bool) -> Self {
+        let language = matches.get_one::<String>("language").cloned();
+        let highlight = matches.get_flag("syntax") || language.is_some();
+        let theme = matches.get_one::<String>("theme");
+        if !color || !(highlight || theme.is_some()) {
+            return Syntaxes::default();
+        }
+        let name = theme.map(String::as_str).unwrap_or(THEME);
+        let Some(theme) = themes().themes.remove(name) else {
+            let message = format!("unknown theme `{}`, see --list-themes\n", name);
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, message).exit();
+        };
+        let loaded = highlight.then(|| {
+            let syntaxes = SyntaxSet::load_defaults_newlines();
+            let unknown = |language: &&str| syntaxes.find_syntax_by_token(language).is_none();
+            if let Some(language) = language.as_deref().filter(unknown) {
+                let message = format!("unknown language `{}`\n", language);
+                clap::Error::raw(clap::error::ErrorKind::InvalidValue, message).exit();
+            }
+            Box::new(Loaded { syntaxes, language })
+        });
+        Syntaxes { loaded, theme: Some(Box::new(theme)) }
+    }
+
+    /// Builds the highlighting from the options added by `args`, of which there are none without the `syntect`
+    /// feature.
+    #[cfg(not(feature = "syntect"))]
+    pub fn from_matches(_matches: &ArgMatches, _color: bool) -> Self {
+        Syntaxes::default()
+    }
+
+    /// Returns `true` if the files are highlighted.
+    #[cfg(feature = "syntect")]
+    pub fn is_active(&self) -> bool {
+        self.loaded.is_some()
+    }
+
+    /// Returns `true` if the files are highlighted, which never happens without the `syntect` feature.
+    #[cfg(not(feature = "syntect"))]
+    pub fn is_active(&self) -> bool {
+        false
+    }
+
+    /// Returns the styles of the decorations in the colors of the theme, `None` if there is no theme.
+    #[cfg(feature = "syntect")]
+    pub fn palette(&self) -> Option<Palette> {
+        let settings = &self.theme.as_deref()?.settings;
+        let rgb = |color: Color| Style::Rgb(color.r, color.g, color.b);
+        Some(Palette {
+            number: settings.gutter_foreground.or(settings.foreground).map(rgb).or(Some(Style::NUMBER)),
+            header: Some(Style::HEADER),
+            matched: settings.find_highlight_foreground.or(settings.find_highlight).map_or(Style::MATCH, rgb),
+            whitespace: Style::WHITESPACE,
+        })
+    }
+
+    /// Returns the styles of the decorations in the colors of the theme, there are no themes without the
+    /// `syntect` feature.
+    #[cfg(not(feature = "syntect"))]
+    pub fn palette(&self) -> Option<Palette> {
+        None
+    }
+
+    /// Starts highlighting a file, from its name and its first line.
+    ///
+    /// ## Returns
+    /// `None` if the files are not highlighted, or if the language of this one is not known.
+    #[cfg(feature = "syntect")]
+    pub fn for_file<'a>(&'a self, filename: &str, first_line: &[u8]) -> Option<FileHighlighter<'a>> {
+        let loaded = self.loaded.as_deref()?;
+        let theme = self.theme.as_deref()?;
+        let syntax = match &loaded.language {
+            Some(language) => loaded.syntaxes.find_syntax_by_token(language),
+            None => detect(&loaded.syntaxes, filename, first_line),
+        }?;
+        if syntax.name == loaded.syntaxes.find_syntax_plain_text().name {
+            return None;
+        }
+        Some(FileHighlighter { lines: HighlightLines::new(syntax, theme), syntaxes: &loaded.syntaxes })
+    }
+
+    /// Starts highlighting a file, there is nothing to highlight without the `syntect` feature.
+    #[cfg(not(feature = "syntect"))]
+    pub fn for_file<'a>(&'a self, _filename: &str, _first_line: &[u8]) -> Option<FileHighlighter<'a>> {
+        None
+    }
+}
+
+/// Loads the built-in themes, and the `.tmTheme` files of the user themes folder on top of them.
+///
+/// A folder that cannot be read is reported on the standard error, and only the built-in themes are used.
+#[cfg(feature = "syntect")]
+fn themes() -> ThemeSet {
+    let mut themes = ThemeSet::load_defaults();
+    if let Some(folder) = themes_folder().filter(|folder| folder.is_dir()) {
+        if let Err(e) = themes.add_from_folder(&folder) {
+            eprintln!("minicat: {}: {}", folder.display(), e);
+        }
+    }
+    themes
+}
+
+/// Returns the folder of the user themes, `minicat/themes` in `$XDG_CONFIG_HOME`, or else in `~/.config`.
+#[cfg(feature = "syntect")]
+fn themes_folder() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .filter(|folder| !folder.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("minicat").join("themes"))
+}
+
+/// Prints the names of the themes `--theme` accepts into `out`, one per line and sorted, if `--list-themes`
+/// is given.
+///
+/// ## Returns
+/// `true` if the themes were listed, in which case there is nothing else to print.
+///
+/// # Errors
+/// The function will return an error if writing to `out` fails.
+#[cfg(feature = "syntect")]
+pub fn list_themes<W: std::io::Write>(matches: &ArgMatches, out: &mut W) -> Result<bool, MinicatError> {
+    if !matches.get_flag("list_themes") {
+        return Ok(false);
+    }
+    for name in themes().themes.keys() {
+        writeln!(out, "{}", name)?;
+    }
+    out.flush()?;
+
+    Ok(true)
+}
+
+/// Lists the themes if `--list-themes` is given, which it cannot be without the `syntect` feature.
+#[cfg(not(feature = "syntect"))]
+pub fn list_themes<W: std::io::Write>(_matches: &ArgMatches, _out: &mut W) -> Result<bool, crate::MinicatError> {
+    Ok(false)
+}
+
+/// Detects the language of a file from its name, its extension, and then its first line.
+#[cfg(feature = "syntect")]
+fn detect<'s>(syntaxes: &'s SyntaxSet, filename: &str, first_line: &[u8]) -> Option<&'s SyntaxReference> {
+    let path = std::path::Path::new(filename);
+    let name = path.file_name().and_then(|name| name.to_str());
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    // names such as `Makefile` are registered the same way as the extensions
+    name.and_then(|name| syntaxes.find_syntax_by_extension(name))
+        .or_else(|| extension.and_then(|extension| syntaxes.find_syntax_by_extension(extension)))
+        .or_else(|| syntaxes.find_syntax_by_first_line(&String::from_utf8_lossy(first_line)))
+}
+
+impl FileHighlighter<'_> {
+    /// Renders a line into `output` with `render`, each part of it in the color of the theme.
+    ///
+    /// The line is highlighted as it was read, and each part is rendered separately, so the display
+    /// transformations of `render` do not get in the way of the language. A line the language cannot be
+    /// parsed on is rendered without colors.
+    #[cfg(feature = "syntect")]
+    pub fn render(&mut self, line: &[u8], output: &mut Vec<u8>, render: impl Fn(&[u8], &mut Vec<u8>)) {
+        // the definitions expect the lines with their newline
+        let mut text = String::from_utf8_lossy(line).into_owned();
+        text.push('\n');
+        let Ok(regions) = self.lines.highlight_line(&text, self.syntaxes) else {
+            return render(line, output);
+        };
+        for (region_style, part) in regions {
+            let part = part.strip_suffix('\n').unwrap_or(part);
+            if part.is_empty() {
+                continue;
+            }
+            let color = region_style.foreground;
+            Style::Rgb(color.r, color.g, color.b).paint(output, |output| render(part.as_bytes(), output));
+        }
+    }
+
+    /// Renders a line into `output` with `render`, never called without the `syntect` feature since no
+    /// `FileHighlighter` can be made.
+    #[cfg(not(feature = "syntect"))]
+    pub fn render(&mut self, line: &[u8], output: &mut Vec<u8>, render: impl Fn(&[u8], &mut Vec<u8>)) {
+        render(line, output)
+    }
+}
+
+/// Adds the syntax highlighting options to the command, if the `syntect` feature is enabled.
+///
+/// * `syntax` ('--syntax'): this option will highlight the source files, detecting their language.
+/// * `language` ('--language'): this option will highlight the files as the given language, by name or extension.
+/// * `theme` ('--theme'): this option sets the theme of the colors, also used for the line numbers and the
+///   matches of `--highlight`.
+/// * `list_themes` ('--list-themes'): this option will print the names of the themes instead of the files.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "syntect")]
+    let command = command
+        .arg(Arg::new("syntax")
+            .action(ArgAction::SetTrue)
+            .long("syntax")
+            .overrides_with("syntax")
+            .help("Highlight the syntax of source files on terminals"))
+        .arg(Arg::new("language")
+            .action(ArgAction::Set)
+            .long("language")
+            .value_name("LANGUAGE")
+            .help("Highlight the files as LANGUAGE, e.g. rust or py"))
+        .arg(Arg::new("theme")
+            .action(ArgAction::Set)
+            .long("theme")
+            .value_name("NAME")
+            .help("Color theme of the highlighting, line numbers and matches"))
+        .arg(Arg::new("list_themes")
+            .action(ArgAction::SetTrue)
+            .long("list-themes")
+            .help("List the available color themes and exit"));
+    command
+}
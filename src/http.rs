@@ -0,0 +1,52 @@
+//! Reading `http://` and `https://` URLs given as input files, so `minicat -n URL` replaces
+//! `curl -s URL | cat -n`.
+//!
+//! The body of the response is streamed like any other input, so a large download is not held in memory. A
+//! response with another status than `200 OK` is an error of the file, reported like a file that cannot be
+//! opened. Fetching URLs needs the `http` feature, without which they are refused.
+
+use std::io::{self, Read};
+
+/// Returns `true` if the input is an `http://` or `https://` URL.
+pub fn is_url(filename: &str) -> bool {
+    ["http://", "https://"].iter().any(|scheme| {
+        filename.get(..scheme.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Sends a `GET` request for the URL.
+///
+/// ## Returns
+/// A reader giving the body of the response.
+///
+/// # Errors
+/// The function will return an error if the request fails, or if the status of the response is not `200 OK`.
+#[cfg(feature = "http")]
+pub fn open(url: &str) -> io::Result<Box<dyn Read + Send + Sync>> {
+    let request = ureq::get(url).set("User-Agent", concat!("minicat/", env!("CARGO_PKG_VERSION")));
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(code, response)) => return Err(status_error(code, response.status_text())),
+        Err(ureq::Error::Transport(transport)) => return Err(io::Error::other(transport.to_string())),
+    };
+    // redirects are followed already, any other success but `200 OK` has no body to print
+    if response.status() != 200 {
+        return Err(status_error(response.status(), response.status_text()));
+    }
+    Ok(response.into_reader())
+}
+
+/// Refuses to fetch the URL, since the `http` feature is not enabled.
+///
+/// # Errors
+/// The function always returns an error of kind `io::ErrorKind::Unsupported`.
+#[cfg(not(feature = "http"))]
+pub fn open(_url: &str) -> io::Result<Box<dyn Read + Send + Sync>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reading URLs needs the `http` feature"))
+}
+
+/// Returns the error of a response with an unexpected status, `HTTP 404 Not Found`.
+#[cfg(feature = "http")]
+fn status_error(code: u16, text: &str) -> io::Error {
+    io::Error::other(format!("HTTP {} {}", code, text))
+}
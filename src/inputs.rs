@@ -0,0 +1,164 @@
+//! Building the list of the inputs from the file arguments.
+//!
+//! The shell expands the patterns of the arguments on Unix, but not on Windows, nor when they are quoted, so
+//! the arguments that still look like patterns, such as `*.log`, are expanded here with the `glob` crate,
+//! unless `--no-glob` is given. The matches of a pattern are sorted and take its place in the list, so the
+//! order of the arguments is kept. A pattern matching nothing stays as it is, and is reported like a missing
+//! file when its turn comes.
+//!
+//! The list can also be read from a file with `--files-from`, one path per line, so that another tool can
+//! hand over more files than a command line holds, or with `--files-from0`, the paths ending with a NUL byte
+//! like `find -print0` writes them, which can hold any name, newlines included. Those paths are taken as they
+//! are, without expanding them.
+//!
+//! With `--recursive`, the directories among the inputs are replaced with the files of their trees that are
+//! neither ignored nor hidden, see the `walk` module.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+use crate::walk::{self, Selection};
+use crate::error::MinicatError;
+use crate::{cloud, http, socket};
+
+/// Expands the patterns among the file arguments.
+pub fn expand_globs(files: Vec<String>) -> Vec<String> {
+    files.into_iter().flat_map(expand_glob).collect()
+}
+
+/// Reads the paths listed in a file, or in the standard input for `-`, each of them ending with `delimiter`,
+/// a newline or a NUL byte. The empty paths are left out, and with newlines, so is the carriage return ending
+/// the lines of a list written on Windows.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the list cannot be
+/// opened or read.
+pub fn read_list(path: &str, delimiter: u8) -> Result<Vec<String>, MinicatError> {
+    let reader: Box<dyn Read> = if path == "-" {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path).map_err(|source| MinicatError::OpenFailed { path: path.to_string(), source })?)
+    };
+    let mut files = Vec::new();
+    for entry in BufReader::new(reader).split(delimiter) {
+        let entry = entry.map_err(|source| MinicatError::ReadFailed { path: path.to_string(), line: None, source })?;
+        let entry = if delimiter == b'\n' { entry.strip_suffix(b"\r").unwrap_or(&entry) } else { &entry };
+        if !entry.is_empty() {
+            files.push(String::from_utf8_lossy(entry).into_owned());
+        }
+    }
+    Ok(files)
+}
+
+/// Replaces the directories among the files with the regular files of their trees picked by `selection`.
+pub fn expand_directories(files: Vec<String>, selection: Selection) -> Vec<String> {
+    let mut expanded = Vec::with_capacity(files.len());
+    for file in files {
+        let path = Path::new(&file);
+        if file != "-" && path.is_dir() {
+            walk::walk(path, selection, &mut expanded);
+        } else {
+            expanded.push(file);
+        }
+    }
+    expanded
+}
+
+/// Returns the files matched by an argument, or the argument itself if it is not a pattern, if it is the name
+/// of an existing file, or if it matches nothing.
+fn expand_glob(argument: String) -> Vec<String> {
+    if !is_pattern(&argument) || Path::new(&argument).exists() {
+        return vec![argument];
+    }
+    let Ok(paths) = glob::glob(&argument) else {
+        return vec![argument];
+    };
+    // like the shell, the hidden files are only matched by a dot written in the pattern, and never as `.` or `..`
+    // (the walk of the glob crate drops every hidden entry when asked to require that dot, hence the filter, which
+    // leaves out the `./` the walk does not keep either)
+    let pattern = glob::Pattern::new(argument.trim_start_matches("./")).ok();
+    let literal_dot = glob::MatchOptions { require_literal_leading_dot: true, ..glob::MatchOptions::new() };
+    // the entries that cannot be read are left out, like the shell does
+    let matches: Vec<String> = paths
+        .flatten()
+        .filter(|path| pattern.as_ref().is_none_or(|pattern| pattern.matches_path_with(path, literal_dot)))
+        .map(|path| path.to_string_lossy().into_owned())
+        .filter(|path| !matches!(path.rsplit(std::path::MAIN_SEPARATOR).next(), Some("." | "..")))
+        .collect();
+    if matches.is_empty() { vec![argument] } else { matches }
+}
+
+/// Returns `true` if the argument contains the wildcards of a pattern, URLs and the standard input aside.
+fn is_pattern(argument: &str) -> bool {
+    argument != "-"
+        && !http::is_url(argument)
+        && !cloud::is_object_url(argument)
+        && !socket::is_socket_url(argument)
+        && argument.contains(['*', '?', '['])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Returns a folder holding `a.log`, `b.log`, `c.txt`, `sub/d.log` and the hidden `.e.log`.
+    fn folder() -> tempfile::TempDir {
+        let folder = tempfile::tempdir().unwrap();
+        for name in ["b.log", "a.log", "c.txt", ".e.log"] {
+            fs::write(folder.path().join(name), name).unwrap();
+        }
+        fs::create_dir(folder.path().join("sub")).unwrap();
+        fs::write(folder.path().join("sub").join("d.log"), "d").unwrap();
+        folder
+    }
+
+    #[test]
+    fn patterns_are_replaced_with_their_sorted_matches() {
+        let folder = folder();
+        let root = folder.path().to_string_lossy().into_owned();
+        let expanded = expand_globs(vec![format!("{root}/*.txt"), format!("{root}/*.log"), "-".to_string()]);
+        let expected = [format!("{root}/c.txt"), format!("{root}/a.log"), format!("{root}/b.log"), "-".to_string()];
+        assert_eq!(expanded, expected);
+        assert_eq!(expand_globs(vec![format!("{root}/**/d.*")]), [format!("{root}/sub/d.log")]);
+    }
+
+    #[test]
+    fn hidden_files_need_a_dot_in_the_pattern() {
+        let folder = folder();
+        let root = folder.path().to_string_lossy().into_owned();
+        assert_eq!(expand_globs(vec![format!("{root}/*e.log")]), [format!("{root}/*e.log")]);
+        assert_eq!(expand_globs(vec![format!("{root}/.*.log")]), [format!("{root}/.e.log")]);
+        assert_eq!(expand_globs(vec![format!("{root}/.*")]), [format!("{root}/.e.log")]);
+    }
+
+    #[test]
+    fn arguments_that_match_nothing_or_are_no_pattern_are_kept() {
+        let folder = folder();
+        let root = folder.path().to_string_lossy().into_owned();
+        assert_eq!(expand_globs(vec![format!("{root}/*.md")]), [format!("{root}/*.md")]);
+        assert!(!is_pattern("-"));
+        assert!(!is_pattern("https://example.com/?q=*"));
+        assert!(is_pattern("[ab].log"));
+    }
+
+    #[test]
+    fn lists_have_a_path_per_line() {
+        let folder = folder();
+        let list = folder.path().join("list");
+        fs::write(&list, "a.log\r\n\nsub/d.log\nlast").unwrap();
+        assert_eq!(read_list(list.to_str().unwrap(), b'\n').unwrap(), ["a.log", "sub/d.log", "last"]);
+        let missing = folder.path().join("missing");
+        assert!(matches!(read_list(missing.to_str().unwrap(), b'\n'), Err(MinicatError::OpenFailed { .. })));
+    }
+
+    #[test]
+    fn nul_delimited_lists_keep_the_newlines_of_the_paths() {
+        let folder = folder();
+        let list = folder.path().join("list");
+        fs::write(&list, "new\nline\r\0\0a.log").unwrap();
+        assert_eq!(read_list(list.to_str().unwrap(), b'\0').unwrap(), ["new\nline\r", "a.log"]);
+    }
+}
@@ -0,0 +1,162 @@
+//! Platform specific tuning of how the input files are read.
+//!
+//! Every regular file is read front to back exactly once, so the kernel is told to read ahead aggressively
+//! (`POSIX_FADV_SEQUENTIAL`) and, when asked for, to evict the file from the page cache once it has been read
+//! (`POSIX_FADV_DONTNEED`), so catting a huge file does not push everything else out of the cache. On platforms
+//! without `posix_fadvise` the hints are simply not given.
+//!
+//! It also tells files apart by their device and inode numbers, to catch an input file that is also the output.
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use crate::timings;
+
+/// `Advice` enum lists the access pattern hints given to the kernel.
+#[derive(Debug, Clone, Copy)]
+enum Advice {
+    /// The file is going to be read sequentially.
+    Sequential,
+    /// The cached pages of the file are not going to be needed again.
+    DontNeed,
+}
+
+/// `InputFile` struct is a regular file opened for a single sequential pass.
+///
+/// The sequential access hint is given when the file is opened, and the page cache hint, if enabled, when the
+/// `InputFile` is dropped.
+#[derive(Debug)]
+pub struct InputFile {
+    file: File,
+    drop_cache: bool,
+}
+
+impl InputFile {
+    /// Opens the file at `path` for reading.
+    ///
+    /// ## Parameters
+    /// * `path` - The path of the file.
+    /// * `drop_cache` - Whether the file should be dropped from the page cache once the `InputFile` is dropped.
+    ///
+    /// # Errors
+    /// The function will return an error if `std::fs::File::open()` fails.
+    pub fn open<P: AsRef<Path>>(path: P, drop_cache: bool) -> io::Result<Self> {
+        let file = timings::measure_open(|| File::open(path))?;
+        advise(&file, Advice::Sequential);
+
+        Ok(InputFile { file, drop_cache })
+    }
+
+    /// Returns the underlying file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns the underlying file, for the copy routines that are specialized on `File`.
+    pub fn file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+}
+
+impl Read for InputFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Drop for InputFile {
+    fn drop(&mut self) {
+        if self.drop_cache {
+            advise(&self.file, Advice::DontNeed);
+        }
+    }
+}
+
+/// Gives the kernel a hint about how the whole `file` is going to be used.
+///
+/// The hints are advisory only, so a failure is not worth reporting.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn advise(file: &File, advice: Advice) {
+    use std::os::fd::AsRawFd;
+
+    let advice = match advice {
+        Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+    };
+    // SAFETY: the descriptor is valid for as long as `file` is borrowed, a zero length means the whole file.
+    unsafe {
+        libc::posix_fadvise(file.as_raw_fd(), 0, 0, advice);
+    }
+}
+
+/// Portable fallback of `advise`, which gives no hints.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn advise(_file: &File, _advice: Advice) {}
+
+/// `FileId` struct identifies a file by its device and inode numbers, so that two paths or descriptors can be
+/// checked to refer to the same file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+/// Returns the identity of the standard output if it is a regular file, the only kind of output an input file
+/// can be read back from.
+///
+/// ## Returns
+/// `None` if the standard output is not a regular file, or on platforms without inode numbers.
+#[cfg(unix)]
+pub fn stdout_id() -> Option<FileId> {
+    use std::os::fd::AsFd;
+
+    let stdout = File::from(io::stdout().as_fd().try_clone_to_owned().ok()?);
+    let metadata = stdout.metadata().ok()?;
+    if metadata.is_file() { file_id(&metadata) } else { None }
+}
+
+/// Returns the identity of an input file, `-` being the standard input.
+///
+/// ## Returns
+/// `None` if the file cannot be inspected, or on platforms without inode numbers.
+#[cfg(unix)]
+pub fn input_id(filename: &str) -> Option<FileId> {
+    use std::os::fd::AsFd;
+
+    let metadata = match filename {
+        "-" => File::from(io::stdin().as_fd().try_clone_to_owned().ok()?).metadata(),
+        _ => std::fs::metadata(filename),
+    };
+    file_id(&metadata.ok()?)
+}
+
+/// Returns the identity of the file the metadata describes.
+///
+/// ## Returns
+/// `None` on platforms without inode numbers.
+#[cfg(unix)]
+pub fn file_id(metadata: &std::fs::Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(FileId { device: metadata.dev(), inode: metadata.ino() })
+}
+
+/// Portable fallback of `stdout_id`, which never identifies the output.
+#[cfg(not(unix))]
+pub fn stdout_id() -> Option<FileId> {
+    None
+}
+
+/// Portable fallback of `input_id`, which never identifies the input.
+#[cfg(not(unix))]
+pub fn input_id(_filename: &str) -> Option<FileId> {
+    None
+}
+
+/// Portable fallback of `file_id`, which never identifies the file.
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &std::fs::Metadata) -> Option<FileId> {
+    None
+}
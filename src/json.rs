@@ -0,0 +1,296 @@
+//! Pretty-printing JSON files, for `--pretty-json`.
+//!
+//! The document is re-indented as it is read, one byte at a time, without being parsed into a tree: a huge
+//! array is printed element by element instead of being loaded whole. The formatter only tracks the strings
+//! and the nesting of the objects and arrays, so it does not check that the document is valid, and a file that
+//! is not JSON comes out mangled rather than rejected. Several documents in a row, as in JSON Lines, are each
+//! printed from a new line.
+
+use std::io::{self, BufRead, Read};
+
+use crate::style::Style;
+
+/// The indentation of every nesting level.
+const INDENT: &[u8] = b"  ";
+
+/// The style of the keys of objects.
+const KEY: Style = Style::Escape(b"\x1b[1;34m");
+
+/// The style of the string values.
+const STRING: Style = Style::Escape(b"\x1b[32m");
+
+/// The style of the numbers, `true`, `false` and `null`.
+const SCALAR: Style = Style::Escape(b"\x1b[33m");
+
+/// `JsonMode` enum decides which files are pretty-printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum JsonMode {
+    /// The files are printed as they are.
+    #[default]
+    Never,
+    /// The files with a `.json` extension are pretty-printed.
+    Auto,
+    /// Every file is pretty-printed.
+    Always,
+}
+
+impl JsonMode {
+    /// Returns `true` if the file is pretty-printed.
+    pub fn applies(self, filename: &str) -> bool {
+        match self {
+            JsonMode::Never => false,
+            JsonMode::Auto => std::path::Path::new(filename)
+                .extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("json")),
+            JsonMode::Always => true,
+        }
+    }
+}
+
+/// `JsonFormatter` struct re-indents a JSON document fed to it one byte at a time.
+///
+/// # Fields
+///
+/// * `color`: Whether the keys and values are colored.
+/// * `containers`: The objects and arrays the formatter is in, `true` for an object.
+/// * `opened`: Whether a container has just been opened, so the newline after it is only written once it
+///   turns out not to be empty.
+/// * `expect_key`: Whether the next string is the key of an object.
+/// * `in_string`: Whether the formatter is within a string, where every byte is kept.
+/// * `escaped`: Whether the previous byte of the string was a backslash.
+/// * `in_scalar`: Whether the formatter is within a number, `true`, `false` or `null`.
+/// * `written`: Whether anything has been written yet, so the next document starts on a new line.
+#[derive(Debug, Default)]
+struct JsonFormatter {
+    color: bool,
+    containers: Vec<bool>,
+    opened: bool,
+    expect_key: bool,
+    in_string: bool,
+    escaped: bool,
+    in_scalar: bool,
+    written: bool,
+}
+
+impl JsonFormatter {
+    /// Formats a byte of the document into `output`.
+    fn feed(&mut self, byte: u8, output: &mut Vec<u8>) {
+        if self.in_string {
+            output.push(byte);
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+                self.end_style(output);
+            }
+            return;
+        }
+        if self.in_scalar && !(byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'+' | b'.')) {
+            self.in_scalar = false;
+            self.end_style(output);
+        }
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            b'{' | b'[' => {
+                self.start_value(output);
+                output.push(byte);
+                self.containers.push(byte == b'{');
+                self.opened = true;
+                self.expect_key = byte == b'{';
+            }
+            b'}' | b']' => {
+                self.containers.pop();
+                if !self.opened {
+                    self.newline(output);
+                }
+                self.opened = false;
+                self.expect_key = false;
+                output.push(byte);
+            }
+            b',' => {
+                output.push(byte);
+                self.newline(output);
+                self.expect_key = self.containers.last() == Some(&true);
+            }
+            b':' => {
+                output.extend_from_slice(b": ");
+                self.expect_key = false;
+            }
+            b'"' => {
+                self.start_value(output);
+                self.start_style(if self.expect_key { KEY } else { STRING }, output);
+                output.push(byte);
+                self.in_string = true;
+                self.expect_key = false;
+            }
+            _ => {
+                if !self.in_scalar {
+                    self.start_value(output);
+                    self.start_style(SCALAR, output);
+                    self.in_scalar = true;
+                }
+                output.push(byte);
+            }
+        }
+    }
+
+    /// Writes what is still owed at the end of the input: the end of the style of a last scalar, and the
+    /// final newline.
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        if self.in_scalar || self.in_string {
+            self.in_scalar = false;
+            self.end_style(output);
+        }
+        if self.written {
+            output.push(b'\n');
+        }
+    }
+
+    /// Writes what comes before a value: the newline after the container it is the first element of, or the
+    /// newline separating it from the previous document.
+    fn start_value(&mut self, output: &mut Vec<u8>) {
+        if self.opened {
+            self.opened = false;
+            self.newline(output);
+        } else if self.containers.is_empty() && self.written {
+            output.push(b'\n');
+        }
+        self.written = true;
+    }
+
+    /// Writes a newline and the indentation of the current nesting level.
+    fn newline(&self, output: &mut Vec<u8>) {
+        output.push(b'\n');
+        for _ in 0..self.containers.len() {
+            output.extend_from_slice(INDENT);
+        }
+    }
+
+    fn start_style(&self, style: Style, output: &mut Vec<u8>) {
+        if self.color {
+            style.start(output);
+        }
+    }
+
+    fn end_style(&self, output: &mut Vec<u8>) {
+        if self.color {
+            Style::end(output);
+        }
+    }
+}
+
+/// `PrettyJson` struct is a reader giving the pretty-printed content of the JSON document of another reader,
+/// so it goes through the line processing like any other file.
+///
+/// # Fields
+///
+/// * `inner`: The reader of the document.
+/// * `formatter`: The `JsonFormatter` re-indenting it.
+/// * `buffer`: The formatted bytes of the last chunk read from `inner`.
+/// * `position`: How much of `buffer` has been consumed.
+/// * `done`: Whether the end of `inner` has been reached.
+pub struct PrettyJson<R> {
+    inner: R,
+    formatter: JsonFormatter,
+    buffer: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: BufRead> PrettyJson<R> {
+    /// Pretty-prints the document of `inner`, coloring it if `color` is `true`.
+    pub fn new(inner: R, color: bool) -> Self {
+        PrettyJson {
+            inner,
+            formatter: JsonFormatter { color, ..JsonFormatter::default() },
+            buffer: Vec::new(),
+            position: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for PrettyJson<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl<R: BufRead> BufRead for PrettyJson<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.position == self.buffer.len() && !self.done {
+            self.buffer.clear();
+            self.position = 0;
+            let chunk = self.inner.fill_buf()?;
+            if chunk.is_empty() {
+                self.formatter.finish(&mut self.buffer);
+                self.done = true;
+            } else {
+                for &byte in chunk {
+                    self.formatter.feed(byte, &mut self.buffer);
+                }
+                let length = chunk.len();
+                self.inner.consume(length);
+            }
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretty-prints `document`, read a few bytes at a time so the formatter sees it in pieces.
+    fn pretty(document: &str, color: bool) -> String {
+        let mut output = String::new();
+        let inner = io::BufReader::with_capacity(3, document.as_bytes());
+        PrettyJson::new(inner, color).read_to_string(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn pretty_json_indents_the_nesting() {
+        let document = r#"{"a": [1, true], "b": {}, "c": []}"#;
+        assert_eq!(pretty(document, false), "{\n  \"a\": [\n    1,\n    true\n  ],\n  \"b\": {},\n  \"c\": []\n}\n");
+    }
+
+    #[test]
+    fn pretty_json_keeps_the_strings() {
+        let document = r#"["a \"{,}\" b",  "c\\"]"#;
+        assert_eq!(pretty(document, false), "[\n  \"a \\\"{,}\\\" b\",\n  \"c\\\\\"\n]\n");
+    }
+
+    #[test]
+    fn pretty_json_separates_the_documents() {
+        assert_eq!(pretty("{\"a\":1}\n{\"a\":2}\n", false), "{\n  \"a\": 1\n}\n{\n  \"a\": 2\n}\n");
+        assert_eq!(pretty("", false), "");
+    }
+
+    #[test]
+    fn pretty_json_colors_the_keys_and_values() {
+        let expected = concat!(
+            "{\n  \x1b[1;34m\"k\"\x1b[0m: \x1b[32m\"v\"\x1b[0m,\n",
+            "  \x1b[1;34m\"n\"\x1b[0m: \x1b[33m-1.5\x1b[0m\n}\n",
+        );
+        assert_eq!(pretty(r#"{"k":"v","n":-1.5}"#, true), expected);
+    }
+
+    #[test]
+    fn json_mode_applies_to_the_json_files() {
+        assert!(JsonMode::Auto.applies("data.JSON"));
+        assert!(!JsonMode::Auto.applies("data.txt"));
+        assert!(JsonMode::Always.applies("data.txt"));
+        assert!(!JsonMode::Never.applies("data.json"));
+    }
+}
@@ -1,8 +1,83 @@
-use std::error::Error;
-use std::fs::File;
+use std::fs;
 use std::io;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::time::Duration;
 use clap::{Command, Arg, ArgAction};
+use clap::parser::ValueSource;
+use encoding_rs::Encoding;
+
+mod ansi;
+mod archive;
+mod base64;
+mod binary;
+mod bom;
+mod checksum;
+mod clipboard;
+mod cloud;
+mod decompress;
+mod dump;
+mod encoding;
+mod endings;
+mod error;
+mod fifo;
+mod filters;
+mod follow;
+mod highlight;
+mod http;
+mod inputs;
+mod io_backend;
+mod json;
+mod limit;
+mod markdown;
+mod mmap;
+mod numbering;
+mod output;
+mod pacing;
+mod pager;
+mod prefetch;
+mod reverse;
+mod serial;
+mod serve;
+mod socket;
+mod splice;
+mod stats;
+mod stdin;
+mod style;
+mod tabs;
+mod terminal;
+mod timings;
+mod tui;
+mod walk;
+mod wrap;
+
+pub use error::MinicatError;
+use ansi::AnsiStripper;
+use base64::Base64Mode;
+use binary::BinaryMode;
+use checksum::{Algorithm, Checksummed, Sums};
+use decompress::Decompression;
+use dump::{DumpFormat, Renderer};
+use encoding::Encoded;
+use endings::{LineEnding, LineEndings};
+use fifo::FifoWait;
+use io_backend::{FileId, InputFile};
+use filters::Filters;
+use follow::Followers;
+use highlight::{FileHighlighter, Syntaxes};
+use json::{JsonMode, PrettyJson};
+use limit::{Limit, Limited};
+use markdown::{MarkdownRenderer, Render};
+use numbering::{LineNumberFormatter, NumberBase, NumberPad};
+use output::{Copies, OutputFile, OutputMode, SplitLimit, Tee};
+use pacing::{Pace, Paced};
+use pager::Pager;
+use prefetch::{Prefetched, Prefetcher};
+use stats::{Counted, Counter, StatsMode};
+use style::{ColorChoice, Highlighter, Palette};
+use tabs::Tabs;
+use timings::{Timed, Timings};
+use walk::Selection;
 
 /// `Config` struct is used to configure the parameters for file processing.
 ///
@@ -11,11 +86,335 @@ use clap::{Command, Arg, ArgAction};
 /// * `files`: A vector of file names (Strings) that will be processed by the program.
 /// * `count_lines`: A boolean value indicating whether to print line numbers or not.
 /// * `nonblank_number`: A boolean value indicating whether to print line numbers for non-blank lines or not.
-#[derive(Debug)]
+/// * `show_ends`: A boolean value indicating whether to print a `$` at the end of each line or not.
+/// * `show_tabs`: A boolean value indicating whether to display tab characters as `^I` or not.
+/// * `wrap`: The number of columns the lines are wrapped at, `None` to print them whole.
+/// * `truncate`: The number of columns the content of the lines is truncated to, `None` to print it whole.
+/// * `tabs`: The `Tabs` deciding whether the tabs are expanded into spaces, or the indentation turned into tabs.
+/// * `show_nonprinting`: A boolean value indicating whether to display control and high-bit characters
+///   using `^` and `M-` notation or not.
+/// * `squeeze_blank`: A boolean value indicating whether to collapse repeated empty lines into a single one or not.
+/// * `unbuffered`: A boolean value indicating whether to flush the output after every line or not.
+/// * `utf8`: A `Utf8Mode` deciding what happens to lines that are not valid UTF-8.
+/// * `mmap`: A boolean value indicating whether to read regular files through a memory mapping or not.
+/// * `jobs`: The number of small files read ahead on a background thread while the current one is written,
+///   0 to read every file only when it is its turn.
+/// * `buffer_size`: The capacity in bytes of the read and write buffers.
+/// * `drop_cache`: A boolean value indicating whether to drop the files from the page cache after reading them or not.
+/// * `error_policy`: An `ErrorPolicy` deciding whether to go on with the next file when one cannot be read.
+/// * `follow`: A `FollowMode` deciding whether to keep printing the data appended to the files
+///   until the program is interrupted.
+/// * `max_lines`: The number of lines printed from the start of each file before the rest of it is skipped,
+///   `None` to print every line. The skipped lines of `skip_lines` and the ones the filters drop are not part
+///   of them.
+/// * `skip_lines`: The number of lines dropped from the start of each file before anything is printed or numbered.
+/// * `number_original`: A boolean value indicating whether to number the lines by their position in the file,
+///   rather than in the output, or not. The two differ when lines are skipped.
+/// * `skip_bytes`: The number of bytes dropped from the start of each file, before it is split into lines.
+/// * `count_bytes`: The number of bytes printed from each file after `skip_bytes`, `None` to print up to its end.
+/// * `strip_bom`: A boolean value indicating whether to take the byte order mark off the start of the
+///   files or not, which is never done with `skip_bytes`, nor in dumps. See `Config::strips_bom`.
+/// * `encoding`: The encoding the files are transcoded to UTF-8 from as they are read, `None` to read them as
+///   they are.
+/// * `output_encoding`: The encoding the output is transcoded to from UTF-8, `None` to print it as it is.
+/// * `reverse`: A boolean value indicating whether to print the lines of each file last to first or not. The
+///   line selection options then apply to the reversed lines, so `max_lines` keeps the last lines of the file.
+/// * `squeeze_repeats`: A boolean value indicating whether to collapse runs of identical adjacent lines into
+///   a single one or not.
+/// * `count_repeats`: A boolean value indicating whether to follow a collapsed run with a `(repeated N times)`
+///   line or not.
+/// * `strip_ansi`: A boolean value indicating whether to remove the ANSI escape sequences from the input or not,
+///   before the lines are filtered and rendered.
+/// * `check_endings`: A boolean value indicating whether to warn about the files mixing LF and CRLF line endings,
+///   or having bare CRs, or not.
+/// * `line_ending`: The `LineEnding` the lines are converted to, or `LineEnding::Keep` to leave them as they are.
+/// * `filters`: The `Filters` selecting the lines to print and rewriting them. The printed lines keep the
+///   numbers of their position in the file.
+/// * `highlight`: The `Highlighter` coloring the matches of a pattern in the printed lines.
+/// * `whitespace_issues`: A boolean value indicating whether to color the trailing whitespace and the indentation
+///   mixing tabs and spaces or not.
+/// * `syntax`: The `Syntaxes` highlighting the source files in the colors of their language, which takes
+///   precedence over `highlight`.
+/// * `render`: A `Render` deciding which files are rendered as Markdown, which takes precedence over `syntax`.
+///   It is `Render::Never` unless the output is colored.
+/// * `pretty_json`: A `JsonMode` deciding which files are re-indented as JSON before their lines are processed.
+/// * `color`: A boolean value indicating whether the output is colored or not.
+/// * `palette`: The `Palette` of the styles the line numbers and the banners are painted in, uncolored unless
+///   the output is colored.
+/// * `number_format`: The `LineNumberFormatter` writing the line numbers, by default right-aligned in 6 columns
+///   like GNU cat does.
+/// * `number_start`: The number of the first numbered line.
+/// * `number_step`: How much the number grows from one numbered line to the next.
+/// * `numbering`: A `Numbering` deciding whether the numbering goes on from one file to the next.
+/// * `with_filename`: A boolean value indicating whether to prefix every line with `path:line:`, its file and
+///   its position in the file, or not.
+/// * `header`: The template of the banner printed before every file, in which `{name}` stands for the name of the
+///   file, `None` to print no banner.
+/// * `file_separator`: The bytes printed between two files, `None` to print nothing in between.
+/// * `timestamps`: The strftime format of the time printed in front of every line, `None` to print no time.
+/// * `paging`: A `Paging` deciding whether the output of `run` goes through a pager.
+/// * `dump`: The `DumpFormat` the bytes of the files are dumped in instead of being printed, `None` to print
+///   them. The line options do not apply to a dump.
+/// * `dump_group`: The number of bytes shown as a single number in a dump.
+/// * `base64`: The `Base64Mode` the files are converted with instead of being printed, `None` to print them.
+///   Like a dump, the conversion does not apply the line options.
+/// * `interactive`: A boolean value indicating whether `run` shows the output in the interactive viewer instead
+///   of printing it or not.
+/// * `checksums`: The `Algorithm`s the checksums of the output are computed with, reported on the standard error
+///   once it is printed. Empty to compute none.
+/// * `verify`: The `Sums` the checksums of every printed file are checked against, `None` to check nothing.
+/// * `stats`: A `StatsMode` deciding whether a summary of the printed files is written to the standard error,
+///   and whether the files are printed at all.
+/// * `timings`: A boolean value indicating whether to write the time spent opening, reading and writing every
+///   file to the standard error or not.
+/// * `decompression`: A `Decompression` deciding which inputs are decompressed when they are compressed.
+/// * `archive_member`: The name of the member printed out of every input, which is a tar or zip archive then,
+///   `None` to print the inputs themselves. A member can also be given with the input, as `ARCHIVE:MEMBER`.
+/// * `list_members`: A boolean value indicating whether to print the names of the members of the archives
+///   instead of their content or not, for the inputs without a member.
+/// * `binary`: A `BinaryMode` deciding whether the binary files are printed, skipped or dumped in hexadecimal.
+///   The files are only looked at in recursive mode, and printed as they are otherwise, unless `run` prints them
+///   on a terminal without `force`.
+/// * `force`: A boolean value indicating whether `run` prints the binary files on a terminal or not.
+/// * `baud`: The baud rate the serial devices are read at.
+/// * `fifo_wait`: A `FifoWait` deciding how long a FIFO is waited on for a writer before it is given up on.
+/// * `stdin_timeout`: How long the standard input is waited on for data before it is given up on, `None` to wait
+///   for as long as it takes.
+/// * `output`: The path of the file `run` copies the output into, `None` to only write it to the standard output.
+/// * `quiet`: A boolean value indicating whether `run` writes the output into the `output` file only, leaving the
+///   standard output alone, or not.
+/// * `output_mode`: An `OutputMode` deciding whether the `output` file is truncated, appended to, or replaced
+///   once everything has been printed.
+/// * `split`: The `SplitLimit` of the chunks the output is cut into, `output` being the template of their paths
+///   then, `None` to write a single file.
+/// * `clipboard`: A boolean value indicating whether `run` also places the output on the clipboard or not.
+/// * `serve`: The address `run` serves the output on over HTTP instead of printing it, `None` to print it.
+/// * `pace`: The `Pace` the output is throttled to, `None` to write it as fast as possible.
+/// * `limit`: The `Limit` the whole output is truncated at, counted across all the files.
+#[derive(Debug, Default)]
 pub struct Config {
     files: Vec<String>,
     count_lines: bool,
     nonblank_number: bool,
+    show_ends: bool,
+    show_tabs: bool,
+    tabs: Tabs,
+    wrap: Option<usize>,
+    truncate: Option<usize>,
+    show_nonprinting: bool,
+    squeeze_blank: bool,
+    unbuffered: bool,
+    utf8: Utf8Mode,
+    mmap: bool,
+    jobs: usize,
+    buffer_size: usize,
+    drop_cache: bool,
+    error_policy: ErrorPolicy,
+    follow: FollowMode,
+    max_lines: Option<usize>,
+    skip_lines: usize,
+    number_original: bool,
+    skip_bytes: usize,
+    count_bytes: Option<usize>,
+    strip_bom: bool,
+    encoding: Option<&'static Encoding>,
+    output_encoding: Option<&'static Encoding>,
+    reverse: bool,
+    squeeze_repeats: bool,
+    count_repeats: bool,
+    strip_ansi: bool,
+    check_endings: bool,
+    line_ending: LineEnding,
+    filters: Filters,
+    highlight: Highlighter,
+    whitespace_issues: bool,
+    syntax: Syntaxes,
+    render: Render,
+    pretty_json: JsonMode,
+    color: bool,
+    palette: Palette,
+    number_format: LineNumberFormatter,
+    number_start: usize,
+    number_step: usize,
+    numbering: Numbering,
+    with_filename: bool,
+    header: Option<String>,
+    file_separator: Option<Vec<u8>>,
+    timestamps: Option<String>,
+    paging: Paging,
+    dump: Option<DumpFormat>,
+    dump_group: usize,
+    base64: Option<Base64Mode>,
+    interactive: bool,
+    checksums: Vec<Algorithm>,
+    verify: Option<Sums>,
+    stats: StatsMode,
+    timings: bool,
+    decompression: Decompression,
+    archive_member: Option<String>,
+    list_members: bool,
+    binary: BinaryMode,
+    force: bool,
+    baud: u32,
+    fifo_wait: FifoWait,
+    stdin_timeout: Option<Duration>,
+    output: Option<String>,
+    quiet: bool,
+    output_mode: OutputMode,
+    split: Option<SplitLimit>,
+    clipboard: bool,
+    serve: Option<String>,
+    pace: Option<Pace>,
+    limit: Limit,
+}
+
+/// `ErrorPolicy` enum decides what happens when a file cannot be opened or read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// The error is reported, the remaining files are printed and the program exits with a nonzero status.
+    #[default]
+    Continue,
+    /// The program stops at the first file that cannot be opened or read.
+    FailFast,
+}
+
+/// `FollowMode` enum decides whether the files are followed once they have been printed, like `tail -f`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FollowMode {
+    /// The files are printed once, up to their end.
+    #[default]
+    Off,
+    /// The open file keeps being read, even after it has been renamed or deleted.
+    Descriptor,
+    /// The path keeps being read: a file that is truncated or replaced, for instance by log rotation, is reopened.
+    Name,
+}
+
+/// `Numbering` enum decides whether the line numbers restart with every file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Numbering {
+    /// The numbering goes on from one file to the next, like GNU cat does.
+    #[default]
+    Continuous,
+    /// The numbering starts over with every file.
+    Restart,
+}
+
+/// `Paging` enum decides whether the output is piped through a pager, `$PAGER` or `less -RF`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Paging {
+    /// The pager is used when the output goes to a terminal, unless the files are followed or typed in.
+    #[default]
+    Auto,
+    /// The pager is always used.
+    Always,
+    /// The output is never paged.
+    Never,
+}
+
+/// `Utf8Mode` enum decides how lines that are not valid UTF-8 are handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// The bytes are passed through untouched.
+    #[default]
+    Raw,
+    /// Invalid sequences are replaced with U+FFFD REPLACEMENT CHARACTER.
+    Lossy,
+    /// An invalid sequence stops the program with an error naming the file and the line.
+    Strict,
+}
+
+impl Config {
+    /// Returns `true` if any of the options requires the input to be processed line by line.
+    ///
+    /// When it returns `false`, the files are copied to the output byte for byte, which keeps binary
+    /// files intact.
+    fn transforms_lines(&self) -> bool {
+        self.count_lines
+            || self.nonblank_number
+            || self.show_ends
+            || self.show_tabs
+            || self.tabs.is_active()
+            || self.wrap.is_some()
+            || self.truncate.is_some()
+            || self.show_nonprinting
+            || self.squeeze_blank
+            || self.utf8 != Utf8Mode::Raw
+            || self.max_lines.is_some()
+            || self.skip_lines > 0
+            || self.reverse
+            || self.squeeze_repeats
+            || self.strip_ansi
+            || self.check_endings
+            || self.line_ending.is_active()
+            || self.filters.is_active()
+            || self.highlight.is_active()
+            || self.whitespace_issues
+            || self.syntax.is_active()
+            || self.render != Render::Never
+            || self.with_filename
+            || self.timestamps.is_some()
+    }
+
+    /// Returns `true` if what is printed of the input is not the content of a file as it is, because it is
+    /// fetched from a URL, a cloud storage, a socket or a serial device, decompressed, comes out of an archive or
+    /// is transcoded from another encoding, in which case it can only be read through `open_file`.
+    fn transforms_input(&self, filename: &str) -> bool {
+        let (path, member) = archive::split(filename);
+        http::is_url(path)
+            || cloud::is_object_url(path)
+            || socket::is_socket_url(path)
+            || serial::is_serial_url(path)
+            || member.is_some()
+            || self.archive_member.is_some()
+            || self.list_members
+            || self.decompression.applies(path)
+            || self.encoding.is_some()
+    }
+
+    /// Returns the algorithms the output is hashed with, the ones of `checksums` and the ones `verify` needs.
+    fn hashes(&self) -> Vec<Algorithm> {
+        let mut algorithms = self.checksums.clone();
+        for algorithm in self.verify.iter().flat_map(Sums::algorithms) {
+            if !algorithms.contains(&algorithm) {
+                algorithms.push(algorithm);
+            }
+        }
+        algorithms
+    }
+
+    /// Returns the `Renderer` the files go through instead of being printed line by line, `None` if they are
+    /// printed.
+    fn renderer(&self) -> Option<Box<dyn Renderer>> {
+        match (self.dump, self.base64) {
+            (Some(format), _) => Some(format.renderer(self.dump_group)),
+            (None, Some(mode)) => Some(mode.renderer()),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns `true` if only a part of each file is printed, selected by `skip_bytes` and `count_bytes`.
+    fn selects_bytes(&self) -> bool {
+        self.skip_bytes > 0 || self.count_bytes.is_some()
+    }
+
+    /// Returns `true` if the byte order mark of a file is taken off, `first` telling whether nothing has been
+    /// printed before it. The mark of the first file is kept when it starts the output, so that a plain copy of a
+    /// file is the same byte for byte.
+    fn strips_bom(&self, first: bool) -> bool {
+        self.strip_bom && (!first || self.header.is_some() || self.transforms_lines())
+    }
+
+    /// Returns the part of a file already in memory selected by `skip_bytes` and `count_bytes`, without the byte
+    /// order mark it starts with if `strip_bom` is `true`.
+    fn select_bytes<'d>(&self, data: &'d [u8], strip_bom: bool) -> &'d [u8] {
+        let data = &data[self.skip_bytes.min(data.len())..];
+        let data = if strip_bom { &data[bom::length(data)..] } else { data };
+        match self.count_bytes {
+            Some(count) => &data[..count.min(data.len())],
+            None => data,
+        }
+    }
 }
 
 /// Constructs a new Command for the `minicat` program.
@@ -28,11 +427,175 @@ pub struct Config {
 ///
 /// # Arguments
 ///
-/// * `files`: appendable argument allowing users to specify the files to be read. Hyphen values are permitted.
-/// * `number` ('-n'): this option will number all output lines.
-/// * `nonblank` ('-b'): this option will number only nonblank lines.
+/// * `files`: appendable argument allowing users to specify the files to be read. A `-` stands for standard
+///   input and may appear several times; it is also used when no files are given.
+/// * `number` ('-n', '--number'): this option will number all output lines.
+/// * `nonblank` ('-b', '--number-nonblank'): this option will number only nonblank lines.
+/// * `show_ends` ('-E', '--show-ends'): this option will display a `$` at the end of each line.
+/// * `show_tabs` ('-T', '--show-tabs'): this option will display tab characters as `^I`.
+/// * `expand_tabs` ('--expand-tabs'): this option will replace the tabs with spaces up to the next tab stop, like
+///   `expand`, the tab separating the line numbers from the lines included.
+/// * `unexpand` ('--unexpand'): this option will replace the spaces and tabs the lines start with with tabs up
+///   to the last tab stop they reach, like `unexpand`.
+/// * `tabstop` ('--tabstop'): this option sets the number of columns between two tab stops, 8 by default.
+/// * `wrap` ('--wrap'): this option will wrap the lines wider than the terminal, or than the given number of
+///   columns, the continuation lines being indented past the line numbers without getting one of their own.
+/// * `truncate` ('--truncate'): this option will cut the lines wider than the given number of columns, ending them
+///   with `…`.
+/// * `show_nonprinting` ('-v', '--show-nonprinting'): this option will use `^` and `M-` notation,
+///   except for line feeds and tabs.
+/// * `show_all` ('-A', '--show-all'): this option is equivalent to `-vET`.
+/// * `e` ('-e'): this option is equivalent to `-vE`.
+/// * `t` ('-t'): this option is equivalent to `-vT`.
+/// * `squeeze_blank` ('-s', '--squeeze-blank'): this option will suppress repeated empty output lines.
+/// * `unbuffered` ('-u', '--unbuffered'): this option will flush the output after every line.
+/// * `rate` ('--rate'): this option will throttle the output to the given number of bytes per second, such as
+///   `100K/s`, `K`, `M` and `G` suffixes being accepted.
+/// * `lines_per_sec` ('--lines-per-sec'): this option will throttle the output to the given number of lines per
+///   second, which may be a fraction.
+/// * `delay` ('--delay'): this option will wait for the given duration between two lines, like a typewriter.
+/// * `delay_per` ('--delay-per'): this option sets whether `--delay` is waited between two `line`s, the default,
+///   or between two `char`acters.
+/// * `max_bytes` ('--max-bytes'): this option will stop once the given number of bytes has been written, across
+///   all the files, such as `1M`, `K`, `M` and `G` suffixes being accepted.
+/// * `max_output_lines` ('--max-output-lines'): this option will stop once the given number of lines has been
+///   written, across all the files.
+/// * `output` ('-o', '--output'): this option will also write the output into a file, which is created or
+///   truncated, like `tee` does.
+/// * `quiet` ('-q', '--quiet'): this option will write the output into the `--output` file only.
+/// * `append` ('--append'): this option will add the output at the end of the `--output` file instead of
+///   truncating it.
+/// * `output_tmp_then_rename` ('--output-tmp-then-rename'): this option will write the output into a temporary
+///   file, which replaces the `--output` file once everything has been printed.
+/// * `split_lines` ('--split-lines'): this option will cut the output into chunks of the given number of lines,
+///   written into the files named by the `--output` template, such as `out.%03d`.
+/// * `split_bytes` ('--split-bytes'): this option will cut the output into chunks of the given size, `K`, `M`
+///   and `G` suffixes being accepted.
+/// * `lossy` ('--lossy'): this option will replace invalid UTF-8 sequences with U+FFFD.
+/// * `strict` ('--strict'): this option will fail on the first line that is not valid UTF-8.
+/// * `mmap` ('--mmap'): this option will read regular files through a memory mapping when processing lines.
+/// * `jobs` ('--jobs'): this option sets how many small files are read ahead while the current one is written.
+/// * `buffer_size` ('--buffer-size'): this option sets the size of the read and write buffers, `K`, `M` and `G`
+///   suffixes are accepted.
+/// * `drop_cache` ('--drop-cache'): this option will tell the kernel that the files are not needed in the page
+///   cache once they have been read.
+/// * `fail_fast` ('--fail-fast'): this option will stop at the first file that cannot be opened or read.
+/// * `nonblocking` ('--nonblocking'): this option will give up on the FIFOs that have no writer, instead of
+///   waiting for one.
+/// * `open_timeout` ('--open-timeout'): this option will give up on the FIFOs that have no writer after the
+///   given duration, such as `1.5` or `500ms`.
+/// * `stdin_timeout` ('--stdin-timeout'): this option will give up on the standard input if no data arrives
+///   within the given duration, instead of hanging when the program was run by accident.
+/// * `follow` ('-f', '--follow'): this option will keep printing the data appended to the files, like
+///   `tail -f`, until the program is interrupted with Ctrl+C. With several files, a `==> name <==` banner
+///   shows which file the output comes from.
+/// * `follow_name` ('-F', '--follow-name'): this option will follow the files like `follow`, but reopen them
+///   when they are truncated or replaced, like `tail -F`.
+/// * `max_lines` ('--max-lines'): this option will print only the first lines of each file, and stop reading
+///   the file there.
+/// * `skip_lines` ('--skip-lines'): this option will drop the first lines of each file, like the header lines
+///   of CSV files being concatenated.
+/// * `lines` ('--lines'): this option will print only a range of lines of each file, `START:END` with both ends
+///   included, or `START:` up to the end of the file. It is a shorthand for `skip_lines` and `max_lines`.
+/// * `number_original` ('--number-original'): this option will number the lines by their position in the file
+///   instead of their position in the output.
+/// * `skip_bytes` ('--skip-bytes'): this option will drop the first bytes of each file, seeking past them in
+///   regular files.
+/// * `count_bytes` ('--count-bytes'): this option will print only that many bytes of each file, after the
+///   skipped ones.
+/// * `keep_bom` ('--keep-bom'): this option will print the UTF-8 and UTF-16 byte order marks the files start
+///   with, which are stripped otherwise, but for the one starting the output.
+/// * `from_encoding` ('--from-encoding'): this option will transcode the files from the given encoding to UTF-8,
+///   e.g. `utf-16le`, `latin1` or `shift_jis`.
+/// * `to_encoding` ('--to-encoding'): this option will transcode the output from UTF-8 to the given encoding,
+///   starting it with a byte order mark for UTF-16.
+/// * `reverse` ('--reverse'): this option will print the lines of each file last to first, like `tac`.
+/// * `squeeze_repeats` ('--squeeze-repeats'): this option will collapse runs of identical adjacent lines
+///   into a single one, like `uniq`.
+/// * `count_repeats` ('--count-repeats'): this option will squeeze repeats and tell how many times each
+///   collapsed line appeared in a row.
+/// * `strip_ansi` ('--strip-ansi'): this option will remove the ANSI escape sequences (colors, cursor movements,
+///   window titles) from the input, so the output is clean for logs and redirects.
+/// * `check_line_endings` ('--check-line-endings'): this option will warn on the standard error about the files
+///   mixing LF and CRLF line endings, or having bare CRs, with their counts and the first lines they show up on.
+/// * `lf` ('--lf'): this option will end every line with a line feed alone, converting the CRLF endings.
+/// * `crlf` ('--crlf'): this option will end every line with a carriage return and a line feed, converting the LF
+///   endings.
+/// * `compact_numbers` ('--compact-numbers'): this option will print the line numbers without aligning them,
+///   instead of the 6 columns of GNU cat. It is a shorthand for `--number-width 0`.
+/// * `number_width` ('--number-width'): this option sets the minimum number of columns of the line numbers.
+/// * `number_pad` ('--number-pad'): this option decides whether the line numbers are padded with `spaces`
+///   or `zeros`.
+/// * `number_separator` ('--number-separator'): this option sets what is printed between the line numbers
+///   and the lines, a tab by default.
+/// * `number_format` ('--number-format'): this option decides whether the line numbers are written in `dec`imal,
+///   `hex`adecimal or `oct`al.
+/// * `number_start` ('--number-start'): this option sets the number of the first numbered line, 1 by default.
+/// * `number_step` ('--number-step'): this option sets how much the number grows from one line to the next.
+/// * `restart_numbering` ('--restart-numbering'): this option will start the numbering over with every file.
+/// * `continuous_numbering` ('--continuous-numbering'): this option will go on with the numbering from one file
+///   to the next, which is the default.
+/// * `with_filename` ('-H', '--with-filename'): this option will prefix every line with its file and position
+///   in the file, like `grep -Hn` does.
+/// * `header` ('--header'): this option will print a `==> name <==` banner before every file, like `head` does.
+///   A template can be given instead, `{name}` being replaced with the name of the file.
+/// * `file_separator` ('--file-separator'): this option sets a string printed between two files, in which
+///   `\n`, `\t`, `\f` and the other usual escape sequences are understood.
+/// * `timestamps` ('--timestamps'): this option will prefix every line with the local time it is printed at,
+///   handy when following logs. A strftime format can be given, the default is `%Y-%m-%dT%H:%M:%S%.3f`.
+/// * `render` ('--render'): this option will render Markdown files with terminal styles when the output is
+///   colored, `markdown` for every file or `auto` for the files with a Markdown extension.
+/// * `pretty_json` ('--pretty-json'): this option will re-indent every file as JSON, which is only done for the
+///   files with a `.json` extension otherwise, when the output is a terminal. The keys and values are colored
+///   when the output is.
+/// * `hex` ('-x', '--hex'): this option will dump the bytes of the files in hexadecimal like `xxd`, instead of
+///   printing them. It is a shorthand for `--dump hex`.
+/// * `dump` ('--dump'): this option will dump the bytes of the files in `hex`, `octal` or `decimal`, instead of
+///   printing them.
+/// * `dump_group` ('--dump-group'): this option sets how many bytes are shown as a single number in a dump,
+///   1, 2, 4 or 8. The default is 2 in hexadecimal, like `xxd`, and 1 otherwise, like `od`.
+/// * `base64_encode` ('--base64-encode'): this option will encode the files to base64, in lines of 76
+///   characters.
+/// * `base64_decode` ('--base64-decode'): this option will decode the files from base64, which may be wrapped
+///   in lines.
+/// * `no_glob` ('--no-glob'): this option will take the file arguments as they are, instead of expanding the
+///   patterns the shell left, such as a quoted `"*.log"`.
+/// * `files_from` ('--files-from'): this option will read the paths of more files to print from a file, or from
+///   the standard input for `-`, one per line. They are printed after the files given as arguments, if any.
+/// * `files_from0` ('--files-from0'): this option does the same with paths ending with a NUL byte, as written
+///   by `find -print0`, so that they can hold newlines.
+/// * `unix_socket` ('--unix-socket'): this option will print what is received from a Unix domain socket, after
+///   the other files. It can be given several times, and is the same as a `unix://PATH` file.
+/// * `listen` ('--listen'): this option will wait for a single TCP connection on `HOST:PORT` once the other
+///   files are printed, and print what is received until it is closed, like `nc -l`. It is the same as a
+///   `listen://HOST:PORT` file.
+/// * `recursive` ('-r', '--recursive'): this option will print the regular files of the trees of the
+///   directories among the files, in the order of their paths, instead of refusing the directories. The
+///   files ignored by a `.gitignore` and the hidden files are left out, like ripgrep does.
+/// * `no_ignore` ('--no-ignore'): this option will print the files ignored by the ignore files with
+///   `--recursive`.
+/// * `hidden` ('--hidden'): this option will print the hidden files and directories with `--recursive`.
+/// * `binary` ('--binary'): this option decides what happens to the files containing a NUL byte with
+///   `--recursive`: `skip` them with a notice, the default, print them `raw`, or dump them in `hex`.
+/// * `force` ('--force'): this option will print the binary files on a terminal, where they are skipped with a
+///   warning otherwise.
+/// * `decompress` ('--decompress'): this option will decompress every input that is compressed, instead of only
+///   the files with the extension of a compression format, such as `.gz`.
+/// * `no_decompress` ('--no-decompress'): this option will print the compressed files as they are.
+/// * `archive_member` ('--archive-member'): this option will print the given member out of every input, which
+///   has to be a tar or zip archive.
+/// * `list_members` ('--list-members'): this option will print the names of the members of every input, which
+///   has to be a tar or zip archive.
+/// * `paging` ('--paging'): this option decides whether the output goes through a pager, `auto` (when it goes
+///   to a terminal), `always` or `never`.
+/// * `plain` ('-p', '--plain'): this option will switch off the decorations enabled before it: line numbers,
+///   the display options, file names, banners, separators, timestamps and colors.
+///
+/// The command also gets the options of `filters::args`, `style::args`, `highlight::args`, `tui::args`,
+/// `checksum::args`, `stats::args`, `timings::args`, `serial::args`, `clipboard::args` and `serve::args`, some
+/// of which depend on the enabled features.
 ///
-/// Note: the `number` and `nonblank` options are mutually exclusive.
+/// Note: the `number` and `nonblank` options are mutually exclusive, and so are the `lossy` and `strict` options.
 ///
 /// # Returns
 ///
@@ -40,30 +603,564 @@ pub struct Config {
 ///
 /// # Example
 ///
-/// ```
+/// ```ignore
 /// let matches = build_cli().get_matches();
 /// ```
 fn build_cli() -> Command {
-    Command::new("minicat")
+    let command = Command::new("minicat")
         .about("Rust version of the cat command")
         .version("0.1.0")
         .arg(Arg::new("files")
             .action(ArgAction::Append)
             .value_name("FILES")
-            .default_value("")
-            .help("Files to read")
-            .allow_hyphen_values(true))
+            .default_value("-")
+            .help("Files to read, - for standard input"))
         .arg(Arg::new("number")
             .action(ArgAction::SetTrue)
             .short('n')
+            .long("number")
             .overrides_with("number")
             .help("Numbers the lines")
             .conflicts_with("nonblank"))
         .arg(Arg::new("nonblank")
             .action(ArgAction::SetTrue)
             .short('b')
+            .long("number-nonblank")
             .overrides_with("nonblank")
             .help("Number only nonblank lines"))
+        .arg(Arg::new("show_ends")
+            .action(ArgAction::SetTrue)
+            .short('E')
+            .long("show-ends")
+            .overrides_with("show_ends")
+            .help("Display $ at end of each line"))
+        .arg(Arg::new("show_tabs")
+            .action(ArgAction::SetTrue)
+            .short('T')
+            .long("show-tabs")
+            .overrides_with("show_tabs")
+            .help("Display TAB characters as ^I"))
+        .arg(Arg::new("expand_tabs")
+            .action(ArgAction::SetTrue)
+            .long("expand-tabs")
+            .overrides_with("expand_tabs")
+            .help("Replace the tabs with spaces up to the next tab stop"))
+        .arg(Arg::new("unexpand")
+            .action(ArgAction::SetTrue)
+            .long("unexpand")
+            .overrides_with("unexpand")
+            .conflicts_with("expand_tabs")
+            .help("Turn the indentation of the lines into tabs"))
+        .arg(Arg::new("tabstop")
+            .action(ArgAction::Set)
+            .long("tabstop")
+            .value_name("N")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .default_value("8")
+            .help("Put the tab stops N columns apart"))
+        .arg(Arg::new("wrap")
+            .action(ArgAction::Set)
+            .long("wrap")
+            .value_name("COLS")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("auto")
+            .value_parser(parse_wrap)
+            .help("Wrap the lines at the terminal width, or at COLS columns"))
+        .arg(Arg::new("truncate")
+            .action(ArgAction::Set)
+            .long("truncate")
+            .value_name("COLS")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .conflicts_with("wrap")
+            .help("Cut the lines wider than COLS columns, ending them with …"))
+        .arg(Arg::new("show_nonprinting")
+            .action(ArgAction::SetTrue)
+            .short('v')
+            .long("show-nonprinting")
+            .overrides_with("show_nonprinting")
+            .help("Use ^ and M- notation, except for LFD and TAB"))
+        .arg(Arg::new("show_all")
+            .action(ArgAction::SetTrue)
+            .short('A')
+            .long("show-all")
+            .overrides_with("show_all")
+            .help("Equivalent to -vET"))
+        .arg(Arg::new("e")
+            .action(ArgAction::SetTrue)
+            .short('e')
+            .overrides_with("e")
+            .help("Equivalent to -vE"))
+        .arg(Arg::new("t")
+            .action(ArgAction::SetTrue)
+            .short('t')
+            .overrides_with("t")
+            .help("Equivalent to -vT"))
+        .arg(Arg::new("squeeze_blank")
+            .action(ArgAction::SetTrue)
+            .short('s')
+            .long("squeeze-blank")
+            .overrides_with("squeeze_blank")
+            .help("Suppress repeated empty output lines"))
+        .arg(Arg::new("unbuffered")
+            .action(ArgAction::SetTrue)
+            .short('u')
+            .long("unbuffered")
+            .overrides_with("unbuffered")
+            .help("Flush the output after every line"))
+        .arg(Arg::new("rate")
+            .action(ArgAction::Set)
+            .long("rate")
+            .value_name("SIZE/s")
+            .value_parser(parse_rate)
+            .help("Throttle the output to SIZE bytes per second, e.g. 100KB/s"))
+        .arg(Arg::new("lines_per_sec")
+            .action(ArgAction::Set)
+            .long("lines-per-sec")
+            .value_name("N")
+            .value_parser(parse_lines_rate)
+            .conflicts_with("rate")
+            .help("Throttle the output to N lines per second"))
+        .arg(Arg::new("delay")
+            .action(ArgAction::Set)
+            .long("delay")
+            .value_name("DURATION")
+            .value_parser(parse_duration)
+            .conflicts_with_all(["rate", "lines_per_sec"])
+            .help("Wait DURATION between two lines, or characters with --delay-per char, e.g. 50ms"))
+        .arg(Arg::new("delay_per")
+            .action(ArgAction::Set)
+            .long("delay-per")
+            .value_name("UNIT")
+            .value_parser(["line", "char"])
+            .requires("delay")
+            .help("Wait the --delay between two lines or two characters [default: line]"))
+        .arg(Arg::new("max_bytes")
+            .action(ArgAction::Set)
+            .long("max-bytes")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .help("Stop once SIZE bytes have been written, across all the files, e.g. 1G"))
+        .arg(Arg::new("max_output_lines")
+            .action(ArgAction::Set)
+            .long("max-output-lines")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64))
+            .help("Stop once N lines have been written, across all the files"))
+        .arg(Arg::new("output")
+            .action(ArgAction::Set)
+            .short('o')
+            .long("output")
+            .value_name("PATH")
+            .help("Also write the output into the file at PATH, like tee"))
+        .arg(Arg::new("quiet")
+            .action(ArgAction::SetTrue)
+            .short('q')
+            .long("quiet")
+            .requires("output")
+            .help("Write the output into the --output file only, not to stdout"))
+        .arg(Arg::new("append")
+            .action(ArgAction::SetTrue)
+            .long("append")
+            .requires("output")
+            .help("Add the output at the end of the --output file instead of truncating it"))
+        .arg(Arg::new("output_tmp_then_rename")
+            .action(ArgAction::SetTrue)
+            .long("output-tmp-then-rename")
+            .requires("output")
+            .conflicts_with("append")
+            .help("Replace the --output file atomically once everything has been printed"))
+        .arg(Arg::new("split_lines")
+            .action(ArgAction::Set)
+            .long("split-lines")
+            .value_name("N")
+            .value_parser(clap::builder::RangedU64ValueParser::<usize>::new().range(1..))
+            .requires("output")
+            .conflicts_with_all(["append", "output_tmp_then_rename"])
+            .help("Cut the output into files of N lines, named by the --output template, e.g. out.%03d"))
+        .arg(Arg::new("split_bytes")
+            .action(ArgAction::Set)
+            .long("split-bytes")
+            .value_name("SIZE")
+            .value_parser(parse_size)
+            .requires("output")
+            .conflicts_with_all(["split_lines", "append", "output_tmp_then_rename"])
+            .help("Cut the output into files of SIZE bytes, named by the --output template"))
+        .arg(Arg::new("lossy")
+            .action(ArgAction::SetTrue)
+            .long("lossy")
+            .overrides_with("lossy")
+            .help("Replace invalid UTF-8 sequences with U+FFFD")
+            .conflicts_with("strict"))
+        .arg(Arg::new("strict")
+            .action(ArgAction::SetTrue)
+            .long("strict")
+            .overrides_with("strict")
+            .help("Fail on lines that are not valid UTF-8"))
+        .arg(Arg::new("mmap")
+            .action(ArgAction::SetTrue)
+            .long("mmap")
+            .overrides_with("mmap")
+            .help("Read regular files through a memory mapping"))
+        .arg(Arg::new("jobs")
+            .action(ArgAction::Set)
+            .long("jobs")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("0")
+            .help("Read up to N small files ahead on a background thread"))
+        .arg(Arg::new("buffer_size")
+            .action(ArgAction::Set)
+            .long("buffer-size")
+            .value_name("BYTES")
+            .value_parser(parse_size)
+            .default_value("8K")
+            .help("Size of the read and write buffers, e.g. 64K or 1M"))
+        .arg(Arg::new("drop_cache")
+            .action(ArgAction::SetTrue)
+            .long("drop-cache")
+            .overrides_with("drop_cache")
+            .help("Drop the files from the page cache after reading them"))
+        .arg(Arg::new("fail_fast")
+            .action(ArgAction::SetTrue)
+            .long("fail-fast")
+            .overrides_with("fail_fast")
+            .help("Stop at the first file that cannot be opened or read"))
+        .arg(Arg::new("nonblocking")
+            .action(ArgAction::SetTrue)
+            .long("nonblocking")
+            .conflicts_with("open_timeout")
+            .help("Give up on the FIFOs without a writer instead of waiting for one"))
+        .arg(Arg::new("open_timeout")
+            .action(ArgAction::Set)
+            .long("open-timeout")
+            .value_name("DURATION")
+            .value_parser(parse_duration)
+            .help("Give up on the FIFOs still without a writer after DURATION, e.g. 5 or 500ms"))
+        .arg(Arg::new("stdin_timeout")
+            .action(ArgAction::Set)
+            .long("stdin-timeout")
+            .value_name("DURATION")
+            .value_parser(parse_duration)
+            .help("Give up on the standard input if no data arrives within DURATION, e.g. 5 or 500ms"))
+        .arg(Arg::new("follow")
+            .action(ArgAction::SetTrue)
+            .short('f')
+            .long("follow")
+            .overrides_with("follow")
+            .help("Keep printing data appended to the files until interrupted"))
+        .arg(Arg::new("follow_name")
+            .action(ArgAction::SetTrue)
+            .short('F')
+            .long("follow-name")
+            .overrides_with("follow_name")
+            .help("Like --follow, but reopen the files when they are truncated or rotated"))
+        .arg(Arg::new("max_lines")
+            .action(ArgAction::Set)
+            .long("max-lines")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .help("Print only the first N lines of each file"))
+        .arg(Arg::new("skip_lines")
+            .action(ArgAction::Set)
+            .long("skip-lines")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("0")
+            .help("Skip the first N lines of each file"))
+        .arg(Arg::new("lines")
+            .action(ArgAction::Set)
+            .long("lines")
+            .value_name("START:END")
+            .value_parser(parse_range)
+            .conflicts_with_all(["skip_lines", "max_lines"])
+            .help("Print only lines START to END of each file, END may be left out"))
+        .arg(Arg::new("number_original")
+            .action(ArgAction::SetTrue)
+            .long("number-original")
+            .overrides_with("number_original")
+            .help("Number lines by their position in the file rather than in the output"))
+        .arg(Arg::new("skip_bytes")
+            .action(ArgAction::Set)
+            .long("skip-bytes")
+            .value_name("BYTES")
+            .value_parser(parse_size)
+            .help("Skip the first BYTES bytes of each file, e.g. 4K or 1M"))
+        .arg(Arg::new("count_bytes")
+            .action(ArgAction::Set)
+            .long("count-bytes")
+            .value_name("BYTES")
+            .value_parser(parse_size)
+            .help("Print only BYTES bytes of each file"))
+        .arg(Arg::new("keep_bom")
+            .action(ArgAction::SetTrue)
+            .long("keep-bom")
+            .overrides_with("keep_bom")
+            .help("Print the byte order marks the files start with"))
+        .arg(Arg::new("from_encoding")
+            .action(ArgAction::Set)
+            .long("from-encoding")
+            .value_name("ENCODING")
+            .value_parser(encoding::parse)
+            .help("Transcode the files from ENCODING to UTF-8, e.g. utf-16le, latin1 or shift_jis"))
+        .arg(Arg::new("to_encoding")
+            .action(ArgAction::Set)
+            .long("to-encoding")
+            .value_name("ENCODING")
+            .value_parser(encoding::parse)
+            .help("Transcode the output from UTF-8 to ENCODING, e.g. utf-16le for Windows programs"))
+        .arg(Arg::new("reverse")
+            .action(ArgAction::SetTrue)
+            .long("reverse")
+            .overrides_with("reverse")
+            .conflicts_with_all(["follow", "follow_name", "skip_bytes", "count_bytes"])
+            .help("Print the lines of each file last to first"))
+        .arg(Arg::new("squeeze_repeats")
+            .action(ArgAction::SetTrue)
+            .long("squeeze-repeats")
+            .overrides_with("squeeze_repeats")
+            .help("Collapse runs of identical adjacent lines into one"))
+        .arg(Arg::new("count_repeats")
+            .action(ArgAction::SetTrue)
+            .long("count-repeats")
+            .overrides_with("count_repeats")
+            .help("Like --squeeze-repeats, and tell how many times each collapsed line appeared"))
+        .arg(Arg::new("strip_ansi")
+            .action(ArgAction::SetTrue)
+            .long("strip-ansi")
+            .overrides_with("strip_ansi")
+            .help("Remove ANSI escape sequences from the input"))
+        .arg(Arg::new("check_line_endings")
+            .action(ArgAction::SetTrue)
+            .long("check-line-endings")
+            .overrides_with("check_line_endings")
+            .help("Warn about the files mixing LF and CRLF line endings or having bare CRs"))
+        .arg(Arg::new("lf")
+            .action(ArgAction::SetTrue)
+            .long("lf")
+            .overrides_with("lf")
+            .conflicts_with("crlf")
+            .help("End the lines with LF, converting the CRLF endings"))
+        .arg(Arg::new("crlf")
+            .action(ArgAction::SetTrue)
+            .long("crlf")
+            .overrides_with("crlf")
+            .help("End the lines with CRLF, converting the LF endings"))
+        .arg(Arg::new("compact_numbers")
+            .action(ArgAction::SetTrue)
+            .long("compact-numbers")
+            .overrides_with("compact_numbers")
+            .help("Do not align the line numbers in 6 columns")
+            .conflicts_with("number_width"))
+        .arg(Arg::new("number_width")
+            .action(ArgAction::Set)
+            .long("number-width")
+            .value_name("COLUMNS")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("6")
+            .help("Minimum width of the line numbers"))
+        .arg(Arg::new("number_pad")
+            .action(ArgAction::Set)
+            .long("number-pad")
+            .value_name("PAD")
+            .value_parser(["spaces", "zeros"])
+            .default_value("spaces")
+            .help("Pad the line numbers with spaces or zeros"))
+        .arg(Arg::new("number_separator")
+            .action(ArgAction::Set)
+            .long("number-separator")
+            .value_name("SEPARATOR")
+            .default_value("\t")
+            .hide_default_value(true)
+            .help("Text between the line numbers and the lines, a tab by default"))
+        .arg(Arg::new("number_format")
+            .action(ArgAction::Set)
+            .long("number-format")
+            .value_name("BASE")
+            .value_parser(["dec", "hex", "oct"])
+            .default_value("dec")
+            .help("Base of the line numbers"))
+        .arg(Arg::new("number_start")
+            .action(ArgAction::Set)
+            .long("number-start")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("1")
+            .help("Number of the first numbered line"))
+        .arg(Arg::new("number_step")
+            .action(ArgAction::Set)
+            .long("number-step")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize))
+            .default_value("1")
+            .help("Increment between the numbers of two lines"))
+        .arg(Arg::new("restart_numbering")
+            .action(ArgAction::SetTrue)
+            .long("restart-numbering")
+            .overrides_with_all(["restart_numbering", "continuous_numbering"])
+            .help("Start the numbering over with every file"))
+        .arg(Arg::new("continuous_numbering")
+            .action(ArgAction::SetTrue)
+            .long("continuous-numbering")
+            .overrides_with_all(["continuous_numbering", "restart_numbering"])
+            .help("Go on with the numbering from one file to the next (default)"))
+        .arg(Arg::new("with_filename")
+            .action(ArgAction::SetTrue)
+            .short('H')
+            .long("with-filename")
+            .overrides_with("with_filename")
+            .help("Prefix every line with path:line:"))
+        .arg(Arg::new("header")
+            .action(ArgAction::Set)
+            .long("header")
+            .value_name("TEMPLATE")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("==> {name} <==")
+            .help("Print a banner before every file, {name} is replaced with the file name"))
+        .arg(Arg::new("file_separator")
+            .action(ArgAction::Set)
+            .long("file-separator")
+            .value_name("STRING")
+            .value_parser(unescape)
+            .allow_hyphen_values(true)
+            .help("Print STRING between files, escapes such as \\n and \\f are understood"))
+        .arg(Arg::new("timestamps")
+            .action(ArgAction::Set)
+            .long("timestamps")
+            .value_name("FORMAT")
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_missing_value("%Y-%m-%dT%H:%M:%S%.3f")
+            .value_parser(parse_time_format)
+            .help("Prefix every line with the time it is printed at, in the strftime FORMAT"))
+        .arg(Arg::new("render")
+            .action(ArgAction::Set)
+            .long("render")
+            .value_name("FORMAT")
+            .value_parser(["auto", "markdown", "never"])
+            .default_value("never")
+            .help("Render Markdown on colored output: auto (by extension), markdown or never"))
+        .arg(Arg::new("pretty_json")
+            .action(ArgAction::SetTrue)
+            .long("pretty-json")
+            .overrides_with("pretty_json")
+            .help("Re-indent the files as JSON, done for .json files on terminals anyway"))
+        .arg(Arg::new("hex")
+            .action(ArgAction::SetTrue)
+            .short('x')
+            .long("hex")
+            .overrides_with("hex")
+            .conflicts_with_all(["follow", "follow_name", "reverse"])
+            .help("Dump the bytes in hexadecimal like xxd"))
+        .arg(Arg::new("dump")
+            .action(ArgAction::Set)
+            .long("dump")
+            .value_name("FORMAT")
+            .value_parser(["hex", "octal", "decimal"])
+            .conflicts_with_all(["hex", "follow", "follow_name", "reverse"])
+            .help("Dump the bytes in hex, octal or decimal like od"))
+        .arg(Arg::new("dump_group")
+            .action(ArgAction::Set)
+            .long("dump-group")
+            .value_name("BYTES")
+            .value_parser(["1", "2", "4", "8"])
+            .help("Number of bytes shown as a single number in a dump"))
+        .arg(Arg::new("base64_encode")
+            .action(ArgAction::SetTrue)
+            .long("base64-encode")
+            .overrides_with("base64_encode")
+            .conflicts_with_all(["base64_decode", "hex", "dump", "follow", "follow_name", "reverse"])
+            .help("Encode the files to base64"))
+        .arg(Arg::new("base64_decode")
+            .action(ArgAction::SetTrue)
+            .long("base64-decode")
+            .overrides_with("base64_decode")
+            .conflicts_with_all(["hex", "dump", "follow", "follow_name", "reverse"])
+            .help("Decode the files from base64, line breaks are ignored"))
+        .arg(Arg::new("no_glob")
+            .action(ArgAction::SetTrue)
+            .long("no-glob")
+            .help("Do not expand the patterns among the files, such as a quoted \"*.log\""))
+        .arg(Arg::new("files_from")
+            .action(ArgAction::Set)
+            .long("files-from")
+            .value_name("FILE")
+            .help("Read the paths of the files to print from FILE, one per line, - for standard input"))
+        .arg(Arg::new("files_from0")
+            .action(ArgAction::Set)
+            .long("files-from0")
+            .value_name("FILE")
+            .conflicts_with("files_from")
+            .help("Read the paths of the files to print from FILE, each ending with a NUL byte"))
+        .arg(Arg::new("unix_socket")
+            .action(ArgAction::Append)
+            .long("unix-socket")
+            .value_name("PATH")
+            .help("Print what is received from the Unix domain socket at PATH, same as a unix://PATH file"))
+        .arg(Arg::new("listen")
+            .action(ArgAction::Set)
+            .long("listen")
+            .value_name("HOST:PORT")
+            .help("Accept a single TCP connection on HOST:PORT and print what it sends, like nc -l"))
+        .arg(Arg::new("recursive")
+            .action(ArgAction::SetTrue)
+            .short('r')
+            .long("recursive")
+            .help("Print the files of the directories and of their subdirectories, e.g. with --header"))
+        .arg(Arg::new("no_ignore")
+            .action(ArgAction::SetTrue)
+            .long("no-ignore")
+            .help("Print the files ignored by .gitignore and .ignore files with --recursive"))
+        .arg(Arg::new("hidden")
+            .action(ArgAction::SetTrue)
+            .long("hidden")
+            .help("Print the hidden files and directories with --recursive"))
+        .arg(Arg::new("binary")
+            .action(ArgAction::Set)
+            .long("binary")
+            .value_name("WHEN")
+            .value_parser(["skip", "raw", "hex"])
+            .help("Skip, print or hexdump the binary files with --recursive"))
+        .arg(Arg::new("force")
+            .action(ArgAction::SetTrue)
+            .long("force")
+            .help("Print the binary files on a terminal too"))
+        .arg(Arg::new("decompress")
+            .action(ArgAction::SetTrue)
+            .long("decompress")
+            .overrides_with("no_decompress")
+            .help("Decompress every compressed input, the standard input included, not only the .gz, .xz... files"))
+        .arg(Arg::new("no_decompress")
+            .action(ArgAction::SetTrue)
+            .long("no-decompress")
+            .overrides_with("decompress")
+            .help("Print the compressed files as they are"))
+        .arg(Arg::new("archive_member")
+            .action(ArgAction::Set)
+            .long("archive-member")
+            .value_name("MEMBER")
+            .help("Print MEMBER out of the tar or zip archives, also given as ARCHIVE:MEMBER"))
+        .arg(Arg::new("list_members")
+            .action(ArgAction::SetTrue)
+            .long("list-members")
+            .conflicts_with("archive_member")
+            .help("Print the names of the members of the tar or zip archives"))
+        .arg(Arg::new("paging")
+            .action(ArgAction::Set)
+            .long("paging")
+            .value_name("WHEN")
+            .value_parser(["auto", "always", "never"])
+            .default_value("auto")
+            .help("Pipe the output through $PAGER or less: auto (on terminals), always or never"))
+        .arg(Arg::new("plain")
+            .action(ArgAction::SetTrue)
+            .short('p')
+            .long("plain")
+            .overrides_with("plain")
+            .help("Switch off the decorations enabled by the options before it"));
+    let command = stats::args(checksum::args(tui::args(highlight::args(style::args(filters::args(command))))));
+    serve::args(clipboard::args(serial::args(timings::args(command))))
 }
 
 /// The `get_args` function is used to parse command line arguments and return a Config struct.
@@ -74,8 +1171,8 @@ fn build_cli() -> Command {
 ///
 /// # Returns
 ///
-/// * `Result<Config, Box<dyn Error>>` - Returns a `Config` struct that contains the parsed command-line arguments.
-/// In case of any errors, returns an error of type `Box<dyn Error>`.
+/// * `Result<Config, MinicatError>` - Returns a `Config` struct that contains the parsed command-line arguments.
+///   In case of any errors, returns an error of type `MinicatError`.
 ///
 /// # Errors
 ///
@@ -85,98 +1182,1609 @@ fn build_cli() -> Command {
 /// # Example
 ///
 /// ```no_run
+/// # use rust_minicat::get_args;
 /// let config = get_args().unwrap();
 /// println!("{:?}", config);
 /// ```
-pub fn get_args() -> Result<Config, Box<dyn Error>> {
+pub fn get_args() -> Result<Config, MinicatError> {
     let matches = build_cli().get_matches();
-    let files = matches
-        .get_many("files")
-        .expect("at least one file")
-        .map(|x: &String| x.to_owned())
-        .collect::<Vec<String>>();
+    if highlight::list_themes(&matches, &mut io::stdout().lock())? {
+        // like `--version`, listing the themes is all there is to do
+        std::process::exit(0);
+    }
+    let list = match (matches.get_one::<String>("files_from"), matches.get_one::<String>("files_from0")) {
+        (Some(path), _) => Some(inputs::read_list(path, b'\n')?),
+        (None, Some(path)) => Some(inputs::read_list(path, b'\0')?),
+        (None, None) => None,
+    };
+    // the standard input is only read by default when there is no list nor socket either
+    let others = list.is_some()
+        || matches.contains_id("unix_socket")
+        || matches.contains_id("listen")
+        || serial::input_from_matches(&matches).is_some();
+    let files = if others && matches.value_source("files") == Some(ValueSource::DefaultValue) {
+        Vec::new()
+    } else {
+        matches.get_many("files").expect("at least one file").map(|x: &String| x.to_owned()).collect()
+    };
+    let mut files = if matches.get_flag("no_glob") { files } else { inputs::expand_globs(files) };
+    files.extend(list.unwrap_or_default());
+    files.extend(matches.get_many::<String>("unix_socket").into_iter().flatten().map(|path| socket::unix_url(path)));
+    files.extend(matches.get_one::<String>("listen").map(|address| socket::listen_url(address)));
+    files.extend(serial::input_from_matches(&matches));
+    let files = if matches.get_flag("recursive") {
+        let selection = Selection { ignored: matches.get_flag("no_ignore"), hidden: matches.get_flag("hidden") };
+        inputs::expand_directories(files, selection)
+    } else {
+        files
+    };
+    // `--plain` switches off the decorations given before it, the ones given after it still apply
+    // the flag has an index even when it is not given, the one of its default value after the command line
+    let plain = matches.indices_of("plain").filter(|_| matches.get_flag("plain")).and_then(Iterator::max);
+    let decoration = |id: &str| {
+        matches.value_source(id) == Some(ValueSource::CommandLine)
+            && matches.indices_of(id).and_then(Iterator::max) > plain
+    };
+    // `-A`, `-e` and `-t` are shorthands, so they only ever switch the individual display flags on
+    let show_all = decoration("show_all");
+    let e = decoration("e");
+    let t = decoration("t");
+    let utf8 = if matches.get_flag("lossy") {
+        Utf8Mode::Lossy
+    } else if matches.get_flag("strict") {
+        Utf8Mode::Strict
+    } else {
+        Utf8Mode::Raw
+    };
+    let (skip_lines, max_lines) = match matches.get_one::<(usize, Option<usize>)>("lines") {
+        Some(&(start, end)) => (start - 1, end.map(|end| end - start + 1)),
+        None => (*matches.get_one("skip_lines").expect("has a default value"), matches.get_one("max_lines").copied()),
+    };
+    let number_width = if matches.get_flag("compact_numbers") {
+        0
+    } else {
+        *matches.get_one("number_width").expect("has a default value")
+    };
+    let number_pad = match matches.get_one::<String>("number_pad").map(String::as_str) {
+        Some("zeros") => NumberPad::Zeros,
+        _ => NumberPad::Spaces,
+    };
+    let number_separator = matches.get_one::<String>("number_separator").expect("has a default value");
+    let number_base = match matches.get_one::<String>("number_format").map(String::as_str) {
+        Some("hex") => NumberBase::Hexadecimal,
+        Some("oct") => NumberBase::Octal,
+        _ => NumberBase::Decimal,
+    };
+    let tabstop = *matches.get_one("tabstop").expect("has a default value");
+    let tabs = if matches.get_flag("expand_tabs") {
+        Tabs::Expand(tabstop)
+    } else if matches.get_flag("unexpand") {
+        Tabs::Unexpand(tabstop)
+    } else {
+        Tabs::Keep
+    };
+    // the gutter is expanded as well, so the lines start at the same column as the tabs of the gutter put them
+    let number_separator = tabs.separator(number_separator, number_width);
+    let number_format = LineNumberFormatter::new(number_width, number_pad, number_separator, number_base);
+    let interactive = tui::requested(&matches);
+    let serve = serve::requested(&matches);
+    // the files checked with `--verify` have to be printed as they are
+    let verify = checksum::sums_from_matches(&matches)?;
+    // `--plain` switches the colors off too, unless `--color` comes after it, the viewer draws its own, a
+    // served page is plain text, and the colors of the files checked would be hashed with them
+    let color = match plain {
+        _ if interactive || serve.is_some() || verify.is_some() => false,
+        Some(_) if !decoration("color") => false,
+        _ => ColorChoice::from_matches(&matches).enabled(),
+    };
+    let syntax = Syntaxes::from_matches(&matches, color);
+    let palette = if color { syntax.palette().unwrap_or(Palette::COLORED) } else { Palette::default() };
+    let render = match matches.get_one::<String>("render").map(String::as_str) {
+        Some("auto") if color => Render::Auto,
+        Some("markdown") if color => Render::Markdown,
+        _ => Render::Never,
+    };
+    let pretty_json = if matches.get_flag("pretty_json") {
+        JsonMode::Always
+    } else if plain.is_none() && verify.is_none() && terminal::stdout_is_terminal() {
+        JsonMode::Auto
+    } else {
+        JsonMode::Never
+    };
+    let dump = match matches.get_one::<String>("dump").map(String::as_str) {
+        Some("octal") => Some(DumpFormat::Octal),
+        Some("decimal") => Some(DumpFormat::Decimal),
+        Some(_) => Some(DumpFormat::Hex),
+        None => matches.get_flag("hex").then_some(DumpFormat::Hex),
+    };
+    let binary = match matches.get_one::<String>("binary").map(String::as_str) {
+        _ if !matches.get_flag("recursive") => BinaryMode::Raw,
+        Some("raw") => BinaryMode::Raw,
+        Some("hex") => BinaryMode::Hex,
+        _ => BinaryMode::Skip,
+    };
+    let fifo_wait = if matches.get_flag("nonblocking") {
+        FifoWait::Never
+    } else {
+        matches.get_one::<Duration>("open_timeout").map_or(FifoWait::Forever, |&timeout| FifoWait::Timeout(timeout))
+    };
+    let pace = if let Some(&rate) = matches.get_one::<u64>("rate") {
+        Some(Pace::Bytes(rate))
+    } else if let Some(&rate) = matches.get_one::<f64>("lines_per_sec") {
+        Some(Pace::Lines(rate))
+    } else {
+        let chars = matches.get_one::<String>("delay_per").is_some_and(|unit| unit == "char");
+        matches.get_one::<Duration>("delay").filter(|delay| !delay.is_zero()).map(|&delay| Pace::delay(delay, chars))
+    };
+    let limit = Limit {
+        bytes: matches.get_one::<usize>("max_bytes").map(|&bytes| bytes as u64),
+        lines: matches.get_one::<u64>("max_output_lines").copied(),
+    };
+    let dump_group = match matches.get_one::<String>("dump_group") {
+        Some(group) => group.parse().expect("one of the possible values"),
+        None => dump.map_or(1, DumpFormat::default_group),
+    };
+    let base64 = if matches.get_flag("base64_encode") {
+        Some(Base64Mode::Encode)
+    } else if matches.get_flag("base64_decode") {
+        Some(Base64Mode::Decode)
+    } else {
+        None
+    };
+    // a mark is only at the very start of a file, and the dumps and the files checked show the bytes as they are
+    let strip_bom = !matches.get_flag("keep_bom")
+        && verify.is_none()
+        && matches.get_one::<usize>("skip_bytes").is_none_or(|&skip| skip == 0)
+        && dump.is_none()
+        && base64.is_none();
+    let stats = stats::from_matches(&matches);
+    let decompression = if matches.get_flag("decompress") {
+        Decompression::Always
+    // the files checked are the compressed ones, the way `sha256sum` hashed them
+    } else if matches.get_flag("no_decompress") || verify.is_some() {
+        Decompression::Never
+    } else {
+        Decompression::Auto
+    };
+    let paging = match matches.get_one::<String>("paging").map(String::as_str) {
+        // there is nothing to page when only the summary is written
+        _ if stats == StatsMode::Only => Paging::Never,
+        Some("always") => Paging::Always,
+        Some("never") => Paging::Never,
+        _ => Paging::Auto,
+    };
+    let follow = if matches.get_flag("follow_name") {
+        FollowMode::Name
+    } else if matches.get_flag("follow") {
+        FollowMode::Descriptor
+    } else {
+        FollowMode::Off
+    };
 
     Ok(Config{
-        files: files,
-        count_lines: matches.get_flag("number"),
-        nonblank_number: matches.get_flag("nonblank")
+        files,
+        count_lines: decoration("number"),
+        nonblank_number: decoration("nonblank"),
+        show_ends: show_all || e || decoration("show_ends"),
+        show_tabs: show_all || t || decoration("show_tabs"),
+        tabs,
+        wrap: matches.get_one::<Option<usize>>("wrap").and_then(|columns| columns.or_else(terminal::width)),
+        truncate: matches.get_one("truncate").copied(),
+        show_nonprinting: show_all || e || t || decoration("show_nonprinting"),
+        squeeze_blank: matches.get_flag("squeeze_blank"),
+        unbuffered: matches.get_flag("unbuffered"),
+        utf8,
+        mmap: matches.get_flag("mmap"),
+        jobs: *matches.get_one("jobs").expect("has a default value"),
+        buffer_size: *matches.get_one("buffer_size").expect("has a default value"),
+        drop_cache: matches.get_flag("drop_cache"),
+        error_policy: if matches.get_flag("fail_fast") { ErrorPolicy::FailFast } else { ErrorPolicy::Continue },
+        follow,
+        max_lines,
+        skip_lines,
+        number_original: matches.get_flag("number_original"),
+        skip_bytes: matches.get_one("skip_bytes").copied().unwrap_or(0),
+        count_bytes: matches.get_one("count_bytes").copied(),
+        strip_bom,
+        encoding: matches.get_one("from_encoding").copied(),
+        output_encoding: matches.get_one("to_encoding").copied(),
+        reverse: matches.get_flag("reverse"),
+        squeeze_repeats: matches.get_flag("squeeze_repeats") || matches.get_flag("count_repeats"),
+        count_repeats: matches.get_flag("count_repeats"),
+        strip_ansi: matches.get_flag("strip_ansi"),
+        check_endings: matches.get_flag("check_line_endings"),
+        line_ending: if matches.get_flag("lf") {
+            LineEnding::Lf
+        } else if matches.get_flag("crlf") {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Keep
+        },
+        filters: Filters::from_matches(&matches),
+        highlight: Highlighter::from_matches(&matches, color, palette.matched),
+        whitespace_issues: style::whitespace_issues_from_matches(&matches, color),
+        syntax,
+        render,
+        pretty_json,
+        color,
+        palette,
+        number_format,
+        number_start: *matches.get_one("number_start").expect("has a default value"),
+        number_step: *matches.get_one("number_step").expect("has a default value"),
+        numbering: if matches.get_flag("restart_numbering") { Numbering::Restart } else { Numbering::Continuous },
+        with_filename: decoration("with_filename"),
+        header: matches.get_one("header").filter(|_| decoration("header")).cloned(),
+        file_separator: matches.get_one("file_separator").filter(|_| decoration("file_separator")).cloned(),
+        timestamps: matches.get_one("timestamps").filter(|_| decoration("timestamps")).cloned(),
+        paging,
+        dump,
+        dump_group,
+        base64,
+        interactive,
+        checksums: checksum::from_matches(&matches),
+        verify,
+        stats,
+        timings: timings::from_matches(&matches),
+        decompression,
+        archive_member: matches.get_one("archive_member").cloned(),
+        list_members: matches.get_flag("list_members"),
+        binary,
+        force: matches.get_flag("force"),
+        baud: serial::baud_from_matches(&matches),
+        fifo_wait,
+        stdin_timeout: matches.get_one::<Duration>("stdin_timeout").copied(),
+        output: matches.get_one("output").cloned(),
+        quiet: matches.get_flag("quiet"),
+        output_mode: if matches.get_flag("append") {
+            OutputMode::Append
+        } else if matches.get_flag("output_tmp_then_rename") {
+            OutputMode::Replace
+        } else {
+            OutputMode::Truncate
+        },
+        split: match (matches.get_one::<usize>("split_lines"), matches.get_one::<usize>("split_bytes")) {
+            (Some(&lines), _) => Some(SplitLimit::Lines(lines)),
+            (None, Some(&bytes)) => Some(SplitLimit::Bytes(bytes)),
+            (None, None) => None,
+        },
+        clipboard: clipboard::from_matches(&matches),
+        serve,
+        pace,
+        limit,
     })
 }
 /// This function accepts a `Config` object and processes each file included in the `Config` object's `files` vector.
-/// It handles file opening, checking the lines, and printing.
+/// It handles file opening, checking the lines, and printing to the standard output, which is locked and
+/// buffered for the whole run and flushed once at the end.
 ///
 /// # Arguments
 ///
 /// * `config`: An instance of `Config` class which contains the configuration for the program. It includes line counting preference,
-/// non-blank line counting preference, and the list of file names to be processed.
+///   non-blank line counting preference, and the list of file names to be processed.
 ///
 /// # Returns
 ///
 /// * On success, an `Ok(())` is returned.
-/// * On failure, an `Err` variant with a boxed `Error` instance is returned.
+/// * On failure, an `Err` variant with a `MinicatError` is returned.
 ///
 /// # Errors
 ///
-/// The function will return an error if there is an issue when trying to open or read the lines of the files.
+/// The function will return an error if there is an issue when trying to write the output. Files that cannot
+/// be opened or read are reported on the standard error as they come, the rest of the files is still printed,
+/// and `MinicatError::FilesFailed` is returned at the end. With `ErrorPolicy::FailFast`, the error of the first
+/// such file is returned right away instead.
+///
+/// With `follow`, the function only returns once the program is interrupted, with `MinicatError::Interrupted`.
+///
+/// When the output is paged (see `Paging`), the function returns once the pager has been quit. Quitting it
+/// before the end of the output is not an error. With `interactive`, the output is shown in the viewer of the
+/// `tui` module instead, and the function returns once it has been quit.
 ///
 /// # Example
 ///
-/// ```
-/// let config = Config {
-///     files: vec!["./src/main.rs", "./src/lib.rs"],
-///     count_lines: true,
-///     nonblank_number: false
-/// };
+/// ```no_run
+/// # use rust_minicat::{get_args, run};
+/// let config = get_args().unwrap();
 ///
 /// match run(config) {
 ///     Ok(()) => println!("Files processed successfully."),
 ///     Err(e) => eprintln!("An error occurred: {}", e),
 /// }
 /// ```
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    for filename in config.files {
-        match open_file(&filename) {
-            Ok(file) => {
-                // dbg!("Opened file {}", filename);
-                let mut blank_count: usize = 0;
-                for (number, line) in file.lines().enumerate() {
-                    if let Ok(line) = line {
-                        if config.count_lines {
-                            println!("{}\t{}", number + 1, line);
-                        } else if config.nonblank_number {
-                            if line.is_empty() {
-                                blank_count += 1;
-                                println!("{}", line);
-                            } else {
-                                println!("{}\t{}", number + 1 - blank_count, line);
-                            }
-                        } else {
-                            println!("{}", line);
-                        }
-                    }
+pub fn run(mut config: Config) -> Result<(), MinicatError> {
+    if config.interactive {
+        return tui::view(&config);
+    }
+    if let Some(address) = &config.serve {
+        return serve::serve(&config, address);
+    }
+    let file = config
+        .output
+        .as_deref()
+        .map(|path| OutputFile::create(path, config.output_mode, config.split, config.buffer_size))
+        .transpose()?;
+    let mut copies = Copies::new(file, config.clipboard);
+    if config.quiet {
+        let result = cat(&config, &mut copies, false);
+        return finish_copies(result, copies);
+    }
+    let interactive = config.follow != FollowMode::Off
+        || (io::stdin().is_terminal() && config.files.iter().any(|filename| filename == "-"));
+    if let Some(mut pager) = Pager::spawn(config.paging, interactive) {
+        let mut out = Tee::new(BufWriter::with_capacity(config.buffer_size, pager.input()), copies);
+        let result = cat(&config, &mut out, false);
+        // the pager only sees the end of the output once its input is closed
+        let copies = out.into_copy();
+        pager.wait();
+        return match finish_copies(result, copies) {
+            Err(e) if e.is_broken_pipe() => Ok(()),
+            result => result,
+        };
+    }
+    // the binary files would garble the terminal, a pager shows them safely
+    config.binary = terminal::binary_mode(config.binary, config.force);
+    let mut out = Tee::new(BufWriter::with_capacity(config.buffer_size, io::stdout().lock()), copies);
+    let result = cat(&config, &mut out, true);
+    // the output file only replaces the previous one, and the clipboard is only set, once everything is printed
+    finish_copies(result, out.into_copy())
+}
+
+/// Finishes the copies of the output once `cat` has returned `result`, see `Copies::finish`, and returns the
+/// result of both. Following the files only ends when the program is interrupted, so the copies are finished
+/// then as well, with what was printed until Ctrl+C was pressed.
+fn finish_copies(result: Result<(), MinicatError>, copies: Copies) -> Result<(), MinicatError> {
+    match result {
+        Ok(()) => copies.finish(),
+        Err(MinicatError::Interrupted) => copies.finish().and(Err(MinicatError::Interrupted)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Does the same as `run`, but writes the output into the given writer instead of the standard output.
+///
+/// # Arguments
+///
+/// * `config`: An instance of `Config` class which contains the configuration for the program.
+/// * `out`: The writer the output is written into. It is flushed before the function returns.
+///
+/// # Returns
+///
+/// * On success, an `Ok(())` is returned.
+/// * On failure, an `Err` variant with a `MinicatError` is returned.
+///
+/// # Errors
+///
+/// The function will return an error if there is an issue when trying to write into `out`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use rust_minicat::{get_args, run_with_writer};
+/// let config = get_args()?;
+/// let mut output = Vec::new();
+/// run_with_writer(config, &mut output)?;
+/// # Ok::<(), rust_minicat::MinicatError>(())
+/// ```
+pub fn run_with_writer<W: Write>(config: Config, out: &mut W) -> Result<(), MinicatError> {
+    cat(&config, out, false)
+}
+
+/// Processes the files of the `Config` into `out`, the common part of `run` and `run_with_writer`.
+///
+/// `to_stdout` tells whether `out` ends up in the standard output of the process, in which case the
+/// files may also be transferred to the standard output directly, bypassing `out` (see `copy_fast`).
+fn cat<W: Write>(config: &Config, out: &mut W, to_stdout: bool) -> Result<(), MinicatError> {
+    let mut timings = Timings::new(config.timings);
+    // the waits of the pacing are not writes, so they are left out of the timings
+    let mut counted = Counted::new(Paced::new(Timed(Limited::new(out, config.limit)), config.pace), config.stats);
+    let mut checksummed = Checksummed::new(&mut counted, &config.hashes());
+    // the output is transcoded before anything else, so it is hashed, counted and capped as it is written
+    let out = &mut Encoded::new(&mut checksummed, config.output_encoding);
+    // the output can only be one of the input files if it is the standard output or the output file
+    let output_ids: Vec<FileId> = [
+        if to_stdout { io_backend::stdout_id() } else { None },
+        // the file replaced at the end is only read, the output going into a temporary file until then
+        config.output.as_deref().filter(|_| config.output_mode != OutputMode::Replace).and_then(io_backend::input_id),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    // the bytes spliced into the standard output would be neither hashed, counted, timed, copied, paced, capped
+    // nor transcoded
+    let to_stdout = to_stdout
+        && config.output_encoding.is_none()
+        && config.pace.is_none()
+        && !config.limit.is_set()
+        && !out.get_mut().is_active()
+        && !out.get_mut().get_mut().is_active()
+        && !config.timings
+        && config.output.is_none()
+        && !config.clipboard;
+    let mut printer = LinePrinter::new(config);
+    let prefetcher = (config.jobs > 0).then(|| Prefetcher::spawn(config.files.clone(), config.jobs));
+    // the standard input is read to its end, there is nothing to follow on a pipe or a terminal
+    let follows = |filename: &str| config.follow != FollowMode::Off && filename != "-";
+    let banners = config.files.iter().filter(|filename| follows(filename)).count() > 1;
+    let mut followers = Followers::new(config.follow, banners);
+    let mut renderer = config.renderer();
+    let mut failed: usize = 0;
+    let mut headers: usize = 0;
+    let mut printed: usize = 0;
+    // the notice of the `Limit` once it is reached, after which nothing else is printed
+    let mut truncated = None;
+    for filename in &config.files {
+        let prefetched = prefetcher.as_ref().and_then(Prefetcher::next);
+        // what was read ahead may be compressed or an archive, it is read again through `open_file` then
+        let prefetched = prefetched.filter(|_| !config.transforms_input(filename));
+        // a dump shows the bytes of every file already
+        let binary = renderer.is_none() && is_binary(filename, prefetched.as_ref(), config);
+        if binary && config.binary == BinaryMode::Skip {
+            eprintln!("minicat: {}: binary file skipped", filename);
+            continue;
+        }
+        if binary && config.binary == BinaryMode::Warn {
+            eprintln!("minicat: {}: binary file not printed on the terminal, use --force to print it", filename);
+            continue;
+        }
+        if let Some(separator) = config.file_separator.as_ref().filter(|_| printed > 0) {
+            out.write_all(separator)?;
+        }
+        // several followed files get the banners of `Followers` already
+        if let Some(header) = config.header.as_ref().filter(|_| !(banners && follows(filename))) {
+            write_header(header, filename, headers > 0, &config.palette, out)?;
+            headers += 1;
+        }
+        // a binary file is printed as it is, and so is a mark starting the output
+        printer.file.strip_bom = !binary && config.strips_bom(printed == 0);
+        printed += 1;
+        out.get_mut().start_file();
+        out.get_mut().get_mut().start_file();
+        timings.start_file();
+        let result = if !output_ids.is_empty()
+            && io_backend::input_id(filename).is_some_and(|id| output_ids.contains(&id))
+        {
+            Err(MinicatError::InputIsOutput { path: filename.to_string() })
+        } else if let Err(source) = stdin::wait(filename, config.stdin_timeout) {
+            Err(MinicatError::ReadFailed { path: filename.to_string(), line: None, source })
+        } else if let Some(renderer) = renderer.as_deref_mut() {
+            dump_file(filename, prefetched, config, renderer, out)
+        } else if binary {
+            hexdump_file(filename, prefetched, config, out)
+        } else if follows(filename) {
+            followers.add(filename, &mut printer, out)
+        } else {
+            cat_file(filename, prefetched, &mut printer, to_stdout, out)
+        };
+        // the file the output is truncated in is still ended, so it is counted, timed and hashed as printed
+        truncated = result.as_ref().err().and_then(limit::reached);
+        let result = if truncated.is_some() { Ok(()) } else { result };
+        let result = result.and_then(|()| {
+            printer.end_file(filename);
+            out.get_mut().get_mut().end_file(filename, printer.file.stats.take());
+            timings.end_file(filename);
+            let digests = out.get_mut().end_file(filename);
+            config.verify.as_ref().map_or(Ok(()), |sums| sums.check(filename, digests))
+        });
+        match result {
+            Ok(()) => {}
+            Err(e) if e.is_file_error() && config.error_policy == ErrorPolicy::Continue => {
+                eprintln!("minicat: {}", e);
+                failed += 1;
+            }
+            Err(e) => return Err(e),
+        }
+        if truncated.is_some() {
+            break;
+        }
+    }
+    if let Some(notice) = &truncated {
+        eprintln!("minicat: {}", notice);
+    } else {
+        printer.finish(out)?;
+        if let Some(renderer) = renderer.as_deref_mut() {
+            let mut output = Vec::new();
+            let finished = renderer.finish(&mut output);
+            out.write_all(&output)?;
+            if let Err(message) = finished {
+                // what is left unfinished belongs to the last file
+                let path = config.files.last().cloned().unwrap_or_default();
+                let e = MinicatError::InvalidData { path, message };
+                if config.error_policy == ErrorPolicy::FailFast {
+                    return Err(e);
+                }
+                eprintln!("minicat: {}", e);
+                failed += 1;
+            }
+        }
+        out.finish()?;
+    }
+    out.flush()?;
+    checksum::report(&out.get_mut().finish(), &config.checksums, &mut io::stderr().lock())?;
+    out.get_mut().get_mut().report(&mut io::stderr().lock())?;
+    timings.report(&mut io::stderr().lock())?;
+    if !followers.is_empty() && truncated.is_none() {
+        if let Err(e) = followers.run(&mut printer, out) {
+            let notice = limit::reached(&e).ok_or(e)?;
+            eprintln!("minicat: {}", notice);
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        _ => Err(MinicatError::FilesFailed(failed)),
+    }
+}
+
+/// Writes the banner of a file, from a template in which `{name}` stands for the name of the file.
+///
+/// `separate` tells whether a file has been printed before, in which case a blank line separates the banner
+/// from it, the same as `head` does. The banner is painted in the header style of the `Palette`.
+fn write_header<W: Write>(
+    template: &str,
+    filename: &str,
+    separate: bool,
+    palette: &Palette,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    if separate {
+        writeln!(out)?;
+    }
+    palette.write_header(out, &template.replace("{name}", filename))?;
+
+    Ok(())
+}
+
+/// Prints a single file into `out`, picking the fastest way the `Config` allows.
+///
+/// ## Parameters
+/// * `filename` - The name of the file to print, `-` for the standard input.
+/// * `prefetched` - The content of the file if it was read ahead, see `Prefetcher`.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines.
+/// * `to_stdout` - Whether `out` ends up in the standard output, see `copy_fast`.
+/// * `out` - The writer the file is written into.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+/// opened or read, and any other `MinicatError` if writing to `out` fails.
+fn cat_file<W: Write>(
+    filename: &str,
+    prefetched: Option<Prefetched>,
+    printer: &mut LinePrinter,
+    to_stdout: bool,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let config = printer.config;
+    printer.start_file();
+    if config.pretty_json.applies(filename) {
+        return print_json(filename, prefetched, printer, out);
+    }
+    match prefetched {
+        Some(Prefetched::Loaded(data)) => return print_data(filename, &data, printer, out),
+        Some(Prefetched::Failed(source)) => {
+            return Err(MinicatError::OpenFailed { path: filename.to_string(), source });
+        }
+        Some(Prefetched::Deferred) | None => {}
+    }
+    // a compressed file or an archive has to be read by `open_file`, it cannot be copied nor mapped as it is
+    let transformed = config.transforms_input(filename);
+    if !transformed
+        && !config.transforms_lines()
+        && !config.selects_bytes()
+        && copy_fast(filename, config, printer.file.strip_bom, to_stdout, out)?
+    {
+        return Ok(());
+    }
+    if !transformed && config.transforms_lines() && config.mmap && print_mapped(filename, printer, out)? {
+        return Ok(());
+    }
+    if config.reverse {
+        return reverse::print_file(filename, printer, out);
+    }
+    let mut file = open_file(filename, config, printer.file.strip_bom)
+        .map_err(|source| MinicatError::OpenFailed { path: filename.to_string(), source })?;
+    if config.transforms_lines() {
+        print_lines(filename, &mut file, printer, out)
+    } else {
+        copy_raw(filename, &mut file, config, out)
+    }
+}
+
+/// Returns `true` if the file has to be looked at for `--binary`, and looks binary.
+///
+/// Only the regular files are looked at, as they are on the disk: the standard input and the inputs read
+/// through `open_file` are printed as usual.
+fn is_binary(filename: &str, prefetched: Option<&Prefetched>, config: &Config) -> bool {
+    if config.binary == BinaryMode::Raw || filename == "-" || config.transforms_input(filename) {
+        return false;
+    }
+    match prefetched {
+        Some(Prefetched::Loaded(data)) => binary::is_binary_data(data),
+        Some(Prefetched::Failed(_)) => false,
+        Some(Prefetched::Deferred) | None => binary::is_binary(filename),
+    }
+}
+
+/// Dumps a single binary file in hexadecimal for `BinaryMode::Hex`, its offsets starting at 0.
+///
+/// # Errors
+/// The function will return the errors of `dump_file`.
+fn hexdump_file<W: Write>(
+    filename: &str,
+    prefetched: Option<Prefetched>,
+    config: &Config,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let mut renderer = DumpFormat::Hex.renderer(DumpFormat::Hex.default_group());
+    dump_file(filename, prefetched, config, renderer.as_mut(), out)?;
+    let mut output = Vec::new();
+    let finished = renderer.finish(&mut output);
+    out.write_all(&output)?;
+    finished.map_err(|message| MinicatError::InvalidData { path: filename.to_string(), message })
+}
+
+/// Dumps or converts the bytes of a single file through a `Renderer`, after the ones of the previous files.
+///
+/// ## Parameters
+/// * `filename` - The name of the file to dump, `-` for the standard input.
+/// * `prefetched` - The content of the file if it was read ahead, see `Prefetcher`.
+/// * `config` - The `Config` with the selected bytes and the size of the read buffer.
+/// * `renderer` - The `Renderer` of the dump.
+/// * `out` - The writer the dump is written into.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+/// opened or read, `MinicatError::InvalidData` if the `Renderer` rejects its data, and any other `MinicatError`
+/// if writing to `out` fails.
+fn dump_file<W: Write>(
+    filename: &str,
+    prefetched: Option<Prefetched>,
+    config: &Config,
+    renderer: &mut dyn Renderer,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let invalid = |message| MinicatError::InvalidData { path: filename.to_string(), message };
+    let mut output = Vec::new();
+    renderer.start_file();
+    match prefetched {
+        Some(Prefetched::Loaded(data)) => {
+            let rendered = renderer.render(config.select_bytes(&data, false), &mut output);
+            out.write_all(&output)?;
+            return rendered.map_err(invalid);
+        }
+        Some(Prefetched::Failed(source)) => {
+            return Err(MinicatError::OpenFailed { path: filename.to_string(), source });
+        }
+        Some(Prefetched::Deferred) | None => {}
+    }
+    let mut reader = open_file(filename, config, false)
+        .map_err(|source| MinicatError::OpenFailed { path: filename.to_string(), source })?;
+    loop {
+        let chunk = match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(source) => {
+                return Err(MinicatError::ReadFailed { path: filename.to_string(), line: None, source });
+            }
+        };
+        output.clear();
+        let rendered = renderer.render(chunk, &mut output);
+        // what was converted before the error is still written
+        out.write_all(&output)?;
+        rendered.map_err(invalid)?;
+        let length = chunk.len();
+        reader.consume(length);
+    }
+
+    Ok(())
+}
+
+/// Prints a JSON file re-indented by `PrettyJson`, which is read like any other file afterwards.
+///
+/// ## Parameters
+/// * `filename` - The name of the file to print, `-` for the standard input.
+/// * `prefetched` - The content of the file if it was read ahead, see `Prefetcher`.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines.
+/// * `out` - The writer the file is written into.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+/// opened or read, and any other `MinicatError` if writing to `out` fails.
+fn print_json<W: Write>(
+    filename: &str,
+    prefetched: Option<Prefetched>,
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let config = printer.config;
+    let reader: Box<dyn BufRead> = match prefetched {
+        Some(Prefetched::Loaded(data)) => {
+            Box::new(io::Cursor::new(config.select_bytes(&data, printer.file.strip_bom).to_vec()))
+        }
+        Some(Prefetched::Failed(source)) => {
+            return Err(MinicatError::OpenFailed { path: filename.to_string(), source });
+        }
+        Some(Prefetched::Deferred) | None => open_file(filename, config, printer.file.strip_bom)
+            .map_err(|source| MinicatError::OpenFailed { path: filename.to_string(), source })?,
+    };
+    let mut reader = PrettyJson::new(reader, config.color);
+    if config.transforms_lines() {
+        print_lines(filename, &mut reader, printer, out)
+    } else {
+        copy_raw(filename, &mut reader, config, out)
+    }
+}
+
+/// Decides whether the output should be flushed after every line of the given file.
+///
+/// This is the case for the `unbuffered` mode, and also when reading from an interactive terminal or a serial
+/// device, so that the typed or received lines show up right away instead of when the output buffer fills up.
+fn flush_each_line(filename: &str, config: &Config) -> bool {
+    config.unbuffered || (filename == "-" && io::stdin().is_terminal()) || serial::is_serial_url(filename)
+}
+
+/// `LinePrinter` struct holds the state needed to number and render lines one at a time,
+/// independently of where the lines come from.
+///
+/// # Fields
+///
+/// * `config`: The `Config` with the numbering and display options.
+/// * `output`: The buffer a line is rendered into before it is written, reused for every line.
+/// * `file`: The `FileState` of the file being printed.
+struct LinePrinter<'a> {
+    config: &'a Config,
+    output: Vec<u8>,
+    file: FileState<'a>,
+}
+
+/// `FileState` struct is what a `LinePrinter` keeps from one line of a file to the next. `Followers` keeps one
+/// for every followed file, and swaps it into the `LinePrinter` while printing the file.
+///
+/// # Fields
+///
+/// * `previous_blank`: Whether the last printed line was empty, carried between files for `squeeze_blank`.
+/// * `line_number`: The number of the last numbered line, of the current file with `Numbering::Restart`.
+/// * `lines_read`: The number of lines of the current file seen so far, used in error messages and for
+///   `skip_lines`.
+/// * `lines_printed`: The number of lines of the current file printed so far, for `max_lines`.
+/// * `previous_line`: The content of the last printed line, carried between files for `squeeze_repeats`.
+/// * `repeats`: How many times in a row `previous_line` has been seen.
+/// * `syntax`: The `FileHighlighter` of the current file, picked when its first line is read, `None` if the
+///   file is not highlighted.
+/// * `markdown`: The `MarkdownRenderer` of the current file, picked when its first line is read, `None` if the
+///   file is not rendered as Markdown.
+/// * `ansi`: The `AnsiStripper` removing the escape sequences of the current file, which may span lines.
+/// * `endings`: The `LineEndings` of the current file, counted for `check_endings`.
+/// * `strip_bom`: Whether the byte order mark of the current file is taken off, see `Config::strips_bom`.
+/// * `stats`: The `Counter` of the lines printed from the current file, before they are decorated, for `stats`.
+#[derive(Default)]
+struct FileState<'a> {
+    previous_blank: bool,
+    line_number: usize,
+    lines_read: usize,
+    lines_printed: usize,
+    previous_line: Vec<u8>,
+    repeats: usize,
+    syntax: Option<FileHighlighter<'a>>,
+    markdown: Option<MarkdownRenderer>,
+    ansi: AnsiStripper,
+    endings: LineEndings,
+    strip_bom: bool,
+    stats: Option<Counter>,
+}
+
+impl<'a> LinePrinter<'a> {
+    fn new(config: &'a Config) -> Self {
+        LinePrinter {
+            config,
+            output: Vec::new(),
+            file: FileState { strip_bom: config.strip_bom, ..FileState::default() },
+        }
+    }
+
+    /// Swaps the state of the file being printed with `file`. The line number stays where it is unless it
+    /// restarts with every file, it is shared by all of them otherwise.
+    fn swap_file(&mut self, file: &mut FileState<'a>) {
+        mem::swap(&mut self.file, file);
+        if self.config.numbering != Numbering::Restart {
+            mem::swap(&mut self.file.line_number, &mut file.line_number);
+        }
+    }
+
+    /// Writes what is still owed once the last line has been printed, which is the count of the last run
+    /// of repeated lines.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails.
+    fn finish<W: Write>(&mut self, out: &mut W) -> Result<(), MinicatError> {
+        if self.config.count_repeats && self.file.repeats > 1 {
+            writeln!(out, "(repeated {} times)", self.file.repeats)?;
+        }
+        self.file.repeats = 0;
+
+        Ok(())
+    }
+
+    /// Resets the per-file state before the lines of the next file are printed.
+    fn start_file(&mut self) {
+        if self.config.numbering == Numbering::Restart {
+            self.file.line_number = 0;
+        }
+        self.file.lines_read = 0;
+        self.file.lines_printed = 0;
+        self.file.stats = None;
+        self.file.syntax = None;
+        self.file.markdown = None;
+        self.file.ansi.reset();
+        self.file.endings = LineEndings::default();
+    }
+
+    /// Reports on the standard error what is wrong with the line endings of the file printed last, if they are
+    /// checked.
+    fn end_file(&self, filename: &str) {
+        if let Some(warning) = self.file.endings.warning(filename).filter(|_| self.config.check_endings) {
+            eprintln!("minicat: {}", warning);
+        }
+    }
+
+    /// Returns `true` once all the lines wanted from the current file have been printed, so reading the file
+    /// can stop there.
+    fn file_done(&self) -> bool {
+        // the lines dropped by the filters are not part of `max_lines`
+        self.config.max_lines.is_some_and(|max_lines| self.file.lines_printed >= max_lines)
+    }
+
+    /// Numbers, renders and writes a single line, unless it is one of the lines skipped at the start of the file.
+    ///
+    /// ## Parameters
+    /// * `filename` - The name of the file the line comes from, used in error messages.
+    /// * `line` - The line including its terminator, which is written back exactly as it was read:
+    ///   CRLF endings stay CRLF and a missing final newline is not added.
+    /// * `out` - The writer the line is written into.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails, or if the line is not valid
+    /// UTF-8 while the `Utf8Mode::Strict` mode is used.
+    fn print_line<W: Write>(&mut self, filename: &str, line: &[u8], out: &mut W) -> Result<(), MinicatError> {
+        let config = self.config;
+        self.file.lines_read += 1;
+        if config.check_endings {
+            self.file.endings.update(self.file.lines_read, line);
+        }
+        if self.file.lines_read == 1 {
+            self.file.markdown = config.render.for_file(filename);
+            if self.file.markdown.is_none() && config.syntax.is_active() {
+                // the first line tells the language of the scripts starting with a shebang
+                self.file.syntax = config.syntax.for_file(filename, line);
+            }
+        }
+        if self.file.lines_read <= config.skip_lines {
+            return Ok(());
+        }
+        let (content, newline) = match line.strip_suffix(b"\n") {
+            Some(content) => (config.line_ending.strip(content), true),
+            None => (line, false),
+        };
+        let stripped;
+        let content = if config.strip_ansi {
+            // a newline within an escape sequence is removed with it, the line goes on with the next one
+            let Some(content) = self.file.ansi.strip(content, newline) else {
+                return Ok(());
+            };
+            stripped = content;
+            stripped.as_ref()
+        } else {
+            content
+        };
+        if !config.filters.keep(content) {
+            return Ok(());
+        }
+        let rewritten = config.filters.rewrite(content);
+        let content = rewritten.as_ref();
+        let decoded;
+        let content = match config.utf8 {
+            Utf8Mode::Raw => content,
+            Utf8Mode::Lossy => {
+                decoded = String::from_utf8_lossy(content);
+                decoded.as_bytes()
+            }
+            Utf8Mode::Strict => match std::str::from_utf8(content) {
+                Ok(_) => content,
+                Err(source) => {
+                    return Err(MinicatError::InvalidUtf8 {
+                        path: filename.to_string(),
+                        line: self.file.lines_read,
+                        source,
+                    });
                 }
             },
-            Err(e) => eprintln!("Failed to open {} due to {}", filename, e),
+        };
+        let expanded = config.tabs.apply(content);
+        let content = expanded.as_ref();
+        if config.squeeze_repeats {
+            if self.file.repeats > 0 && content == self.file.previous_line.as_slice() {
+                self.file.repeats += 1;
+                return Ok(());
+            }
+            self.finish(out)?;
+            self.file.previous_line.clear();
+            self.file.previous_line.extend_from_slice(content);
+            self.file.repeats = 1;
+        }
+        let blank = content.is_empty();
+        if config.squeeze_blank && blank && self.file.previous_blank {
+            return Ok(());
+        }
+        self.file.previous_blank = blank;
+
+        let output = &mut self.output;
+        output.clear();
+        if let Some(format) = &config.timestamps {
+            write!(output, "{} ", chrono::Local::now().format(format))?;
+        }
+        if config.with_filename {
+            write!(output, "{}:{}:", filename, self.file.lines_read)?;
+        }
+        if config.count_lines || (config.nonblank_number && !blank) {
+            self.file.line_number += 1;
+            let position = if config.number_original {
+                self.file.lines_read
+            } else {
+                self.file.line_number
+            };
+            // the numbers stop growing at the largest one rather than wrapping around
+            let number = config.number_start.saturating_add((position - 1).saturating_mul(config.number_step));
+            match config.palette.number {
+                Some(style) => style.paint(output, |output| config.number_format.write(number, output)),
+                None => config.number_format.write(number, output),
+            }
+        }
+        let start = output.len();
+        if config.show_ends && newline {
+            // a CRLF ending is shown as `^M$`, the same as GNU cat does
+            match content.strip_suffix(b"\r") {
+                Some(content) => {
+                    render_styled(content, config, &mut self.file.markdown, &mut self.file.syntax, output);
+                    output.extend_from_slice(b"^M$");
+                }
+                None => {
+                    render_styled(content, config, &mut self.file.markdown, &mut self.file.syntax, output);
+                    output.push(b'$');
+                }
+            }
+        } else {
+            render_styled(content, config, &mut self.file.markdown, &mut self.file.syntax, output);
+        }
+        if let Some(columns) = config.wrap {
+            // the rows of a wrapped line end like the line itself, a carriage return included
+            let newline = match config.line_ending {
+                LineEnding::Keep if line.ends_with(b"\r\n") => b"\r\n",
+                line_ending => line_ending.newline(),
+            };
+            wrap::wrap(output, start, columns, newline);
+        }
+        if let Some(columns) = config.truncate {
+            wrap::truncate(output, start, columns);
+        }
+        if newline {
+            output.extend_from_slice(config.line_ending.newline());
+        }
+        out.write_all(output)?;
+        self.file.lines_printed += 1;
+        if config.stats != StatsMode::Off {
+            let counter = self.file.stats.get_or_insert_default();
+            counter.update(content);
+            if newline {
+                counter.update(b"\n");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the content of a reader line by line through the given `LinePrinter`.
+///
+/// ## Parameters
+/// * `filename` - The name of the file being printed, used in the error message.
+/// * `reader` - The reader to read the lines from.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines.
+/// * `out` - The writer the lines are written into.
+///
+/// The lines are read with `read_until` into a single buffer reused for the whole file, so their
+/// terminators are kept and no allocation is made per line. Reading stops as soon as the `LinePrinter`
+/// has had enough lines of the file, see `LinePrinter::file_done`.
+///
+/// # Errors
+/// The function will return `MinicatError::ReadFailed` if reading fails, or the error of
+/// `LinePrinter::print_line`.
+fn print_lines<W: Write>(
+    filename: &str,
+    reader: &mut dyn BufRead,
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let flush = flush_each_line(filename, printer.config);
+    let mut line = Vec::new();
+    while !printer.file_done() {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(source) => {
+                return Err(MinicatError::ReadFailed {
+                    path: filename.to_string(),
+                    line: Some(printer.file.lines_read + 1),
+                    source,
+                });
+            }
+        }
+        printer.print_line(filename, &line, out)?;
+        if flush {
+            out.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a regular file line by line from a memory mapping instead of buffered reads.
+///
+/// ## Parameters
+/// * `filename` - The name of the file to print.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines.
+/// * `out` - The writer the lines are written into.
+///
+/// ## Returns
+/// `true` if the file was printed, `false` if it is not a regular file (or cannot be opened or mapped)
+/// and has to go through the regular path instead.
+///
+/// # Errors
+/// The function will return an error if `LinePrinter::print_line` fails.
+fn print_mapped<W: Write>(filename: &str, printer: &mut LinePrinter, out: &mut W) -> Result<bool, MinicatError> {
+    // only regular files can be mapped, and opening a FIFO could block
+    if filename == "-" || !fs::metadata(filename).is_ok_and(|metadata| metadata.is_file()) {
+        return Ok(false);
+    }
+    let file = match InputFile::open(filename, printer.config.drop_cache) {
+        Ok(file) => file,
+        Err(_) => return Ok(false),
+    };
+    let map = match mmap::map(file.file()) {
+        Ok(Some(map)) => map,
+        _ => return Ok(false),
+    };
+    print_data(filename, &map, printer, out)?;
+
+    Ok(true)
+}
+
+/// Prints the content of a file that is already in memory, either mapped or prefetched, or the part of it
+/// selected by `skip_bytes` and `count_bytes`.
+///
+/// ## Parameters
+/// * `filename` - The name of the file the data comes from.
+/// * `data` - The content of the file.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines.
+/// * `out` - The writer the data is written into.
+///
+/// # Errors
+/// The function will return an error if writing to `out` or `LinePrinter::print_line` fails.
+fn print_data<W: Write>(
+    filename: &str,
+    data: &[u8],
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let config = printer.config;
+    let data = config.select_bytes(data, printer.file.strip_bom);
+    if !config.transforms_lines() {
+        out.write_all(data)?;
+    } else if config.reverse {
+        reverse::print_backwards(filename, data, printer, out)?;
+    } else {
+        for line in mmap::lines(data) {
+            if printer.file_done() {
+                break;
+            }
+            printer.print_line(filename, line, out)?;
+            if config.unbuffered {
+                out.flush()?;
+            }
         }
     }
+    if config.unbuffered {
+        out.flush()?;
+    }
 
     Ok(())
 }
 
-/// Opens a file for reading or returns standard input stream if file string is empty.
+/// Copies a regular file or the standard input to the output with `io::copy`.
 ///
 /// ## Parameters
-/// * `file` - A string slice reference which contains the path to the file. If it is an empty string, the function returns standard input stream.
+/// * `filename` - The name of the file to copy, `-` for the standard input.
+/// * `config` - The `Config` deciding whether the output has to be flushed after every line.
+/// * `strip_bom` - Whether to take the byte order mark off the start of the file, see `bom`.
+/// * `to_stdout` - Whether `out` ends up in the standard output, which allows splicing the file into it.
+/// * `out` - The writer the bytes are written into.
+///
+/// ## Returns
+/// `true` if the file was copied, `false` if it has to go through the regular path instead, which is the case
+/// for anything but regular files (and for files that cannot be opened, so that the error is reported there).
+///
+/// The reader is passed to `io::copy` by its concrete type rather than as a boxed `BufRead`, which lets the
+/// standard library use its specialized copy (`copy_file_range`, `sendfile` or `splice` on Linux) and skip
+/// the intermediate buffers. On Linux, a regular file is spliced into the standard output first when it is a pipe.
+///
+/// # Errors
+/// The function will return `MinicatError::CopyFailed` if the copying fails.
+fn copy_fast<W: Write>(
+    filename: &str,
+    config: &Config,
+    strip_bom: bool,
+    to_stdout: bool,
+    out: &mut W,
+) -> Result<bool, MinicatError> {
+    let copy_failed = |source| MinicatError::CopyFailed { path: filename.to_string(), source };
+    if flush_each_line(filename, config) {
+        return Ok(false);
+    }
+    if filename == "-" {
+        let mut stdin = io::stdin().lock();
+        if strip_bom {
+            bom::skip(&mut stdin).map_err(copy_failed)?;
+        }
+        io::copy(&mut stdin, out).map_err(copy_failed)?;
+        return Ok(true);
+    }
+    match fs::metadata(filename) {
+        Ok(metadata) if metadata.is_file() => {}
+        _ => return Ok(false),
+    }
+    let mut file = match InputFile::open(filename, config.drop_cache) {
+        Ok(file) => file,
+        Err(_) => return Ok(false),
+    };
+    if strip_bom {
+        bom::skip_file(file.file_mut()).map_err(copy_failed)?;
+    }
+    if to_stdout {
+        // whatever is still buffered has to come out before the spliced data
+        out.flush()?;
+        if splice::splice_to_stdout(file.file()).map_err(copy_failed)? {
+            return Ok(true);
+        }
+    }
+    // when splicing gave up halfway, the file offset is where it stopped, so copying just carries on
+    io::copy(file.file_mut(), out).map_err(copy_failed)?;
+
+    Ok(true)
+}
+
+/// Copies the content of a reader to the output without any processing.
+///
+/// ## Parameters
+/// * `filename` - The name of the file being copied, used in the error message.
+/// * `reader` - The reader to copy the bytes from.
+/// * `config` - The `Config` deciding whether the output is flushed after every chunk.
+/// * `out` - The writer the bytes are written into.
+///
+/// # Errors
+/// The function will return `MinicatError::ReadFailed` if reading fails, and `MinicatError::WriteFailed`
+/// if writing to `out` fails.
+fn copy_raw<W: Write>(
+    filename: &str,
+    reader: &mut dyn BufRead,
+    config: &Config,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let flush = flush_each_line(filename, config);
+    loop {
+        let chunk = match reader.fill_buf() {
+            Ok([]) => break,
+            Ok(chunk) => chunk,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(source) => {
+                return Err(MinicatError::ReadFailed { path: filename.to_string(), line: None, source });
+            }
+        };
+        out.write_all(chunk)?;
+        if flush {
+            out.flush()?;
+        }
+        let length = chunk.len();
+        reader.consume(length);
+    }
+
+    Ok(())
+}
+
+/// Renders the bytes of a single line into `output` like `render_line`, as Markdown if `markdown` is given, in
+/// the colors of the language of the file if `syntax` is given, or else with the matches of the `Highlighter`
+/// colored. The whitespace issues are painted over the colors of the language and the matches, Markdown having
+/// its own rendering of the whitespace.
+fn render_styled(
+    line: &[u8],
+    config: &Config,
+    markdown: &mut Option<MarkdownRenderer>,
+    syntax: &mut Option<FileHighlighter>,
+    output: &mut Vec<u8>,
+) {
+    let render = |part: &[u8], output: &mut Vec<u8>| render_line(part, config, output);
+    let issues = if config.whitespace_issues { style::whitespace_issues(line) } else { Vec::new() };
+    let painted = style::paint_ranges(&issues, config.palette.whitespace, render);
+    match (markdown, syntax) {
+        (Some(markdown), _) => markdown.render(line, output, render),
+        (None, Some(syntax)) => syntax.render(line, output, painted),
+        (None, None) => config.highlight.render(line, output, painted),
+    }
+}
+
+/// Renders the bytes of a single line into `output`, applying the display transformations
+/// enabled in the `Config`.
+///
+/// ## Parameters
+/// * `line` - The content of the line, without its line terminator.
+/// * `config` - The `Config` deciding which transformations (`show_tabs`, `show_nonprinting`) apply.
+/// * `output` - The buffer the rendered bytes are appended to.
+///
+/// Control characters are displayed in caret notation (`^A`, `^?`) and bytes with the high bit
+/// set are prefixed with `M-`, the same way GNU cat does it.
+fn render_line(line: &[u8], config: &Config, output: &mut Vec<u8>) {
+    for &byte in line {
+        if byte == b'\t' {
+            if config.show_tabs {
+                output.extend_from_slice(b"^I");
+            } else {
+                output.push(byte);
+            }
+        } else if config.show_nonprinting {
+            let mut byte = byte;
+            if byte >= 128 {
+                output.extend_from_slice(b"M-");
+                byte -= 128;
+            }
+            match byte {
+                0..=31 => output.extend_from_slice(&[b'^', byte + 64]),
+                127 => output.extend_from_slice(b"^?"),
+                _ => output.push(byte),
+            }
+        } else {
+            output.push(byte);
+        }
+    }
+}
+
+/// Opens a file for reading or returns standard input stream if file string is `-`.
+///
+/// ## Parameters
+/// * `file` - A string slice reference which contains the path to the file. If it is `-`, the function returns
+///   standard input stream, which allows it to be read several times in between other files. An `http://` or
+///   `https://` URL is fetched, see the `http` module, and an `s3://` or `gs://` URL is read from the cloud
+///   storage, see the `cloud` module. A `unix://` URL is read from a Unix domain socket, and a `listen://` URL
+///   from a TCP connection accepted on its address, see the `socket` module. A `serial://` URL is read from a
+///   serial device at the `baud` rate, see the `serial` module.
+/// * `config` - The `Config` with the size of the read buffer, the page cache preference and the selected bytes.
+/// * `strip_bom` - Whether to take the byte order mark off the start of the file, see `bom`.
 ///
 /// ## Returns
 /// A `std::io::Result` which is an alias for `Result<T, E>` where `E` is `std::io::Error`.
-/// If successful, the function returns a `Box` containing a type implementing the `BufRead` trait.
+/// If successful, the function returns a `Box` containing a type implementing the `BufRead` trait, positioned
+/// after the `skip_bytes` bytes and ending after the `count_bytes` ones. A compressed input is decompressed
+/// first when the `Decompression` mode applies to it, and an archive member, given as `ARCHIVE:MEMBER` or with
+/// `archive_member`, is taken out of it, or the names of its members are listed with `list_members`, the bytes
+/// being counted in what comes out. The selected bytes are then transcoded to UTF-8 from the `encoding`.
+///
+/// # Errors
+/// The function will return an error if `std::fs::File::open()` fails, which includes missing read permissions,
+/// or if the path is a directory. Opening a directory would succeed on Linux and only fail once it is read from,
+/// so it is checked up front to report it like coreutils do. A FIFO without a writer is an error of kind
+/// `io::ErrorKind::TimedOut` once it is given up on, see `fifo::open`.
+fn open_file(file: &str, config: &Config, strip_bom: bool) -> io::Result<Box<dyn BufRead>> {
+    let buffer_size = config.buffer_size;
+    let (file, member) = archive::split(file);
+    let member = member.or(config.archive_member.as_deref());
+    let url = http::is_url(file);
+    let object = cloud::is_object_url(file);
+    let socket = socket::is_socket_url(file);
+    let serial = serial::is_serial_url(file);
+    let local = file != "-" && !url && !object && !socket && !serial;
+    let metadata = if local { Some(fs::metadata(file)?) } else { None };
+    if metadata.as_ref().is_some_and(fs::Metadata::is_dir) {
+        return Err(io::Error::new(io::ErrorKind::IsADirectory, "Is a directory"));
+    }
+    let fifo = metadata.as_ref().is_some_and(fifo::is_fifo);
+    let mut skip = config.skip_bytes as u64;
+    let compressed = config.decompression.applies(file);
+    let list = config.list_members && member.is_none();
+    // a zip archive is opened as a file, everything else is read front to back
+    let zip = local && archive::is_zip(file) && (member.is_some() || list);
+    let mut reader: Box<dyn BufRead> = match file {
+        "-" => Box::new(BufReader::with_capacity(buffer_size, io::stdin().lock())),
+        _ if url => Box::new(BufReader::with_capacity(buffer_size, timings::measure_open(|| http::open(file))?)),
+        _ if object => Box::new(BufReader::with_capacity(buffer_size, timings::measure_open(|| cloud::open(file))?)),
+        _ if socket => Box::new(BufReader::with_capacity(buffer_size, timings::measure_open(|| socket::open(file))?)),
+        _ if serial => Box::new(BufReader::with_capacity(buffer_size, serial::open(file, config.baud)?)),
+        _ if zip => archive::open_zip(InputFile::open(file, config.drop_cache)?, member, buffer_size)?,
+        _ if fifo => {
+            let input = fifo::open(file, config.fifo_wait, config.drop_cache)?;
+            Box::new(BufReader::with_capacity(buffer_size, input))
+        }
+        _ => {
+            let mut input = InputFile::open(file, config.drop_cache)?;
+            // regular files are seeked past the skipped bytes, anything else has to be read through below, and so
+            // do compressed files and archives, whose bytes are skipped once decompressed or taken out
+            if skip > 0 && !compressed && member.is_none() && !list && input.file().metadata()?.is_file() {
+                input.file_mut().seek(SeekFrom::Start(skip))?;
+                skip = 0;
+            }
+            Box::new(BufReader::with_capacity(buffer_size, input))
+        }
+    };
+    if !zip {
+        if compressed {
+            reader = decompress::decompress(reader, buffer_size)?;
+        }
+        if let Some(member) = member {
+            reader = archive::open_member(reader, member)?;
+        } else if list {
+            reader = archive::list_members(reader)?;
+        }
+    }
+    // the decoder knows the byte order mark of its encoding, and takes it off itself
+    if strip_bom && config.encoding.is_none() {
+        bom::skip(reader.as_mut())?;
+    }
+    if skip > 0 {
+        io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+    }
+    if let Some(count) = config.count_bytes {
+        reader = Box::new(reader.take(count as u64));
+    }
+    match config.encoding {
+        Some(encoding) => {
+            // the mark of the encoding is never part of the decoded text
+            let decoded = encoding::Decoded::new(reader, encoding, config.strip_bom);
+            Ok(Box::new(BufReader::with_capacity(buffer_size, decoded)))
+        }
+        None => Ok(reader),
+    }
+}
+
+/// Parses a size in bytes, optionally followed by a `K`, `M` or `G` suffix (powers of 1024).
+///
+/// The suffixes may also be written as `KiB`, `MiB` and `GiB`, so `1M`, `1MiB` and `1048576` are all the same.
 ///
 /// # Errors
-/// The function will return an error if `std::fs::File::open()` fails.
-fn open_file(file: &str) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
-    match file {
-        "" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(file)?)))
+/// The function will return an error if the value is not a positive number with a known suffix.
+fn parse_size(value: &str) -> Result<usize, String> {
+    let digits = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(digits);
+    let multiplier: usize = match suffix {
+        "" | "B" => 1,
+        "K" | "KiB" => 1 << 10,
+        "M" | "MiB" => 1 << 20,
+        "G" | "GiB" => 1 << 30,
+        _ => return Err(format!("unknown size suffix `{}`", suffix)),
+    };
+    number
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .filter(|&size| size > 0)
+        .ok_or_else(|| format!("`{}` is not a valid size", value))
+}
+
+/// Parses a rate in bytes per second, a size as `parse_size` reads it, optionally followed by `/s`. The `KB`,
+/// `MB` and `GB` suffixes are accepted too, as powers of 1024 like the others.
+///
+/// # Errors
+/// The function will return an error if the value is not a valid size.
+fn parse_rate(value: &str) -> Result<u64, String> {
+    let size = value.strip_suffix("/s").unwrap_or(value);
+    let size = match size.strip_suffix("B") {
+        Some(prefix) if prefix.ends_with(['K', 'M', 'G']) => prefix,
+        _ => size,
+    };
+    parse_size(size).map(|size| size as u64).map_err(|_| format!("`{}` is not a valid rate", value))
+}
+
+/// Parses a number of lines per second, which has to be positive and may be a fraction.
+///
+/// # Errors
+/// The function will return an error if the value is not a positive number.
+fn parse_lines_rate(value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|rate| rate.is_finite() && *rate > 0.0)
+        .ok_or_else(|| format!("`{}` is not a valid number of lines per second", value))
+}
+
+/// Parses the columns of `--wrap`, a positive number, or `auto` for the width of the terminal, given as `None`.
+///
+/// # Errors
+/// The function will return an error if the value is neither `auto` nor a positive number.
+fn parse_wrap(value: &str) -> Result<Option<usize>, String> {
+    if value == "auto" {
+        return Ok(None);
+    }
+    match value.parse::<usize>() {
+        Ok(columns) if columns > 0 => Ok(Some(columns)),
+        _ => Err(format!("`{}` is not a valid number of columns", value)),
+    }
+}
+
+/// Parses a duration in seconds, which may have a fraction, optionally followed by a `ms`, `s`, `m` or `h` unit,
+/// so `1.5`, `1.5s` and `1500ms` are all the same.
+///
+/// # Errors
+/// The function will return an error if the value is not a number with a known unit.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let digits = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits);
+    let multiplier = match unit {
+        "ms" => 1e-3,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("unknown duration unit `{}`", unit)),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .and_then(|number| Duration::try_from_secs_f64(number * multiplier).ok())
+        .ok_or_else(|| format!("`{}` is not a valid duration", value))
+}
+
+/// Parses a range of lines, `START:END` with both ends included and counted from 1, or `START:` for a range
+/// going up to the end of the file. A missing `START` stands for the first line.
+///
+/// # Errors
+/// The function will return an error if the ends are not positive numbers or `END` comes before `START`.
+fn parse_range(value: &str) -> Result<(usize, Option<usize>), String> {
+    let invalid = || format!("`{}` is not a valid range, expected START:END", value);
+    let (start, end) = value.split_once(':').ok_or_else(invalid)?;
+    let start = match start {
+        "" => 1,
+        _ => start.parse::<usize>().ok().filter(|&start| start > 0).ok_or_else(invalid)?,
+    };
+    let end = match end {
+        "" => None,
+        _ => Some(end.parse::<usize>().ok().filter(|&end| end >= start).ok_or_else(invalid)?),
+    };
+
+    Ok((start, end))
+}
+
+/// Checks a strftime format for `--timestamps`, so that a wrong one is reported before anything is printed.
+///
+/// # Errors
+/// The function will return an error if the format has an unknown or incomplete `%` specifier.
+fn parse_time_format(value: &str) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    if StrftimeItems::new(value).any(|item| item == Item::Error) {
+        return Err(format!("`{}` is not a valid strftime format", value));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Parses a string with backslash escape sequences into the bytes they stand for.
+///
+/// The sequences are `\n`, `\t`, `\r`, `\f`, `\v`, `\0`, `\e` (escape), `\\` and `\xHH` for any byte.
+///
+/// # Errors
+/// The function will return an error if a backslash starts an unknown sequence.
+fn unescape(value: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut encoded = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+            continue;
+        }
+        let byte = match chars.next() {
+            Some('n') => b'\n',
+            Some('t') => b'\t',
+            Some('r') => b'\r',
+            Some('f') => 0x0c,
+            Some('v') => 0x0b,
+            Some('0') => 0,
+            Some('e') => 0x1b,
+            Some('\\') => b'\\',
+            Some('x') => {
+                let digits: String = chars.by_ref().take(2).collect();
+                // `from_str_radix` would take a sign, or a single digit
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.bytes().all(|digit| digit.is_ascii_hexdigit()) && digits.len() == 2 => byte,
+                    _ => return Err(format!("`\\x{}` is not a valid escape sequence", digits)),
+                }
+            }
+            Some(other) => return Err(format!("`\\{}` is not a valid escape sequence", other)),
+            None => return Err("a trailing backslash has nothing to escape".to_string()),
+        };
+        bytes.push(byte);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_reads_the_binary_suffixes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("4B"), Ok(4));
+        assert_eq!(parse_size("64K"), Ok(64 << 10));
+        assert_eq!(parse_size("2MiB"), Ok(2 << 20));
+        assert_eq!(parse_size("1G"), Ok(1 << 30));
+    }
+
+    #[test]
+    fn parse_size_rejects_what_is_not_a_size() {
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("").is_err());
+        assert!(parse_size("K").is_err());
+        assert!(parse_size("12T").is_err());
+        assert!(parse_size("-1").is_err());
+        assert!(parse_size("99999999999999999999G").is_err());
+    }
+
+    #[test]
+    fn parse_range_reads_both_ends() {
+        assert_eq!(parse_range("3:7"), Ok((3, Some(7))));
+        assert_eq!(parse_range("5:5"), Ok((5, Some(5))));
+        assert_eq!(parse_range("4:"), Ok((4, None)));
+        assert_eq!(parse_range(":9"), Ok((1, Some(9))));
+        assert_eq!(parse_range(":"), Ok((1, None)));
+    }
+
+    #[test]
+    fn parse_range_rejects_what_is_not_a_range() {
+        assert!(parse_range("7").is_err());
+        assert!(parse_range("0:3").is_err());
+        assert!(parse_range("7:3").is_err());
+        assert!(parse_range("a:b").is_err());
+    }
+
+    #[test]
+    fn unescape_reads_the_escape_sequences() {
+        assert_eq!(unescape("--\\n"), Ok(b"--\n".to_vec()));
+        assert_eq!(unescape("\\t\\r\\f\\v\\0\\e\\\\"), Ok(b"\t\r\x0c\x0b\0\x1b\\".to_vec()));
+        assert_eq!(unescape("\\x41\\xff"), Ok(vec![b'A', 0xff]));
+        assert_eq!(unescape("\u{e9}"), Ok("\u{e9}".as_bytes().to_vec()));
+    }
+
+    #[test]
+    fn unescape_rejects_the_unknown_sequences() {
+        assert!(unescape("\\q").is_err());
+        assert!(unescape("trailing\\").is_err());
+        assert!(unescape("\\x4").is_err());
+        assert!(unescape("\\x+1").is_err());
+        assert!(unescape("\\xzz").is_err());
+    }
+
+    #[test]
+    fn parse_duration_reads_the_units() {
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_what_is_not_a_duration() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("1.2.3").is_err());
+        assert!(parse_duration("-1").is_err());
     }
 }
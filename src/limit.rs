@@ -0,0 +1,162 @@
+//! Capping the whole output, for `--max-bytes` and `--max-output-lines`, so that catting `/dev/zero` or a pipe
+//! that never ends stops on its own.
+//!
+//! The cap is counted across all the files, unlike `--max-lines` which applies to each file, and it is enforced
+//! in a `Limited` writer the output goes through, so every way a file can be read is capped alike. The write
+//! reaching the cap is cut at it, and the next one fails with an error `is_reached` recognizes, which stops the
+//! read loop of the file being printed. The rest of the files are then left out, a notice telling where the
+//! output was truncated, and the program ends successfully.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::error::MinicatError;
+
+/// `Limit` struct is the cap of the output, which is unlimited when both of its fields are `None`.
+///
+/// # Fields
+///
+/// * `bytes`: The number of bytes written before the output is truncated.
+/// * `lines`: The number of lines written before the output is truncated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limit {
+    pub bytes: Option<u64>,
+    pub lines: Option<u64>,
+}
+
+impl Limit {
+    /// Returns `true` if the output is capped, in which case every byte has to be written through `Limited`.
+    pub fn is_set(&self) -> bool {
+        self.bytes.is_some() || self.lines.is_some()
+    }
+}
+
+/// `Reached` struct is the error a `Limited` writer fails with once its cap is reached, telling where the output
+/// was truncated.
+#[derive(Debug)]
+struct Reached(String);
+
+impl fmt::Display for Reached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "output truncated after {}", self.0)
+    }
+}
+
+impl Error for Reached {}
+
+/// Returns the notice of a `MinicatError` coming from a `Limited` writer whose cap is reached, `None` for any
+/// other error.
+pub fn reached(error: &MinicatError) -> Option<String> {
+    match error {
+        MinicatError::WriteFailed(source) | MinicatError::CopyFailed { source, .. } => {
+            source.get_ref().and_then(|inner| inner.downcast_ref::<Reached>()).map(Reached::to_string)
+        }
+        _ => None,
+    }
+}
+
+/// `Limited` struct is a writer truncating what is written into it at a `Limit`.
+///
+/// # Fields
+///
+/// * `inner`: The writer the output goes to.
+/// * `limit`: The `Limit`.
+/// * `bytes`: The number of bytes written so far.
+/// * `lines`: The number of lines written so far.
+pub struct Limited<W> {
+    inner: W,
+    limit: Limit,
+    bytes: u64,
+    lines: u64,
+}
+
+impl<W: Write> Limited<W> {
+    pub fn new(inner: W, limit: Limit) -> Self {
+        Limited { inner, limit, bytes: 0, lines: 0 }
+    }
+
+    /// Returns the error of a reached cap, naming the one that was hit.
+    fn reached(&self) -> io::Error {
+        let cap = match self.limit.lines {
+            Some(lines) if self.lines >= lines => format!("{} lines", lines),
+            _ => format!("{} bytes", self.bytes),
+        };
+        io::Error::other(Reached(cap))
+    }
+}
+
+impl<W: Write> Write for Limited<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.limit.is_set() || buf.is_empty() {
+            return self.inner.write(buf);
+        }
+        let mut length = buf.len();
+        if let Some(bytes) = self.limit.bytes {
+            length = length.min((bytes - self.bytes) as usize);
+        }
+        if let Some(lines) = self.limit.lines {
+            // the write is cut right after the last line allowed
+            length = match (lines - self.lines) as usize {
+                0 => 0,
+                left => buf[..length]
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &byte)| byte == b'\n')
+                    .nth(left - 1)
+                    .map_or(length, |(end, _)| end + 1),
+            };
+        }
+        if length == 0 {
+            return Err(self.reached());
+        }
+        let written = self.inner.write(&buf[..length])?;
+        self.bytes += written as u64;
+        self.lines += buf[..written].iter().filter(|&&byte| byte == b'\n').count() as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the pieces into a `Limited` writer until one fails, and returns the output and the notice.
+    fn limited(limit: Limit, pieces: &[&str]) -> (String, Option<String>) {
+        let mut limited = Limited::new(Vec::new(), limit);
+        let notice = pieces
+            .iter()
+            .find_map(|piece| limited.write_all(piece.as_bytes()).err())
+            .and_then(|e| reached(&MinicatError::from(e)));
+        (String::from_utf8(limited.inner).unwrap(), notice)
+    }
+
+    #[test]
+    fn limited_cuts_at_the_bytes() {
+        let limit = Limit { bytes: Some(5), lines: None };
+        let notice = Some("output truncated after 5 bytes".to_string());
+        assert_eq!(limited(limit, &["abc", "defg", "h"]), ("abcde".to_string(), notice));
+        assert_eq!(limited(limit, &["abc", "de"]), ("abcde".to_string(), None));
+    }
+
+    #[test]
+    fn limited_cuts_after_the_lines() {
+        let limit = Limit { bytes: None, lines: Some(2) };
+        let notice = Some("output truncated after 2 lines".to_string());
+        assert_eq!(limited(limit, &["1\n2", "\n3\n"]), ("1\n2\n".to_string(), notice));
+    }
+
+    #[test]
+    fn limited_passes_everything_without_a_limit() {
+        assert_eq!(limited(Limit::default(), &["1\n", "2\n"]), ("1\n2\n".to_string(), None));
+    }
+
+    #[test]
+    fn reached_ignores_the_other_errors() {
+        assert_eq!(reached(&MinicatError::from(io::Error::other("disk full"))), None);
+    }
+}
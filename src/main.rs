@@ -2,7 +2,38 @@ use std::process::exit;
 
 fn main() {
     if let Err(e) = rust_minicat::get_args().and_then(rust_minicat::run) {
-        eprintln!("{}", e);
+        if e.is_broken_pipe() {
+            exit_on_broken_pipe();
+        }
+        if let rust_minicat::MinicatError::Interrupted = e {
+            // the conventional status of a program stopped with Ctrl+C
+            exit(130);
+        }
+        if let rust_minicat::MinicatError::FilesFailed(_) = e {
+            // every file that failed has been reported already, cat just exits with 1 then
+            exit(1);
+        }
+        eprintln!("minicat: {}", e);
         exit(1);
     }
 }
+
+/// Ends the program the way cat ends when its output pipe is closed: killed by `SIGPIPE`, silently.
+///
+/// Rust ignores `SIGPIPE`, so the signal is restored to its default action and raised again here, which lets
+/// the shell see the conventional status of 141.
+#[cfg(unix)]
+fn exit_on_broken_pipe() -> ! {
+    // SAFETY: restoring the default action and raising a signal has no other effect at this point
+    unsafe {
+        libc::signal(libc::SIGPIPE, libc::SIG_DFL);
+        libc::raise(libc::SIGPIPE);
+    }
+    exit(141)
+}
+
+/// Ends the program quietly when its output pipe is closed.
+#[cfg(not(unix))]
+fn exit_on_broken_pipe() -> ! {
+    exit(1)
+}
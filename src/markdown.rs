@@ -0,0 +1,304 @@
+//! Rendering Markdown files with terminal styles, for `--render`.
+//!
+//! The files are rendered line by line as they are read, so a huge file is not held in memory: headings,
+//! emphasis, inline code and links are styled, list bullets and block quotes are drawn with box characters,
+//! and code fences are printed in a color of their own. Markdown is only rendered when the output is colored,
+//! otherwise it is printed as the raw text it is.
+
+use crate::style::Style;
+
+/// The style of the top level headings.
+const HEADING: Style = Style::Escape(b"\x1b[1;4;35m");
+
+/// The style of the other headings.
+const SUBHEADING: Style = Style::Escape(b"\x1b[1;35m");
+
+/// The style of `**strong**` text.
+const STRONG: Style = Style::Escape(b"\x1b[1m");
+
+/// The style of `*emphasized*` text.
+const EMPHASIS: Style = Style::Escape(b"\x1b[3m");
+
+/// The style of inline code and of the lines of code blocks.
+const CODE: Style = Style::Escape(b"\x1b[36m");
+
+/// The style of the text of links.
+const LINK: Style = Style::Escape(b"\x1b[4;34m");
+
+/// The style of what is shown but not part of the text: the fences, link targets, quote bars and rules.
+const DIM: Style = Style::Escape(b"\x1b[2m");
+
+/// The style of the bullets and numbers of list items.
+const BULLET: Style = Style::Escape(b"\x1b[33m");
+
+/// The number of columns of a horizontal rule.
+const RULE_WIDTH: usize = 40;
+
+/// `Render` enum decides which files are rendered as Markdown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Render {
+    /// The files are printed as they are.
+    #[default]
+    Never,
+    /// The files with a `.md` or `.markdown` extension are rendered.
+    Auto,
+    /// Every file is rendered.
+    Markdown,
+}
+
+impl Render {
+    /// Starts rendering a file, if the mode asks for it.
+    pub fn for_file(self, filename: &str) -> Option<MarkdownRenderer> {
+        let extension = std::path::Path::new(filename).extension().and_then(|extension| extension.to_str());
+        let markdown = match self {
+            Render::Never => false,
+            Render::Auto => extension.is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("md") || extension.eq_ignore_ascii_case("markdown")
+            }),
+            Render::Markdown => true,
+        };
+        markdown.then(MarkdownRenderer::default)
+    }
+}
+
+/// `MarkdownRenderer` struct renders the lines of a single Markdown file.
+///
+/// # Fields
+///
+/// * `fence`: The fence of the code block the lines are in, ```` ``` ```` or `~~~`, `None` outside of code
+///   blocks.
+#[derive(Debug, Default)]
+pub struct MarkdownRenderer {
+    fence: Option<&'static [u8]>,
+}
+
+impl MarkdownRenderer {
+    /// Renders a line, without its terminator, into `output`, the text itself going through `render`.
+    pub fn render(&mut self, line: &[u8], output: &mut Vec<u8>, render: impl Fn(&[u8], &mut Vec<u8>)) {
+        let indent = line.iter().take_while(|&&byte| byte == b' ').count();
+        let (margin, text) = line.split_at(indent);
+        if let Some(fence) = self.fence {
+            if text.starts_with(fence) {
+                self.fence = None;
+                return DIM.paint(output, |output| render(line, output));
+            }
+            return CODE.paint(output, |output| render(line, output));
+        }
+        if let Some(fence) = [b"```".as_slice(), b"~~~"].into_iter().find(|fence| text.starts_with(fence)) {
+            self.fence = Some(fence);
+            return DIM.paint(output, |output| render(line, output));
+        }
+        if let Some(heading) = heading(text) {
+            let style = if text.starts_with(b"# ") { HEADING } else { SUBHEADING };
+            return style.paint(output, |output| render(heading, output));
+        }
+        if is_rule(text) {
+            return DIM.paint(output, |output| output.extend_from_slice("─".repeat(RULE_WIDTH).as_bytes()));
+        }
+        render(margin, output);
+        if let Some(quoted) = text.strip_prefix(b">") {
+            DIM.paint(output, |output| output.extend_from_slice("│".as_bytes()));
+            return render_inline(quoted, output, &render);
+        }
+        if let Some(item) = [b"- ".as_slice(), b"* ", b"+ "].into_iter().find_map(|bullet| text.strip_prefix(bullet)) {
+            BULLET.paint(output, |output| output.extend_from_slice("• ".as_bytes()));
+            return render_inline(item, output, &render);
+        }
+        let digits = text.iter().take_while(|byte| byte.is_ascii_digit()).count();
+        if digits > 0 && (text[digits..].starts_with(b". ") || text[digits..].starts_with(b") ")) {
+            BULLET.paint(output, |output| render(&text[..digits + 1], output));
+            return render_inline(&text[digits + 1..], output, &render);
+        }
+        render_inline(text, output, &render)
+    }
+}
+
+/// Returns the text of an ATX heading, `# Title` up to `###### Title`, without its markers.
+fn heading(text: &[u8]) -> Option<&[u8]> {
+    let level = text.iter().take_while(|&&byte| byte == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let title = text[level..].strip_prefix(b" ")?;
+    // the closing sequence of `## Title ##` is not part of the title
+    let title = title.trim_ascii_end();
+    let title = match title.strip_suffix(&text[..level]) {
+        Some(stripped) if stripped.is_empty() || stripped.ends_with(b" ") => stripped.trim_ascii_end(),
+        _ => title,
+    };
+    Some(title)
+}
+
+/// Returns `true` if the line is a horizontal rule, at least three `-`, `*` or `_` and nothing else but spaces.
+fn is_rule(text: &[u8]) -> bool {
+    let Some(&marker) = text.first().filter(|&&byte| matches!(byte, b'-' | b'*' | b'_')) else {
+        return false;
+    };
+    text.iter().all(|&byte| byte == marker || byte == b' ')
+        && text.iter().filter(|&&byte| byte == marker).count() >= 3
+}
+
+/// Renders the inline elements of a line: `**strong**`, `*emphasis*`, `` `code` `` and `[links](target)`.
+/// They are not nested, since the end of a style switches every style off.
+///
+/// A marker without its closing counterpart is printed as it is, and so is an `_` within a word, as in
+/// `snake_case`.
+fn render_inline(text: &[u8], output: &mut Vec<u8>, render: &impl Fn(&[u8], &mut Vec<u8>)) {
+    let mut plain = 0;
+    let mut index = 0;
+    while index < text.len() {
+        let rest = &text[index..];
+        let word_start = index == 0 || !text[index - 1].is_ascii_alphanumeric();
+        let span = match rest[0] {
+            b'`' => delimited(rest, b"`").map(|(inner, length)| (CODE, inner, length)),
+            b'*' | b'_' if word_start => {
+                let strong = &rest[..2.min(rest.len())];
+                if strong == b"**" || strong == b"__" {
+                    delimited(rest, strong).map(|(inner, length)| (STRONG, inner, length))
+                } else {
+                    delimited(rest, &rest[..1]).map(|(inner, length)| (EMPHASIS, inner, length))
+                }
+            }
+            b'[' => {
+                if let Some((label, target, length)) = link(rest) {
+                    render(&text[plain..index], output);
+                    LINK.paint(output, |output| render(label, output));
+                    DIM.paint(output, |output| {
+                        output.push(b' ');
+                        output.push(b'(');
+                        render(target, output);
+                        output.push(b')');
+                    });
+                    index += length;
+                    plain = index;
+                    continue;
+                }
+                None
+            }
+            _ => None,
+        };
+        match span {
+            Some((style, inner, length)) => {
+                render(&text[plain..index], output);
+                style.paint(output, |output| render(inner, output));
+                index += length;
+                plain = index;
+            }
+            None => index += 1,
+        }
+    }
+    render(&text[plain..], output);
+}
+
+/// Finds the text between a marker at the start of `text` and the next occurrence of it, which must not be
+/// empty nor start with a space.
+///
+/// ## Returns
+/// The text in between and the length of the whole span, markers included.
+fn delimited<'t>(text: &'t [u8], marker: &[u8]) -> Option<(&'t [u8], usize)> {
+    let inner = &text[marker.len()..];
+    if inner.first().is_none_or(|&byte| byte == b' ') {
+        return None;
+    }
+    let end = find(inner, marker).filter(|&end| end > 0)?;
+    Some((&inner[..end], marker.len() * 2 + end))
+}
+
+/// Parses a `[label](target)` link at the start of `text`.
+///
+/// ## Returns
+/// The label, the target and the length of the whole link.
+fn link(text: &[u8]) -> Option<(&[u8], &[u8], usize)> {
+    let middle = find(text, b"](")?;
+    let end = middle + 2 + find(&text[middle + 2..], b")")?;
+    Some((&text[1..middle], &text[middle + 2..end], end + 1))
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders the lines of a file, and returns each of them.
+    fn render(lines: &[&str]) -> Vec<Vec<u8>> {
+        let mut renderer = MarkdownRenderer::default();
+        lines
+            .iter()
+            .map(|line| {
+                let mut output = Vec::new();
+                renderer.render(line.as_bytes(), &mut output, |text, output| output.extend_from_slice(text));
+                output
+            })
+            .collect()
+    }
+
+    /// Returns `parts` one after the other, each either plain text or text painted in a style.
+    fn styled(parts: &[(Option<Style>, &str)]) -> Vec<u8> {
+        let mut output = Vec::new();
+        for &(style, text) in parts {
+            match style {
+                Some(style) => style.paint(&mut output, |output| output.extend_from_slice(text.as_bytes())),
+                None => output.extend_from_slice(text.as_bytes()),
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn files_are_picked_by_the_mode() {
+        assert!(Render::Auto.for_file("README.md").is_some());
+        assert!(Render::Auto.for_file("notes.Markdown").is_some());
+        assert!(Render::Auto.for_file("main.rs").is_none());
+        assert!(Render::Markdown.for_file("main.rs").is_some());
+        assert!(Render::Never.for_file("README.md").is_none());
+    }
+
+    #[test]
+    fn headings_lose_their_markers() {
+        assert_eq!(render(&["# Title"]), [styled(&[(Some(HEADING), "Title")])]);
+        assert_eq!(render(&["## Part ##"]), [styled(&[(Some(SUBHEADING), "Part")])]);
+        assert_eq!(render(&["#hashtag"]), [b"#hashtag"]);
+    }
+
+    #[test]
+    fn inline_elements_are_styled() {
+        let expected = styled(&[
+            (Some(STRONG), "bold"),
+            (None, " "),
+            (Some(EMPHASIS), "em"),
+            (None, " "),
+            (Some(CODE), "code"),
+            (None, " "),
+            (Some(LINK), "site"),
+            (Some(DIM), " (http://a)"),
+            (None, " snake_case *open"),
+        ]);
+        assert_eq!(render(&["**bold** *em* `code` [site](http://a) snake_case *open"]), [expected]);
+    }
+
+    #[test]
+    fn lists_and_quotes_get_their_marks() {
+        let bullet = styled(&[(None, "  "), (Some(BULLET), "• "), (None, "item")]);
+        let number = styled(&[(Some(BULLET), "1."), (None, " one")]);
+        let quote = styled(&[(Some(DIM), "│"), (None, " said")]);
+        assert_eq!(render(&["  - item", "1. one", "> said"]), [bullet, number, quote]);
+        assert_eq!(render(&["---"]), [styled(&[(Some(DIM), &"─".repeat(RULE_WIDTH))])]);
+    }
+
+    #[test]
+    fn code_blocks_are_left_alone_up_to_their_fence() {
+        let rendered = render(&["~~~", "# not a title", "```", "~~~", "*a*"]);
+        let expected = [
+            styled(&[(Some(DIM), "~~~")]),
+            styled(&[(Some(CODE), "# not a title")]),
+            styled(&[(Some(CODE), "```")]),
+            styled(&[(Some(DIM), "~~~")]),
+            styled(&[(Some(EMPHASIS), "a")]),
+        ];
+        assert_eq!(rendered, expected);
+    }
+}
@@ -0,0 +1,75 @@
+//! Memory-mapped reading of regular files.
+//!
+//! Mapping a file avoids copying its content into an intermediate buffer, and the lines are found by
+//! scanning the mapping with `memchr`, which makes numbering huge files cheaper than buffered reads.
+
+use std::fs::File;
+use std::io;
+
+use memchr::memchr;
+use memmap2::Mmap;
+
+/// Maps the whole file into memory for reading.
+///
+/// ## Parameters
+/// * `file` - The file to map. It has to be a regular file.
+///
+/// ## Returns
+/// The mapping, or `None` if the file is empty or not a regular file and so cannot be mapped.
+///
+/// # Errors
+/// The function will return an error if reading the metadata of the file or mapping it fails.
+pub fn map(file: &File) -> io::Result<Option<Mmap>> {
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the mapping is only read from; like any other cat, truncating the file while it is
+    // being printed is not supported.
+    let map = unsafe { Mmap::map(file)? };
+
+    Ok(Some(map))
+}
+
+/// Returns an iterator over the lines of `data`, each including its `\n` terminator if it has one.
+pub fn lines(data: &[u8]) -> Lines<'_> {
+    Lines { data }
+}
+
+/// `Lines` struct is an iterator over the lines of a byte slice, see the `lines` function.
+#[derive(Debug, Clone)]
+pub struct Lines<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let end = memchr(b'\n', self.data).map_or(self.data.len(), |index| index + 1);
+        let (line, rest) = self.data.split_at(end);
+        self.data = rest;
+
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_keep_their_terminators() {
+        let lines: Vec<&[u8]> = lines(b"one\ntwo").collect();
+        assert_eq!(lines, [&b"one\n"[..], &b"two"[..]]);
+    }
+
+    #[test]
+    fn lines_of_nothing_are_none() {
+        assert_eq!(lines(b"").count(), 0);
+        assert_eq!(lines(b"\n\n").collect::<Vec<_>>(), [&b"\n"[..], &b"\n"[..]]);
+    }
+}
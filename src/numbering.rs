@@ -0,0 +1,104 @@
+//! Formatting the line numbers printed in front of the lines.
+
+use std::io::Write;
+
+/// `NumberPad` enum decides what fills the width of a line number shorter than it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NumberPad {
+    /// The number is right-aligned with spaces.
+    #[default]
+    Spaces,
+    /// The number is padded with leading zeros.
+    Zeros,
+}
+
+/// `NumberBase` enum decides the base the line numbers are written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NumberBase {
+    /// Base 10.
+    #[default]
+    Decimal,
+    /// Base 16, with lowercase digits, matching the addresses of debuggers and disassembly listings.
+    Hexadecimal,
+    /// Base 8.
+    Octal,
+}
+
+/// `LineNumberFormatter` struct writes the line numbers, the same way for every line.
+///
+/// The default is what GNU cat prints: the number right-aligned in 6 columns and followed by a tab.
+///
+/// # Fields
+///
+/// * `width`: The minimum number of columns of the number, 0 to write it as it is.
+/// * `pad`: The `NumberPad` filling the columns the number does not use.
+/// * `separator`: What is written between the number and the line.
+/// * `base`: The `NumberBase` the number is written in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineNumberFormatter {
+    width: usize,
+    pad: NumberPad,
+    separator: String,
+    base: NumberBase,
+}
+
+impl Default for LineNumberFormatter {
+    fn default() -> Self {
+        LineNumberFormatter::new(6, NumberPad::Spaces, "\t".to_string(), NumberBase::Decimal)
+    }
+}
+
+impl LineNumberFormatter {
+    pub fn new(width: usize, pad: NumberPad, separator: String, base: NumberBase) -> Self {
+        LineNumberFormatter { width, pad, separator, base }
+    }
+
+    /// Writes a line number and the separator into `output`.
+    pub fn write(&self, number: usize, output: &mut Vec<u8>) {
+        let width = self.width;
+        // writing into a vector cannot fail
+        let _ = match (self.pad, self.base) {
+            (NumberPad::Spaces, NumberBase::Decimal) => write!(output, "{:>width$}", number),
+            (NumberPad::Spaces, NumberBase::Hexadecimal) => write!(output, "{:>width$x}", number),
+            (NumberPad::Spaces, NumberBase::Octal) => write!(output, "{:>width$o}", number),
+            (NumberPad::Zeros, NumberBase::Decimal) => write!(output, "{:0>width$}", number),
+            (NumberPad::Zeros, NumberBase::Hexadecimal) => write!(output, "{:0>width$x}", number),
+            (NumberPad::Zeros, NumberBase::Octal) => write!(output, "{:0>width$o}", number),
+        };
+        output.extend_from_slice(self.separator.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns what `formatter` writes for `number`.
+    fn format(formatter: &LineNumberFormatter, number: usize) -> String {
+        let mut output = Vec::new();
+        formatter.write(number, &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn default_is_gnu_cat() {
+        assert_eq!(format(&LineNumberFormatter::default(), 42), "    42\t");
+        assert_eq!(format(&LineNumberFormatter::default(), 1234567), "1234567\t");
+    }
+
+    #[test]
+    fn width_padding_and_separator_are_applied() {
+        let zeros = LineNumberFormatter::new(4, NumberPad::Zeros, ": ".to_string(), NumberBase::Decimal);
+        assert_eq!(format(&zeros, 7), "0007: ");
+        assert_eq!(format(&zeros, 12345), "12345: ");
+        let compact = LineNumberFormatter::new(0, NumberPad::Spaces, " ".to_string(), NumberBase::Decimal);
+        assert_eq!(format(&compact, 7), "7 ");
+    }
+    #[test]
+    fn bases_are_applied_with_the_padding() {
+        let hex = LineNumberFormatter::new(4, NumberPad::Zeros, " ".to_string(), NumberBase::Hexadecimal);
+        assert_eq!(format(&hex, 255), "00ff ");
+        let octal = LineNumberFormatter::new(3, NumberPad::Spaces, " ".to_string(), NumberBase::Octal);
+        assert_eq!(format(&octal, 8), " 10 ");
+    }
+}
@@ -0,0 +1,370 @@
+//! Writing the output into a file with `--output`, in addition to the standard output, or instead of it with
+//! `--quiet`.
+//!
+//! The file gets the same bytes as the standard output, after every transformation, the way `tee` would save
+//! them. It is created, or truncated, before the first file is read, so an input that is the output file is
+//! caught like one that is the standard output. The output can also be added at the end of the file with
+//! `--append`, or written into a temporary file next to it with `--output-tmp-then-rename`, which only replaces
+//! the file once everything has been printed, so that the file is never seen half written, and is left as it
+//! was if anything fails.
+//!
+//! The output can also be copied to the clipboard with `--clipboard`, see the `clipboard` module.
+//!
+//! With `--split-lines` or `--split-bytes`, the output path is a template, such as `out.%03d`, and the output
+//! is cut into chunks written into the files it names, numbered from 0, like `split` does. The files are not
+//! spliced into the standard output while the output is copied, so
+//! that every byte reaches the file.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::clipboard;
+use crate::error::MinicatError;
+
+/// `OutputMode` enum decides how the output file is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The file is truncated before the output is written into it.
+    #[default]
+    Truncate,
+    /// The output is added at the end of the file.
+    Append,
+    /// The output is written into a temporary file, which replaces the file once everything has been printed.
+    Replace,
+}
+
+/// `SplitLimit` enum is the size of the chunks the output is cut into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLimit {
+    /// Every chunk holds the given number of lines, the last one maybe less.
+    Lines(usize),
+    /// Every chunk holds the given number of bytes, the last one maybe less.
+    Bytes(usize),
+}
+
+/// `OutputFile` struct is the file the output is copied into, or the files of its chunks.
+///
+/// # Fields
+///
+/// * `writer`: The buffered writer into the file, the temporary one with `OutputMode::Replace`, or the `Split`
+///   writer of the chunks.
+/// * `path`: The path of the output file.
+/// * `temporary`: The path of the temporary file with `OutputMode::Replace`, until it has replaced the file. It
+///   is removed if it is dropped before then.
+pub struct OutputFile {
+    writer: Box<dyn Write>,
+    path: String,
+    temporary: Option<PathBuf>,
+}
+
+impl OutputFile {
+    /// Creates the output file, truncating it, opening it for appending, or creating the temporary file next to
+    /// it, depending on `mode`. When the output is split, nothing is created until the first chunk is written.
+    ///
+    /// ## Parameters
+    /// * `path` - The path of the output file, or the template of the paths of the chunks.
+    /// * `mode` - The `OutputMode`, which is `OutputMode::Truncate` when the output is split.
+    /// * `split` - The `SplitLimit` of the chunks, `None` to write a single file.
+    /// * `buffer_size` - The size of the write buffer.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::OpenFailed` if the file cannot be created or opened.
+    pub fn create(
+        path: &str,
+        mode: OutputMode,
+        split: Option<SplitLimit>,
+        buffer_size: usize,
+    ) -> Result<Self, MinicatError> {
+        if let Some(limit) = split {
+            let writer = Box::new(Split::new(path, limit, buffer_size));
+            return Ok(OutputFile { writer, path: path.to_string(), temporary: None });
+        }
+        let open_failed = |source| MinicatError::OpenFailed { path: path.to_string(), source };
+        let temporary = (mode == OutputMode::Replace).then(|| temporary_path(Path::new(path)));
+        let file = match (mode, &temporary) {
+            (OutputMode::Append, _) => OpenOptions::new().append(true).create(true).open(path),
+            (_, Some(temporary)) => OpenOptions::new().write(true).create_new(true).open(temporary),
+            _ => File::create(path),
+        }
+        .map_err(open_failed)?;
+        let writer = Box::new(BufWriter::with_capacity(buffer_size, file));
+        Ok(OutputFile { writer, path: path.to_string(), temporary })
+    }
+
+    /// Flushes the output into the file, and replaces the file with the temporary one with `OutputMode::Replace`.
+    ///
+    /// # Errors
+    /// The function will return `MinicatError::WriteFailed` if flushing fails, and `MinicatError::OpenFailed` if
+    /// the file cannot be replaced.
+    pub fn finish(mut self) -> Result<(), MinicatError> {
+        self.writer.flush()?;
+        if let Some(temporary) = self.temporary.take() {
+            fs::rename(&temporary, &self.path).map_err(|source| {
+                // the temporary file is not needed once it cannot take the place of the file
+                let _ = fs::remove_file(&temporary);
+                MinicatError::OpenFailed { path: self.path.clone(), source }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        if let Some(temporary) = self.temporary.take() {
+            let _ = fs::remove_file(temporary);
+        }
+    }
+}
+
+/// Returns the path of the temporary file of an output file, hidden in the same directory, so that renaming it
+/// stays on the same file system and is atomic.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// `Split` struct is a writer cutting the output into chunks, each written into a file of its own, which is
+/// created when the first byte of the chunk comes.
+///
+/// # Fields
+///
+/// * `template`: The template of the paths of the chunks.
+/// * `limit`: The `SplitLimit` of the chunks.
+/// * `buffer_size`: The size of the write buffer of every chunk.
+/// * `index`: The number of the next chunk.
+/// * `chunk`: The file of the chunk being written, `None` between two chunks.
+/// * `filled`: How many lines or bytes the chunk being written holds.
+struct Split {
+    template: String,
+    limit: SplitLimit,
+    buffer_size: usize,
+    index: usize,
+    chunk: Option<BufWriter<File>>,
+    filled: usize,
+}
+
+impl Split {
+    fn new(template: &str, limit: SplitLimit, buffer_size: usize) -> Self {
+        Split { template: template.to_string(), limit, buffer_size, index: 0, chunk: None, filled: 0 }
+    }
+}
+
+impl Write for Split {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.chunk.is_none() {
+            let path = chunk_path(&self.template, self.index);
+            let file = File::create(&path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+            self.chunk = Some(BufWriter::with_capacity(self.buffer_size, file));
+            self.index += 1;
+            self.filled = 0;
+        }
+        // only what fits in the chunk is written, the rest goes into the next one
+        let (taken, filled) = match self.limit {
+            SplitLimit::Bytes(limit) => {
+                let taken = buf.len().min(limit - self.filled);
+                (taken, self.filled + taken)
+            }
+            SplitLimit::Lines(limit) => {
+                let missing = limit - self.filled;
+                match buf.iter().enumerate().filter(|&(_, &byte)| byte == b'\n').nth(missing - 1) {
+                    Some((end, _)) => (end + 1, limit),
+                    None => (buf.len(), self.filled + buf.iter().filter(|&&byte| byte == b'\n').count()),
+                }
+            }
+        };
+        let chunk = self.chunk.as_mut().expect("a chunk is open");
+        chunk.write_all(&buf[..taken])?;
+        self.filled = filled;
+        let (SplitLimit::Lines(limit) | SplitLimit::Bytes(limit)) = self.limit;
+        if self.filled == limit {
+            // the chunk is full, it is closed now rather than when the next one starts
+            chunk.flush()?;
+            self.chunk = None;
+        }
+        Ok(taken)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.chunk.as_mut().map_or(Ok(()), Write::flush)
+    }
+}
+
+/// Returns the path of a chunk, the template with its `%d`, or `%03d` to pad the number with zeros to 3
+/// digits, replaced with the number of the chunk, and `%%` with a `%`. The number is added at the end, as in
+/// `out.000`, if the template does not have any.
+fn chunk_path(template: &str, index: usize) -> String {
+    let mut path = String::new();
+    let mut numbered = false;
+    let mut rest = template;
+    while let Some(start) = rest.find('%') {
+        path.push_str(&rest[..start]);
+        let spec = &rest[start + 1..];
+        let digits = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+        if let Some(after) = spec.strip_prefix('%') {
+            path.push('%');
+            rest = after;
+        } else if let Some(after) = spec[digits..].strip_prefix('d') {
+            let width = spec[..digits].parse().unwrap_or(0);
+            path.push_str(&format!("{:01$}", index, width));
+            numbered = true;
+            rest = after;
+        } else {
+            path.push('%');
+            rest = spec;
+        }
+    }
+    path.push_str(rest);
+    if !numbered {
+        path.push_str(&format!(".{:03}", index));
+    }
+    path
+}
+
+/// `Copies` struct is a writer into everything the output is copied to besides the standard output.
+///
+/// # Fields
+///
+/// * `file`: The `OutputFile` of `--output`, `None` if there is none.
+/// * `clipboard`: The output collected for the clipboard, `None` if it is not copied there.
+pub struct Copies {
+    file: Option<OutputFile>,
+    clipboard: Option<Vec<u8>>,
+}
+
+impl Copies {
+    pub fn new(file: Option<OutputFile>, clipboard: bool) -> Self {
+        Copies { file, clipboard: clipboard.then(Vec::new) }
+    }
+
+    /// Finishes the output file, see `OutputFile::finish`, and places the collected output on the clipboard.
+    ///
+    /// # Errors
+    /// The function will return the errors of `OutputFile::finish` and `clipboard::set`.
+    pub fn finish(self) -> Result<(), MinicatError> {
+        if let Some(file) = self.file {
+            file.finish()?;
+        }
+        self.clipboard.map_or(Ok(()), |data| clipboard::set(&data))
+    }
+}
+
+impl Write for Copies {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(buf)?;
+        }
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            clipboard.extend_from_slice(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), Write::flush)
+    }
+}
+
+/// `Tee` struct is a writer copying everything written into it to a second writer.
+///
+/// # Fields
+///
+/// * `inner`: The writer the output goes to.
+/// * `copy`: The writer the output is copied to.
+pub struct Tee<W, C> {
+    inner: W,
+    copy: C,
+}
+
+impl<W: Write, C: Write> Tee<W, C> {
+    pub fn new(inner: W, copy: C) -> Self {
+        Tee { inner, copy }
+    }
+
+    /// Drops the writer the output goes to, and returns the one it is copied to.
+    pub fn into_copy(self) -> C {
+        self.copy
+    }
+}
+
+impl<W: Write, C: Write> Write for Tee<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.copy.write_all(&buf[..written])?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.copy.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the pieces through a `Split` into a new directory, and returns the chunks it left there.
+    fn split(limit: SplitLimit, pieces: &[&str]) -> Vec<String> {
+        let directory = tempfile::tempdir().unwrap();
+        let template = directory.path().join("chunk").to_str().unwrap().to_string();
+        let mut split = Split::new(&template, limit, 16);
+        for piece in pieces {
+            split.write_all(piece.as_bytes()).unwrap();
+        }
+        split.flush().unwrap();
+        let mut names: Vec<_> = fs::read_dir(directory.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        names.sort();
+        names.iter().map(|name| fs::read_to_string(name).unwrap()).collect()
+    }
+
+    #[test]
+    fn chunk_path_numbers_the_chunks() {
+        assert_eq!(chunk_path("out", 7), "out.007");
+        assert_eq!(chunk_path("out-%d.txt", 12), "out-12.txt");
+        assert_eq!(chunk_path("out-%04d.txt", 12), "out-0012.txt");
+        assert_eq!(chunk_path("100%%-%d", 3), "100%-3");
+        assert_eq!(chunk_path("a%b", 1), "a%b.001");
+    }
+
+    #[test]
+    fn split_cuts_the_lines() {
+        assert_eq!(split(SplitLimit::Lines(2), &["1\n2\n3\n", "4\n5"]), ["1\n2\n", "3\n4\n", "5"]);
+        assert_eq!(split(SplitLimit::Lines(2), &["1", "\n", "2\n"]), ["1\n2\n"]);
+    }
+
+    #[test]
+    fn split_cuts_the_bytes() {
+        assert_eq!(split(SplitLimit::Bytes(3), &["abcd", "efg"]), ["abc", "def", "g"]);
+        assert_eq!(split(SplitLimit::Bytes(3), &[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn output_file_is_truncated_before_it_is_written() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("out").to_str().unwrap().to_string();
+        fs::write(&path, "an older and longer output\n").unwrap();
+        let mut output = OutputFile::create(&path, OutputMode::Truncate, None, 16).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        output.write_all(b"new\n").unwrap();
+        output.finish().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        let missing = directory.path().join("missing/out");
+        let error = OutputFile::create(missing.to_str().unwrap(), OutputMode::Truncate, None, 16).err().unwrap();
+        assert!(matches!(error, MinicatError::OpenFailed { .. }));
+    }
+}
@@ -0,0 +1,124 @@
+//! Throttling the output, for `--rate` and `--lines-per-sec`, to feed a slow consumer or to replay a file as if
+//! it was being written, and typing it out for `--delay`, a line or a character at a time, for presentations.
+//!
+//! The pacing happens in the write path, in a `Paced` writer the output goes through just before it leaves, so
+//! every way a file can be read is paced alike. Every chunk is sent when its time has come, counted from the
+//! first byte: with a byte rate, the output goes out in slices of a tenth of a second worth of bytes, with a
+//! line rate, one line at a time, and with a character rate, one UTF-8 character at a time. The output is
+//! flushed after every chunk, so that the pace is the one the consumer sees. The waits are plain sleeps, so
+//! Ctrl+C stops the program right away, even in the middle of a long delay.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `Pace` enum is the rate the output is sent at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pace {
+    /// The given number of bytes per second.
+    Bytes(u64),
+    /// The given number of lines per second, which may be a fraction.
+    Lines(f64),
+    /// The given number of characters per second, which may be a fraction.
+    Chars(f64),
+}
+
+impl Pace {
+    /// Returns the pace of a delay between two lines, or two characters if `chars` is `true`.
+    pub fn delay(delay: Duration, chars: bool) -> Self {
+        let rate = 1.0 / delay.as_secs_f64();
+        if chars { Pace::Chars(rate) } else { Pace::Lines(rate) }
+    }
+}
+
+/// `Paced` struct is a writer sending what is written into it at a `Pace`.
+///
+/// # Fields
+///
+/// * `inner`: The writer the output goes to.
+/// * `pace`: The `Pace`, `None` to write everything right away.
+/// * `start`: When the first byte was sent, `None` until then.
+/// * `sent`: The number of bytes, of whole lines or of characters sent so far, depending on the `Pace`.
+pub struct Paced<W> {
+    inner: W,
+    pace: Option<Pace>,
+    start: Option<Instant>,
+    sent: u64,
+}
+
+impl<W: Write> Paced<W> {
+    pub fn new(inner: W, pace: Option<Pace>) -> Self {
+        Paced { inner, pace, start: None, sent: 0 }
+    }
+}
+
+impl<W: Write> Write for Paced<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(pace) = self.pace else {
+            return self.inner.write(buf);
+        };
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let start = *self.start.get_or_insert_with(Instant::now);
+        let (length, due) = match pace {
+            Pace::Bytes(rate) => {
+                let slice = (rate / 10).max(1) as usize;
+                (buf.len().min(slice), self.sent as f64 / rate as f64)
+            }
+            Pace::Lines(rate) => {
+                let length = buf.iter().position(|&byte| byte == b'\n').map_or(buf.len(), |end| end + 1);
+                (length, self.sent as f64 / rate)
+            }
+            Pace::Chars(rate) => (char_length(buf[0]).min(buf.len()), self.sent as f64 / rate),
+        };
+        if let Some(wait) = (start + Duration::from_secs_f64(due)).checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+        let written = self.inner.write(&buf[..length])?;
+        self.inner.flush()?;
+        self.sent += match pace {
+            Pace::Bytes(_) => written as u64,
+            Pace::Lines(_) => buf[..written].iter().filter(|&&byte| byte == b'\n').count() as u64,
+            Pace::Chars(_) => 1,
+        };
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the length of the UTF-8 character starting with the given byte, 1 for a byte that cannot start one.
+fn char_length(first: u8) -> usize {
+    match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_are_rates_of_lines_or_characters() {
+        assert_eq!(Pace::delay(Duration::from_millis(50), false), Pace::Lines(20.0));
+        assert_eq!(Pace::delay(Duration::from_millis(250), true), Pace::Chars(4.0));
+    }
+
+    #[test]
+    fn characters_are_typed_out_one_at_a_time() {
+        let mut paced = Paced::new(Vec::new(), Some(Pace::delay(Duration::from_millis(20), true)));
+        let start = Instant::now();
+        assert_eq!(paced.write("é!".as_bytes()).unwrap(), 2);
+        assert_eq!(paced.write(b"!").unwrap(), 1);
+        paced.write_all("\u{1f600}\n".as_bytes()).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(60));
+        assert_eq!(paced.inner, "é!\u{1f600}\n".as_bytes());
+        assert_eq!(char_length(0x80), 1);
+    }
+}
@@ -0,0 +1,67 @@
+//! Piping the output through a pager, like `bat` and `git` do.
+//!
+//! The pager is `$PAGER`, or `less -RF` when it is not set: `-R` lets the colors through and `-F` makes `less`
+//! quit right away when the output fits on a single screen, so short files are printed as if there were no
+//! pager at all. Quitting the pager before the end closes the pipe, which is not an error.
+
+use std::env;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::{terminal, Paging};
+
+/// The pager used when `PAGER` is not set.
+const DEFAULT_PAGER: [&str; 2] = ["less", "-RF"];
+
+/// `Pager` struct is the pager process the output is written into.
+///
+/// # Fields
+///
+/// * `child`: The pager process, whose standard input is taken with `Pager::input`.
+pub struct Pager {
+    child: Child,
+}
+
+impl Pager {
+    /// Starts the pager if `paging` asks for it.
+    ///
+    /// ## Parameters
+    /// * `paging` - The `Paging` mode. With `Paging::Auto`, the pager is only used when the standard output is
+    ///   a terminal and `interactive` is `false`.
+    /// * `interactive` - Whether the output is meant to be seen as it comes, like when following files or
+    ///   reading lines typed on the terminal, which a pager would hold back.
+    ///
+    /// ## Returns
+    /// `None` if there is no pager to use, or if it cannot be started, in which case the output goes to the
+    /// standard output as usual.
+    pub fn spawn(paging: Paging, interactive: bool) -> Option<Self> {
+        let wanted = match paging {
+            Paging::Never => false,
+            Paging::Always => true,
+            Paging::Auto => terminal::stdout_is_terminal() && !interactive,
+        };
+        if !wanted {
+            return None;
+        }
+        let pager = env::var("PAGER").ok().filter(|pager| !pager.trim().is_empty());
+        let mut words: Vec<&str> = match &pager {
+            Some(pager) => pager.split_whitespace().collect(),
+            None => DEFAULT_PAGER.to_vec(),
+        };
+        let program = words.remove(0);
+        let child = Command::new(program).args(words).stdin(Stdio::piped()).spawn().ok()?;
+
+        Some(Pager { child })
+    }
+
+    /// Takes the standard input of the pager, which the output is written into. Dropping it tells the pager
+    /// that the output is complete.
+    pub fn input(&mut self) -> ChildStdin {
+        self.child.stdin.take().expect("the input is piped and only taken once")
+    }
+
+    /// Waits for the user to quit the pager, so the shell prompt does not come back over it.
+    pub fn wait(mut self) {
+        // the exit status of the pager does not change the one of the program
+        let _ = self.child.wait();
+    }
+}
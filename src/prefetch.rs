@@ -0,0 +1,75 @@
+//! Reading files ahead on a background thread.
+//!
+//! When many small files are concatenated, most of the time is spent waiting for the next file to be
+//! opened and read. The `Prefetcher` does that on a separate thread while the current file is written,
+//! and hands the files over in their original order.
+
+use std::fs;
+use std::io;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+/// Files larger than this are not read ahead, they are streamed as usual when it is their turn.
+const PREFETCH_LIMIT: u64 = 4 * 1024 * 1024;
+
+/// `Prefetched` enum is the outcome of reading a single file ahead.
+#[derive(Debug)]
+pub enum Prefetched {
+    /// The whole content of the file.
+    Loaded(Vec<u8>),
+    /// The file could not be opened or read.
+    Failed(io::Error),
+    /// The file was left for the main thread, because it is the standard input, not a regular file,
+    /// or too large to be held in memory.
+    Deferred,
+}
+
+/// `Prefetcher` struct receives the files read ahead by its background thread.
+pub struct Prefetcher {
+    receiver: Receiver<Prefetched>,
+}
+
+impl Prefetcher {
+    /// Starts a thread reading the given files in order.
+    ///
+    /// ## Parameters
+    /// * `files` - The files to read, in the order they are going to be asked for.
+    /// * `depth` - How many files may be read ahead of the one currently being written.
+    pub fn spawn(files: Vec<String>, depth: usize) -> Self {
+        let (sender, receiver) = sync_channel(depth);
+        thread::spawn(move || {
+            for filename in files {
+                // the main thread has stopped listening, so there is nothing left to do
+                if sender.send(prefetch(&filename)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Prefetcher { receiver }
+    }
+
+    /// Returns the next file in order, waiting for it if it is not read yet.
+    ///
+    /// ## Returns
+    /// `None` once all the files have been handed over.
+    pub fn next(&self) -> Option<Prefetched> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Reads a single file ahead, unless it has to be left for the main thread.
+fn prefetch(filename: &str) -> Prefetched {
+    if filename == "-" {
+        return Prefetched::Deferred;
+    }
+    match fs::metadata(filename) {
+        Ok(metadata) if metadata.is_file() && metadata.len() <= PREFETCH_LIMIT => {}
+        // errors are reported when the file is opened as usual
+        _ => return Prefetched::Deferred,
+    }
+    match fs::read(filename) {
+        Ok(data) => Prefetched::Loaded(data),
+        Err(e) => Prefetched::Failed(e),
+    }
+}
@@ -0,0 +1,124 @@
+//! Printing the lines of a file last to first, like `tac`.
+//!
+//! Regular files are read backwards in chunks, so only the chunk being looked at and the line being
+//! assembled are ever held in memory. Pipes and the standard input cannot be read backwards, so they are
+//! read to their end first.
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::bom;
+use crate::io_backend::InputFile;
+use crate::{open_file, LinePrinter, MinicatError};
+
+/// The number of bytes read at once when scanning a regular file backwards.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Prints the lines of a file last to first.
+///
+/// ## Parameters
+/// * `filename` - The name of the file to print, `-` for the standard input.
+/// * `printer` - The `LinePrinter` numbering and rendering the lines, in the order they are printed.
+/// * `out` - The writer the lines are written into.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` or `MinicatError::ReadFailed` if the file cannot be
+/// opened or read, and the error of `LinePrinter::print_line` otherwise.
+pub fn print_file<W: Write>(filename: &str, printer: &mut LinePrinter, out: &mut W) -> Result<(), MinicatError> {
+    let config = printer.config;
+    let open_failed = |source| MinicatError::OpenFailed { path: filename.to_string(), source };
+    // a compressed file or an archive is read by `open_file`, to its end like a pipe, and so is a FIFO
+    if filename != "-" && !config.transforms_input(filename) && fs::metadata(filename).is_ok_and(|m| m.is_file()) {
+        let mut file = InputFile::open(filename, config.drop_cache).map_err(open_failed)?;
+        let metadata = file.file().metadata().map_err(open_failed)?;
+        if metadata.is_file() {
+            return print_chunks(filename, &mut file, metadata.len(), printer, out);
+        }
+    }
+    let mut data = Vec::new();
+    open_file(filename, config, printer.file.strip_bom)
+        .map_err(open_failed)?
+        .read_to_end(&mut data)
+        .map_err(|source| MinicatError::ReadFailed { path: filename.to_string(), line: None, source })?;
+
+    print_backwards(filename, &data, printer, out)
+}
+
+/// Prints the lines of a file already in memory last to first.
+///
+/// # Errors
+/// The function will return the error of `LinePrinter::print_line`.
+pub fn print_backwards<W: Write>(
+    filename: &str,
+    data: &[u8],
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let mut end = data.len();
+    while end > 0 && !printer.file_done() {
+        let start = line_start(&data[..end]);
+        print_line(filename, &data[start..end], printer, out)?;
+        end = start;
+    }
+
+    Ok(())
+}
+
+/// Reads a regular file backwards, one chunk at a time, and prints its lines last to first.
+///
+/// `pending` holds the bytes from `position` up to the end of the lines not printed yet. A line is printed
+/// as soon as the newline ending the line before it has been read, or the start of the file reached.
+fn print_chunks<W: Write>(
+    filename: &str,
+    file: &mut InputFile,
+    length: u64,
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    let read_failed = |source| MinicatError::ReadFailed { path: filename.to_string(), line: None, source };
+    let mut position = length;
+    let mut pending: Vec<u8> = Vec::new();
+    while !printer.file_done() {
+        let start = line_start(&pending);
+        if start > 0 || (position == 0 && !pending.is_empty()) {
+            print_line(filename, &pending[start..], printer, out)?;
+            pending.truncate(start);
+            continue;
+        }
+        if position == 0 {
+            break;
+        }
+        let size = CHUNK_SIZE.min(position as usize);
+        position -= size as u64;
+        let mut chunk = vec![0; size];
+        file.file_mut().seek(SeekFrom::Start(position)).map_err(read_failed)?;
+        file.file_mut().read_exact(&mut chunk).map_err(read_failed)?;
+        chunk.extend_from_slice(&pending);
+        pending = chunk;
+        if position == 0 && printer.file.strip_bom {
+            pending.drain(..bom::length(&pending));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the offset of the last line of `data`, the newline ending `data` being part of that line.
+fn line_start(data: &[u8]) -> usize {
+    let body = data.strip_suffix(b"\n").unwrap_or(data);
+    memchr::memrchr(b'\n', body).map_or(0, |newline| newline + 1)
+}
+
+fn print_line<W: Write>(
+    filename: &str,
+    line: &[u8],
+    printer: &mut LinePrinter,
+    out: &mut W,
+) -> Result<(), MinicatError> {
+    printer.print_line(filename, line, out)?;
+    if printer.config.unbuffered {
+        out.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! Reading a serial device, for `--serial DEVICE --baud RATE`, so that minicat logs what a board or a modem
+//! sends with the line numbers and the timestamps of any other input.
+//!
+//! The device is given to the rest of the pipeline as a `serial://DEVICE` input, opened with the `serialport`
+//! crate at the given baud rate, 8 data bits, no parity and one stop bit. A serial line has no end, so it is
+//! read until the device goes away or the program is interrupted, and every line is written as soon as it
+//! arrives. Serial devices need the `serial` feature, without which they are refused.
+
+use std::io::{self, Read};
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "serial")]
+use clap::{Arg, ArgAction};
+
+/// The scheme of the inputs naming a serial device.
+const SCHEME: &str = "serial://";
+
+/// The baud rate of the devices when `--baud` is not given.
+pub const DEFAULT_BAUD: u32 = 9600;
+
+/// Returns `true` if the input is a `serial://` URL.
+pub fn is_serial_url(filename: &str) -> bool {
+    filename.starts_with(SCHEME)
+}
+
+/// Opens the serial device of a `serial://` URL at the given baud rate.
+///
+/// ## Returns
+/// A reader giving what is received from the device, which waits for as long as nothing is received.
+///
+/// # Errors
+/// The function will return an error if the device cannot be opened or configured.
+#[cfg(feature = "serial")]
+pub fn open(url: &str, baud: u32) -> io::Result<Box<dyn Read>> {
+    let device = url.strip_prefix(SCHEME).unwrap_or(url);
+    let port = serialport::new(device, baud).timeout(std::time::Duration::from_secs(60)).open()?;
+    Ok(Box::new(SerialReader(port)))
+}
+
+/// Refuses to open the serial device, since the `serial` feature is not enabled.
+///
+/// # Errors
+/// The function always returns an error of kind `io::ErrorKind::Unsupported`.
+#[cfg(not(feature = "serial"))]
+pub fn open(_url: &str, _baud: u32) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "reading serial devices needs the `serial` feature"))
+}
+
+/// `SerialReader` struct reads a serial port without ever timing out: a port has to be given a timeout, after
+/// which a read fails when nothing was received, and it is simply read again.
+#[cfg(feature = "serial")]
+struct SerialReader(Box<dyn serialport::SerialPort>);
+
+#[cfg(feature = "serial")]
+impl Read for SerialReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.0.read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Returns the input naming the device given with `--serial`, added by `args`, if any.
+#[cfg(feature = "serial")]
+pub fn input_from_matches(matches: &ArgMatches) -> Option<String> {
+    matches.get_one::<String>("serial").map(|device| format!("{}{}", SCHEME, device))
+}
+
+/// Returns the input naming the device given with `--serial`, which is not offered without the `serial`
+/// feature.
+#[cfg(not(feature = "serial"))]
+pub fn input_from_matches(_matches: &ArgMatches) -> Option<String> {
+    None
+}
+
+/// Returns the baud rate given with `--baud`, added by `args`.
+#[cfg(feature = "serial")]
+pub fn baud_from_matches(matches: &ArgMatches) -> u32 {
+    matches.get_one::<u32>("baud").copied().unwrap_or(DEFAULT_BAUD)
+}
+
+/// Returns the default baud rate, `--baud` not being offered without the `serial` feature.
+#[cfg(not(feature = "serial"))]
+pub fn baud_from_matches(_matches: &ArgMatches) -> u32 {
+    DEFAULT_BAUD
+}
+
+/// Adds the serial device options to the command, if the `serial` feature is enabled.
+///
+/// * `serial` ('--serial'): this option will print what is received from a serial device once the other files
+///   are printed, until it goes away or the program is interrupted.
+/// * `baud` ('--baud'): this option sets the baud rate of the serial device, 9600 by default.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "serial")]
+    let command = command
+        .arg(Arg::new("serial")
+            .action(ArgAction::Set)
+            .long("serial")
+            .value_name("DEVICE")
+            .conflicts_with_all(["follow", "follow_name", "reverse"])
+            .help("Print what is received from a serial device, e.g. /dev/ttyUSB0"))
+        .arg(Arg::new("baud")
+            .action(ArgAction::Set)
+            .long("baud")
+            .value_name("RATE")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .help("Baud rate of the serial device [default: 9600]"));
+    command
+}
+
+#[cfg(all(test, feature = "serial"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serial_options_give_the_device_input_and_the_baud_rate() {
+        // the options `--serial` conflicts with
+        let others = ["follow", "follow_name", "reverse"].map(|id| Arg::new(id).long(id).action(ArgAction::SetTrue));
+        let command = || args(Command::new("minicat").args(&others));
+        let matches = command().try_get_matches_from(["minicat", "--serial", "/dev/ttyUSB0", "--baud", "115200"]);
+        let matches = matches.unwrap();
+        assert_eq!(input_from_matches(&matches).as_deref(), Some("serial:///dev/ttyUSB0"));
+        assert!(is_serial_url("serial:///dev/ttyUSB0"));
+        assert_eq!(baud_from_matches(&matches), 115200);
+        let matches = command().try_get_matches_from(["minicat"]).unwrap();
+        assert_eq!((input_from_matches(&matches), baud_from_matches(&matches)), (None, DEFAULT_BAUD));
+        assert!(command().try_get_matches_from(["minicat", "--baud", "0"]).is_err());
+    }
+
+    #[test]
+    fn missing_devices_cannot_be_opened() {
+        let directory = tempfile::tempdir().unwrap();
+        let device = directory.path().join("ttyUSB0");
+        assert!(open(&format!("serial://{}", device.display()), DEFAULT_BAUD).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+//! Serving the output over HTTP with `--serve ADDRESS`, so that a log can be shared in a moment, as in
+//! `minicat --serve :8080 build.log`.
+//!
+//! Every `GET /` request processes the files again, with every option applied, and answers with the output as
+//! `text/plain`, so the page shows what the files hold when it is loaded. The files that cannot be read are
+//! reported on the standard error and left out of the page, like they are left out of the output. Any other
+//! path is not found. The server is built on `tiny_http`, behind the `serve` feature, and runs until minicat is
+//! interrupted.
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "serve")]
+use clap::{Arg, ArgAction};
+#[cfg(feature = "serve")]
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::{Config, MinicatError};
+
+/// Returns the address given with `--serve`, added by `args`, if any.
+#[cfg(feature = "serve")]
+pub fn requested(matches: &ArgMatches) -> Option<String> {
+    matches.get_one::<String>("serve").cloned()
+}
+
+/// Returns `None`, `--serve` is not offered without the `serve` feature.
+#[cfg(not(feature = "serve"))]
+pub fn requested(_matches: &ArgMatches) -> Option<String> {
+    None
+}
+
+/// Serves the output of the `Config` on the address, `HOST:PORT` or `:PORT` for every interface, until the
+/// program is interrupted.
+///
+/// # Errors
+/// The function will return `MinicatError::OpenFailed` if the address cannot be listened on.
+#[cfg(feature = "serve")]
+pub fn serve(config: &Config, address: &str) -> Result<(), MinicatError> {
+    let bind = if address.starts_with(':') { format!("0.0.0.0{}", address) } else { address.to_string() };
+    let server = Server::http(&bind)
+        .map_err(|e| MinicatError::OpenFailed { path: address.to_string(), source: std::io::Error::other(e) })?;
+    eprintln!("minicat: serving on http://{}/", bind);
+    let content_type = Header::from_bytes("Content-Type", "text/plain; charset=utf-8").expect("a valid header");
+    for request in server.incoming_requests() {
+        let response = match (request.method(), request.url()) {
+            (Method::Get | Method::Head, "/") => {
+                let mut output = Vec::new();
+                match crate::cat(config, &mut output, false) {
+                    Ok(()) | Err(MinicatError::FilesFailed(_)) => {
+                        Response::from_data(output).with_header(content_type.clone())
+                    }
+                    Err(e) => {
+                        eprintln!("minicat: {}", e);
+                        Response::from_string(e.to_string()).with_status_code(500)
+                    }
+                }
+            }
+            (Method::Get | Method::Head, _) => Response::from_string("not found").with_status_code(404),
+            _ => Response::from_string("method not allowed").with_status_code(405),
+        };
+        // a client gone before the end of the response is not worth stopping the server for
+        let _ = request.respond(response);
+    }
+    Ok(())
+}
+
+/// Prints the output as usual, there is no server without the `serve` feature and `--serve` is not offered.
+#[cfg(not(feature = "serve"))]
+pub fn serve(config: &Config, _address: &str) -> Result<(), MinicatError> {
+    crate::cat(config, &mut std::io::stdout().lock(), true)
+}
+
+/// Adds the server option to the command, if the `serve` feature is enabled.
+///
+/// * `serve` ('--serve'): this option will serve the output over HTTP on the given address, `HOST:PORT` or
+///   `:PORT`, processing the files again for every request, instead of printing it.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "serve")]
+    let command = command
+        .arg(Arg::new("serve")
+            .action(ArgAction::Set)
+            .long("serve")
+            .value_name("ADDRESS")
+            .conflicts_with_all(["follow", "follow_name", "paging", "output"])
+            .help("Serve the output over HTTP on ADDRESS, e.g. :8080, instead of printing it"));
+    command
+}
@@ -0,0 +1,129 @@
+//! Reading sockets as inputs: Unix domain sockets given as `unix:///path/to/socket` or with `--unix-socket`,
+//! so that minicat can dump what a local daemon sends on its socket, and a TCP connection accepted on an
+//! address given as `listen://HOST:PORT` or with `--listen`, like a minimal `nc -l`.
+//!
+//! The connection is read like a pipe until the other end closes it, after which it is closed as well and the
+//! next input is printed. Nothing is written to the socket. Unix domain sockets only exist on Unix, elsewhere
+//! they are refused.
+
+use std::io::{self, Read};
+use std::net::TcpListener;
+
+/// The scheme of the inputs naming a Unix domain socket.
+const UNIX_SCHEME: &str = "unix://";
+
+/// The scheme of the inputs naming an address to listen on.
+const LISTEN_SCHEME: &str = "listen://";
+
+/// Returns `true` if the input is a `unix://` or a `listen://` URL.
+pub fn is_socket_url(filename: &str) -> bool {
+    filename.starts_with(UNIX_SCHEME) || filename.starts_with(LISTEN_SCHEME)
+}
+
+/// Returns the input naming the socket at the given path, for `--unix-socket`.
+pub fn unix_url(path: &str) -> String {
+    format!("{}{}", UNIX_SCHEME, path)
+}
+
+/// Returns the input naming the address to listen on, for `--listen`.
+pub fn listen_url(address: &str) -> String {
+    format!("{}{}", LISTEN_SCHEME, address)
+}
+
+/// Opens the socket of a `unix://` or a `listen://` URL.
+///
+/// ## Returns
+/// A reader giving what is received on the connection.
+///
+/// # Errors
+/// The function will return the errors of `connect` and `accept`.
+pub fn open(url: &str) -> io::Result<Box<dyn Read>> {
+    match url.strip_prefix(LISTEN_SCHEME) {
+        Some(address) => accept(address),
+        None => connect(url.strip_prefix(UNIX_SCHEME).unwrap_or(url)),
+    }
+}
+
+/// Listens on the address, `HOST:PORT`, until a single connection comes. The address is no longer listened on
+/// once it is accepted, so any other client is refused.
+///
+/// # Errors
+/// The function will return an error if the address cannot be resolved or bound, such as
+/// `io::ErrorKind::AddrInUse`, or if accepting the connection fails.
+fn accept(address: &str) -> io::Result<Box<dyn Read>> {
+    let (stream, _) = TcpListener::bind(address)?.accept()?;
+    Ok(Box::new(stream))
+}
+
+/// Connects to the Unix domain socket at the given path.
+///
+/// # Errors
+/// The function will return an error if the connection fails, such as `io::ErrorKind::NotFound` if there is no
+/// such socket, or `io::ErrorKind::ConnectionRefused` if nothing listens on it.
+#[cfg(unix)]
+fn connect(path: &str) -> io::Result<Box<dyn Read>> {
+    Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?))
+}
+
+/// Refuses to connect to the socket, since there are no Unix domain sockets on this platform.
+///
+/// # Errors
+/// The function always returns an error of kind `io::ErrorKind::Unsupported`.
+#[cfg(not(unix))]
+fn connect(_path: &str) -> io::Result<Box<dyn Read>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Unix domain sockets are only supported on Unix"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sockets_are_named_by_their_urls() {
+        assert_eq!(unix_url("/run/daemon.sock"), "unix:///run/daemon.sock");
+        assert_eq!(listen_url("127.0.0.1:9000"), "listen://127.0.0.1:9000");
+        assert!(is_socket_url("unix:///run/daemon.sock") && is_socket_url("listen://:9000"));
+        assert!(!is_socket_url("unix.sock"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_sockets_are_read_until_closed() {
+        use std::io::Write;
+        use std::os::unix::net::UnixListener;
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("socket");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || listener.accept().unwrap().0.write_all(b"from the daemon\n").unwrap());
+        let mut received = String::new();
+        open(&unix_url(path.to_str().unwrap())).unwrap().read_to_string(&mut received).unwrap();
+        server.join().unwrap();
+        assert_eq!(received, "from the daemon\n");
+        let missing = directory.path().join("missing");
+        assert_eq!(open(&unix_url(missing.to_str().unwrap())).err().unwrap().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn listening_accepts_a_single_connection() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        let busy = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = busy.local_addr().unwrap().to_string();
+        assert_eq!(open(&listen_url(&address)).err().unwrap().kind(), io::ErrorKind::AddrInUse);
+        drop(busy);
+        let url = listen_url(&address);
+        // the client retries until the address is listened on again
+        let client = std::thread::spawn(move || loop {
+            match TcpStream::connect(&address) {
+                Ok(mut stream) => break stream.write_all(b"over the network\n").unwrap(),
+                Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        });
+        let mut received = String::new();
+        open(&url).unwrap().read_to_string(&mut received).unwrap();
+        client.join().unwrap();
+        assert_eq!(received, "over the network\n");
+    }
+}
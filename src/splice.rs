@@ -0,0 +1,66 @@
+//! Zero-copy transfer of regular files into the standard output when it is a pipe.
+//!
+//! On Linux the data is moved with `splice(2)`, so it never passes through user space. Everywhere else
+//! `splice_to_stdout` does nothing and the caller falls back to a regular copy.
+
+use std::fs::File;
+use std::io;
+
+/// Moves the rest of `file`, from its current offset, into the standard output.
+///
+/// ## Parameters
+/// * `file` - A regular file opened for reading.
+///
+/// ## Returns
+/// `true` if the whole file was transferred, `false` if splicing is not possible here (the standard output
+/// is not a pipe, the kernel refused it, or the platform is not Linux). The file offset always points right
+/// after the transferred data, so the caller can finish the job with a regular copy.
+///
+/// # Errors
+/// The function will return an error if splicing fails for any other reason, for example a broken pipe.
+#[cfg(target_os = "linux")]
+pub fn splice_to_stdout(file: &File) -> io::Result<bool> {
+    use std::os::fd::{AsFd, AsRawFd};
+    use std::os::unix::fs::FileTypeExt;
+
+    let stdout = io::stdout();
+    let stdout_fd = stdout.as_fd();
+    let stdout_type = File::from(stdout_fd.try_clone_to_owned()?).metadata()?.file_type();
+    if !stdout_type.is_fifo() {
+        return Ok(false);
+    }
+
+    const CHUNK: usize = 1 << 20;
+    loop {
+        // SAFETY: both descriptors stay open for the duration of the call and null offsets make the
+        // kernel use and advance the file offsets.
+        let moved = unsafe {
+            libc::splice(
+                file.as_raw_fd(),
+                std::ptr::null_mut(),
+                stdout_fd.as_raw_fd(),
+                std::ptr::null_mut(),
+                CHUNK,
+                libc::SPLICE_F_MOVE | libc::SPLICE_F_MORE,
+            )
+        };
+        match moved {
+            0 => return Ok(true),
+            moved if moved > 0 => continue,
+            _ => {}
+        }
+        let error = io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            // not supported for this file or pipe, or the pipe is non-blocking and full
+            Some(libc::EINVAL | libc::ENOSYS | libc::EAGAIN) => return Ok(false),
+            _ => return Err(error),
+        }
+    }
+}
+
+/// Portable fallback of `splice_to_stdout`, which never transfers anything.
+#[cfg(not(target_os = "linux"))]
+pub fn splice_to_stdout(_file: &File) -> io::Result<bool> {
+    Ok(false)
+}
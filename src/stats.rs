@@ -0,0 +1,279 @@
+//! Counting the lines, words and bytes of the printed files, for `--stats` and `--stats-only`.
+//!
+//! The counts are taken on the output as it is written, through a `Counted` writer, so every way a file can be
+//! read feeds them without the files being read a second time. Once everything has been printed, a summary
+//! in the format of `wc -lwcL` is written to the standard error: lines, words, bytes and the length of the
+//! longest line of every file, then their total when there are several files.
+//!
+//! The files printed as they are are counted as they are written. The lines printed one by one are counted by
+//! the `LinePrinter` instead, before it numbers and decorates them, so that neither the line numbers, the `$` of
+//! `--show-ends` nor the escape sequences of the colors are counted. The lines dropped by the filters are not
+//! counted either.
+
+use std::io::{self, Write};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+/// The width of a tab in the length of the lines, like `wc -L` counts it.
+const TAB: u64 = 8;
+
+/// `StatsMode` enum decides whether the summary of the files is written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StatsMode {
+    /// Only the files are printed.
+    #[default]
+    Off,
+    /// The summary is written after the files.
+    Report,
+    /// Only the summary is written, the files are counted without being printed.
+    Only,
+}
+
+/// `Counts` struct is the summary of a file, or the total of several ones.
+///
+/// # Fields
+///
+/// * `lines`: The number of newlines.
+/// * `words`: The number of sequences of characters separated by whitespace.
+/// * `bytes`: The number of bytes.
+/// * `max_line_length`: The number of columns of the longest line, a tab reaching the next multiple of 8.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    pub lines: u64,
+    pub words: u64,
+    pub bytes: u64,
+    pub max_line_length: u64,
+}
+
+impl Counts {
+    /// Adds the counts of another file to these ones.
+    fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+    }
+}
+
+/// `Counter` struct counts a file fed to it in chunks, keeping the word and the line it is in from one chunk
+/// to the next.
+///
+/// # Fields
+///
+/// * `counts`: The counts so far.
+/// * `in_word`: Whether the last byte was part of a word.
+/// * `column`: The length of the current line so far.
+#[derive(Debug, Default)]
+pub struct Counter {
+    counts: Counts,
+    in_word: bool,
+    column: u64,
+}
+
+impl Counter {
+    /// Counts the next bytes of the file.
+    pub fn update(&mut self, data: &[u8]) {
+        self.counts.bytes += data.len() as u64;
+        for &byte in data {
+            match byte {
+                b'\n' => {
+                    self.counts.lines += 1;
+                    self.end_line();
+                }
+                // a carriage return or a form feed goes back to the first column, like `wc -L` has it
+                b'\r' | b'\x0c' => self.end_line(),
+                b'\t' => self.column += TAB - self.column % TAB,
+                // the continuation bytes of UTF-8 characters do not take a column of their own
+                0x80..=0xbf => {}
+                _ if byte.is_ascii_control() => {}
+                _ => self.column += 1,
+            }
+            let blank = byte.is_ascii_whitespace() || byte == b'\x0b';
+            if !blank && !self.in_word {
+                self.counts.words += 1;
+            }
+            self.in_word = !blank;
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.counts.max_line_length = self.counts.max_line_length.max(self.column);
+        self.column = 0;
+    }
+
+    /// Returns the counts of the file, its last line included even without a newline.
+    pub fn finish(mut self) -> Counts {
+        self.end_line();
+        self.counts
+    }
+}
+
+/// `Counted` struct is a writer counting the files written through it.
+///
+/// # Fields
+///
+/// * `inner`: The writer the output goes to.
+/// * `mode`: The `StatsMode`. With `StatsMode::Only`, nothing reaches `inner`, and with `StatsMode::Off`
+///   nothing is counted.
+/// * `file`: The `Counter` of the file being printed, `None` between two files.
+/// * `files`: The names and counts of the files printed so far.
+pub struct Counted<W> {
+    inner: W,
+    mode: StatsMode,
+    file: Option<Counter>,
+    files: Vec<(String, Counts)>,
+}
+
+impl<W: Write> Counted<W> {
+    /// Wraps `inner`, counting the files unless `mode` is `StatsMode::Off`.
+    pub fn new(inner: W, mode: StatsMode) -> Self {
+        Counted { inner, mode, file: None, files: Vec::new() }
+    }
+
+    /// Returns `true` if the files are counted, in which case every byte has to be written through this writer.
+    pub fn is_active(&self) -> bool {
+        self.mode != StatsMode::Off
+    }
+
+    /// Starts counting a file, the bytes written from now on belong to it.
+    pub fn start_file(&mut self) {
+        if self.is_active() {
+            self.file = Some(Counter::default());
+        }
+    }
+
+    /// Ends counting the file started last, and keeps its counts. `lines` is the `Counter` of the lines of the
+    /// file before their decorations, if it was printed line by line, which is kept instead of what was written.
+    pub fn end_file(&mut self, filename: &str, lines: Option<Counter>) {
+        if let Some(file) = self.file.take() {
+            self.files.push((filename.to_string(), lines.unwrap_or(file).finish()));
+        }
+    }
+
+    /// Writes the summary of the files into `out`, if they are counted: a line per file and a total when there are
+    /// several files, with the counts aligned in columns as wide as the largest of them.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails.
+    pub fn report<O: Write>(&self, out: &mut O) -> io::Result<()> {
+        if !self.is_active() {
+            return Ok(());
+        }
+        let mut total = Counts::default();
+        for (_, counts) in &self.files {
+            total.add(counts);
+        }
+        let width = total.bytes.max(total.words).max(total.lines).max(total.max_line_length).to_string().len();
+        let total = (self.files.len() > 1).then(|| ("total".to_string(), total));
+        for (name, counts) in self.files.iter().chain(total.as_ref()) {
+            writeln!(
+                out,
+                "{:>width$} {:>width$} {:>width$} {:>width$} {}",
+                counts.lines, counts.words, counts.bytes, counts.max_line_length, name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Counted<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = if self.mode == StatsMode::Only { buf.len() } else { self.inner.write(buf)? };
+        if let Some(file) = self.file.as_mut() {
+            file.update(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Returns the `StatsMode` of the options added by `args`.
+pub fn from_matches(matches: &ArgMatches) -> StatsMode {
+    if matches.get_flag("stats_only") {
+        StatsMode::Only
+    } else if matches.get_flag("stats") {
+        StatsMode::Report
+    } else {
+        StatsMode::Off
+    }
+}
+
+/// Adds the summary options to the command.
+///
+/// * `stats` ('--stats'): this option will write the lines, words, bytes and longest line of every printed
+///   file, and their total, to the standard error once they are printed, without their decorations.
+/// * `stats_only` ('--stats-only'): this option will write the same summary without printing the files.
+pub fn args(command: Command) -> Command {
+    command
+        .arg(Arg::new("stats")
+            .action(ArgAction::SetTrue)
+            .long("stats")
+            .help("Write the lines, words, bytes and longest line of the files to stderr, like wc -lwcL"))
+        .arg(Arg::new("stats_only")
+            .action(ArgAction::SetTrue)
+            .long("stats-only")
+            .help("Write the summary of --stats without printing the files"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(chunks: &[&[u8]]) -> Counts {
+        let mut counter = Counter::default();
+        for chunk in chunks {
+            counter.update(chunk);
+        }
+        counter.finish()
+    }
+
+    #[test]
+    fn counter_counts_like_wc() {
+        let counts = count(&[b"one two\n\tthree\nfour"]);
+        assert_eq!(counts, Counts { lines: 2, words: 4, bytes: 19, max_line_length: 13 });
+    }
+
+    #[test]
+    fn counter_keeps_words_and_lines_across_chunks() {
+        assert_eq!(count(&[b"wo", b"rd li", b"ne\n"]), count(&[b"word line\n"]));
+        assert_eq!(count(&["\u{e9}t\u{e9}".as_bytes()]).max_line_length, 3);
+    }
+
+    #[test]
+    fn counted_counts_every_file_written_through_it() {
+        let mut counted = Counted::new(Vec::new(), StatsMode::Report);
+        for (name, data) in [("a", "1\n"), ("b", "2 3\n")] {
+            counted.start_file();
+            counted.write_all(data.as_bytes()).unwrap();
+            counted.end_file(name, None);
+        }
+        let mut report = Vec::new();
+        counted.report(&mut report).unwrap();
+        assert_eq!(counted.inner, b"1\n2 3\n");
+        assert_eq!(String::from_utf8(report).unwrap(), "1 1 2 1 a\n1 2 4 3 b\n2 3 6 3 total\n");
+    }
+
+    #[test]
+    fn counted_only_counts_with_stats_only() {
+        let mut counted = Counted::new(Vec::new(), StatsMode::Only);
+        counted.start_file();
+        counted.write_all(b"line\n").unwrap();
+        counted.end_file("a", None);
+        assert!(counted.inner.is_empty());
+        assert_eq!(counted.files[0].1.bytes, 5);
+    }
+
+    #[test]
+    fn counted_keeps_the_lines_counted_before_their_decorations() {
+        let mut counted = Counted::new(Vec::new(), StatsMode::Report);
+        counted.start_file();
+        counted.write_all(b"     1\tline$\n").unwrap();
+        let mut lines = Counter::default();
+        lines.update(b"line\n");
+        counted.end_file("a", Some(lines));
+        assert_eq!(counted.files[0].1, Counts { lines: 1, words: 1, bytes: 5, max_line_length: 4 });
+    }
+}
@@ -0,0 +1,72 @@
+//! Waiting on the standard input, so that `minicat` run by accident does not hang silently.
+//!
+//! When the standard input is a terminal, a hint on the standard error tells that what is typed is being read,
+//! and how to end it. With `--stdin-timeout`, the standard input is polled before it is read, and given up on
+//! if no data arrives in time, which is an error of the `-` file like any other. Polling needs a Unix system,
+//! elsewhere the standard input is waited on for as long as it takes.
+
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+/// The key ending what is typed on a terminal.
+#[cfg(unix)]
+const END_KEY: &str = "Ctrl+D";
+#[cfg(not(unix))]
+const END_KEY: &str = "Ctrl+Z then Enter";
+
+/// Gets the standard input ready to be read, if `filename` is `-`: gives the hint on a terminal, and waits for
+/// data for at most `timeout`, if there is one.
+///
+/// # Errors
+/// The function will return an error of kind `io::ErrorKind::TimedOut` if no data arrives in time, and any
+/// other error if polling the standard input fails.
+pub fn wait(filename: &str, timeout: Option<Duration>) -> io::Result<()> {
+    if filename != "-" {
+        return Ok(());
+    }
+    if io::stdin().is_terminal() {
+        eprintln!("minicat: reading from the standard input, {} to end", END_KEY);
+    }
+    match timeout {
+        Some(timeout) if !readable(timeout)? => {
+            Err(io::Error::new(io::ErrorKind::TimedOut, format!("no data received after {:?}", timeout)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns `true` if the standard input has data, or has reached its end, within `timeout`.
+#[cfg(unix)]
+fn readable(timeout: Duration) -> io::Result<bool> {
+    let mut stdin = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    loop {
+        // SAFETY: `stdin` is a single valid `pollfd` for the whole call
+        match unsafe { libc::poll(&mut stdin, 1, millis) } {
+            -1 => {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(error);
+                }
+            }
+            ready => return Ok(ready > 0),
+        }
+    }
+}
+
+/// Returns `true`, the standard input cannot be polled on this platform.
+#[cfg(not(unix))]
+fn readable(_timeout: Duration) -> io::Result<bool> {
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_standard_input_is_waited_on() {
+        assert!(wait("file.txt", Some(Duration::ZERO)).is_ok());
+        assert!(wait("./-", Some(Duration::ZERO)).is_ok());
+    }
+}
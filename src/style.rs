@@ -0,0 +1,360 @@
+//! Styling the output with ANSI escape sequences.
+//!
+//! Everything that is colored goes through this module: the line numbers, the banners of the files, the
+//! highlighted matches and the whitespace issues of `--show-whitespace-issues`. Whether colors are used at all
+//! is decided by the `ColorChoice` of `--color`: by default, only when the standard output is a terminal and the
+//! `NO_COLOR` environment variable is not set,
+//! see <https://no-color.org>. Highlighting the matches of a pattern needs the `regex` feature, and
+//! the theme changing the colors of the `Palette` needs the `syntect` feature.
+
+use std::cell::Cell;
+use std::env;
+use std::io::{self, Write};
+use std::ops::Range;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+
+use crate::terminal;
+
+/// The escape sequence switching every style off.
+const RESET: &[u8] = b"\x1b[0m";
+
+/// `Style` enum is the escape sequence switching a style on, which `RESET` switches off again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// A fixed escape sequence, using the colors of the terminal.
+    Escape(&'static [u8]),
+    /// A 24-bit foreground color, the way themes give them.
+    #[cfg_attr(not(feature = "syntect"), allow(dead_code))]
+    Rgb(u8, u8, u8),
+}
+
+impl Style {
+    /// Bold red, the matches of `--highlight` when no theme is given.
+    pub const MATCH: Style = Style::Escape(b"\x1b[1;31m");
+
+    /// Green, the line numbers when no theme is given.
+    pub const NUMBER: Style = Style::Escape(b"\x1b[32m");
+
+    /// Bold, the banners of the files.
+    pub const HEADER: Style = Style::Escape(b"\x1b[1m");
+
+    /// A red background, the whitespace issues, which would not show in a foreground color.
+    pub const WHITESPACE: Style = Style::Escape(b"\x1b[41m");
+
+    /// Appends what `body` writes into `output`, wrapped in the style.
+    pub fn paint(self, output: &mut Vec<u8>, body: impl FnOnce(&mut Vec<u8>)) {
+        self.start(output);
+        body(output);
+        Style::end(output);
+    }
+
+    /// Appends the escape sequence switching the style on, for text that is not written in one go.
+    pub fn start(self, output: &mut Vec<u8>) {
+        match self {
+            Style::Escape(escape) => output.extend_from_slice(escape),
+            // writing into a vector cannot fail
+            Style::Rgb(red, green, blue) => {
+                let _ = write!(output, "\x1b[38;2;{};{};{}m", red, green, blue);
+            }
+        }
+    }
+
+    /// Appends the escape sequence switching every style off again.
+    pub fn end(output: &mut Vec<u8>) {
+        output.extend_from_slice(RESET);
+    }
+}
+
+/// `Palette` struct holds the styles of the decorations, which the theme of `--theme` may change.
+///
+/// The default palette leaves everything but the matches uncolored, which is what is used when the output
+/// is not colored.
+///
+/// # Fields
+///
+/// * `number`: The style of the line numbers, `None` to print them uncolored.
+/// * `header`: The style of the banners printed before the files, `None` to print them uncolored.
+/// * `matched`: The style of the matches of `--highlight`.
+/// * `whitespace`: The style of the whitespace issues of `--show-whitespace-issues`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub number: Option<Style>,
+    pub header: Option<Style>,
+    pub matched: Style,
+    pub whitespace: Style,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette { number: None, header: None, matched: Style::MATCH, whitespace: Style::WHITESPACE }
+    }
+}
+
+impl Palette {
+    /// The palette of a colored output when no theme is given.
+    pub const COLORED: Palette = Palette {
+        number: Some(Style::NUMBER),
+        header: Some(Style::HEADER),
+        matched: Style::MATCH,
+        whitespace: Style::WHITESPACE,
+    };
+
+    /// Writes a banner and its newline into `out`, in the header style.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails.
+    pub fn write_header<W: Write>(&self, out: &mut W, banner: &str) -> io::Result<()> {
+        match self.header {
+            Some(style) => {
+                let mut output = Vec::with_capacity(banner.len() + 16);
+                style.paint(&mut output, |output| output.extend_from_slice(banner.as_bytes()));
+                output.push(b'\n');
+                out.write_all(&output)
+            }
+            None => writeln!(out, "{}", banner),
+        }
+    }
+}
+
+/// `ColorChoice` enum decides whether the output is colored, picked with `--color`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colors are used when the standard output is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Colors are always used, even when the output goes to a file or a pipe.
+    Always,
+    /// Colors are never used.
+    Never,
+}
+
+impl ColorChoice {
+    /// Reads the choice from the `--color` option added by `args`.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        match matches.get_one::<String>("color").map(String::as_str) {
+            Some("always") => ColorChoice::Always,
+            Some("never") => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        }
+    }
+
+    /// Returns `true` if the output is colored. With `Auto`, it has to go to a terminal and `NO_COLOR` must
+    /// not be set, which an explicit `Always` overrides.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Auto => {
+                terminal::stdout_is_terminal() && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// `Highlighter` struct colors the matches of a pattern in the printed lines.
+///
+/// # Fields
+///
+/// * `pattern`: The pattern whose matches are highlighted, `None` if nothing is.
+/// * `style`: The `Style` the matches are painted in.
+#[derive(Debug, Default)]
+pub struct Highlighter {
+    #[cfg(feature = "regex")]
+    pattern: Option<Regex>,
+    #[cfg(feature = "regex")]
+    style: Option<Style>,
+}
+
+impl Highlighter {
+    /// Builds the highlighter from the options added by `args`, painting the matches in `style`. Nothing is
+    /// highlighted unless `color` is `true`.
+    #[cfg(feature = "regex")]
+    pub fn from_matches(matches: &ArgMatches, color: bool, style: Style) -> Self {
+        let pattern = matches.get_one::<Regex>("highlight").filter(|_| color).cloned();
+        Highlighter { pattern, style: Some(style) }
+    }
+
+    /// Builds the highlighter from the options added by `args`, of which there are none without the `regex`
+    /// feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn from_matches(_matches: &ArgMatches, _color: bool, _style: Style) -> Self {
+        Highlighter::default()
+    }
+
+    /// Returns `true` if some matches may be highlighted.
+    #[cfg(feature = "regex")]
+    pub fn is_active(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    /// Returns `true` if some matches may be highlighted, which never happens without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn is_active(&self) -> bool {
+        false
+    }
+
+    /// Renders a line into `output` with `render`, wrapping the matches of the pattern in the style.
+    ///
+    /// The matches are looked for in the line as it was read, and each part is rendered separately, so the
+    /// display transformations of `render` do not get in the way of the pattern.
+    #[cfg(feature = "regex")]
+    pub fn render(&self, line: &[u8], output: &mut Vec<u8>, render: impl Fn(&[u8], &mut Vec<u8>)) {
+        let Some(pattern) = &self.pattern else {
+            return render(line, output);
+        };
+        let style = self.style.unwrap_or(Style::MATCH);
+        let mut end = 0;
+        for found in pattern.find_iter(line).filter(|found| !found.is_empty()) {
+            render(&line[end..found.start()], output);
+            style.paint(output, |output| render(found.as_bytes(), output));
+            end = found.end();
+        }
+        render(&line[end..], output);
+    }
+
+    /// Renders a line into `output` with `render`, there is nothing to highlight without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    pub fn render(&self, line: &[u8], output: &mut Vec<u8>, render: impl Fn(&[u8], &mut Vec<u8>)) {
+        render(line, output)
+    }
+}
+
+/// Returns the whitespace issues of a line, the ranges of bytes painted for `--show-whitespace-issues`, in order:
+/// the indentation if it mixes tabs and spaces, and the spaces and tabs at the end of the line, before a carriage
+/// return. A line made of whitespace only is all trailing whitespace.
+pub fn whitespace_issues(line: &[u8]) -> Vec<Range<usize>> {
+    let blank = |byte: &u8| *byte == b' ' || *byte == b'\t';
+    let content = line.strip_suffix(b"\r").unwrap_or(line);
+    let indent = content.iter().position(|byte| !blank(byte)).unwrap_or(content.len());
+    let trailing = content.iter().rposition(|byte| !blank(byte)).map_or(0, |last| last + 1);
+    let mut issues = Vec::new();
+    if indent < trailing && content[..indent].contains(&b' ') && content[..indent].contains(&b'\t') {
+        issues.push(0..indent);
+    }
+    if trailing < content.len() {
+        issues.push(trailing..content.len());
+    }
+    issues
+}
+
+/// Returns a `render` function painting the given ranges of the line in the style, for renderers handing the
+/// line to `render` in consecutive parts, such as the `Highlighter`.
+pub fn paint_ranges<'r>(
+    ranges: &'r [Range<usize>],
+    style: Style,
+    render: impl Fn(&[u8], &mut Vec<u8>) + 'r,
+) -> impl Fn(&[u8], &mut Vec<u8>) + 'r {
+    // where the next part starts in the line
+    let offset = Cell::new(0);
+    move |part: &[u8], output: &mut Vec<u8>| {
+        let start = offset.replace(offset.get() + part.len());
+        let mut position = 0;
+        for range in ranges {
+            let from = range.start.saturating_sub(start).clamp(position, part.len());
+            let to = range.end.saturating_sub(start).min(part.len());
+            if from < to {
+                render(&part[position..from], output);
+                style.paint(output, |output| render(&part[from..to], output));
+                position = to;
+            }
+        }
+        render(&part[position..], output);
+    }
+}
+
+/// Returns `true` if `--show-whitespace-issues`, added by `args`, is given and `color` is `true`.
+pub fn whitespace_issues_from_matches(matches: &ArgMatches, color: bool) -> bool {
+    color && matches.get_flag("show_whitespace_issues")
+}
+
+/// Adds the styling options to the command, `highlight` only if the `regex` feature is enabled.
+///
+/// * `color` ('--color'): this option decides whether the output is colored, `auto`, `always` or `never`.
+///   Given without a value, it stands for `always`.
+/// * `show_whitespace_issues` ('--show-whitespace-issues'): this option will color the whitespace at the end of
+///   the lines, and the indentation mixing tabs and spaces.
+/// * `highlight` ('--highlight'): this option will color the matches of the pattern, printing every line.
+pub fn args(command: Command) -> Command {
+    let command = command
+        .arg(Arg::new("color")
+            .action(ArgAction::Set)
+            .long("color")
+            .value_name("WHEN")
+            .value_parser(["auto", "always", "never"])
+            .num_args(0..=1)
+            .require_equals(true)
+            .default_value("auto")
+            .default_missing_value("always")
+            .help("Color the output: auto (on terminals, unless NO_COLOR is set), always or never"))
+        .arg(Arg::new("show_whitespace_issues")
+            .action(ArgAction::SetTrue)
+            .long("show-whitespace-issues")
+            .overrides_with("show_whitespace_issues")
+            .help("Color the trailing whitespace and the indentation mixing tabs and spaces"));
+    #[cfg(feature = "regex")]
+    let command = command
+        .arg(Arg::new("highlight")
+            .action(ArgAction::Set)
+            .long("highlight")
+            .value_name("PATTERN")
+            .value_parser(|value: &str| Regex::new(value).map_err(|e| e.to_string()))
+            .help("Color the matches of the regular expression PATTERN"));
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_wraps_the_body_in_the_style() {
+        let mut output = Vec::new();
+        Style::NUMBER.paint(&mut output, |output| output.extend_from_slice(b"1"));
+        Style::Rgb(1, 2, 3).paint(&mut output, |output| output.extend_from_slice(b"x"));
+        assert_eq!(output, b"\x1b[32m1\x1b[0m\x1b[38;2;1;2;3mx\x1b[0m");
+    }
+
+    #[test]
+    fn headers_are_colored_only_by_a_colored_palette() {
+        let mut output = Vec::new();
+        Palette::default().write_header(&mut output, "==> a <==").unwrap();
+        Palette::COLORED.write_header(&mut output, "==> b <==").unwrap();
+        assert_eq!(output, b"==> a <==\n\x1b[1m==> b <==\x1b[0m\n");
+    }
+
+    #[test]
+    fn explicit_choices_ignore_the_terminal() {
+        assert!(ColorChoice::Always.enabled());
+        assert!(!ColorChoice::Never.enabled());
+    }
+
+    /// Returns the whitespace issues of a line as pairs of bounds.
+    fn issues(line: &[u8]) -> Vec<(usize, usize)> {
+        whitespace_issues(line).into_iter().map(|range| (range.start, range.end)).collect()
+    }
+
+    #[test]
+    fn whitespace_issues_are_the_trailing_blanks_and_the_mixed_indentation() {
+        assert_eq!(issues(b"code  \t"), [(4, 7)]);
+        assert_eq!(issues(b"code \r"), [(4, 5)]);
+        assert_eq!(issues(b" \tcode"), [(0, 2)]);
+        assert_eq!(issues(b"\t code "), [(0, 2), (6, 7)]);
+        assert_eq!(issues(b" \t "), [(0, 3)]);
+        assert!(issues(b"\t\tcode").is_empty());
+    }
+
+    #[test]
+    fn painted_ranges_may_span_the_parts_of_a_line() {
+        let ranges = [Range { start: 1, end: 3 }];
+        let render = paint_ranges(&ranges, Style::WHITESPACE, |part: &[u8], output: &mut Vec<u8>| {
+            output.extend_from_slice(part)
+        });
+        let mut output = Vec::new();
+        render(b"ab", &mut output);
+        render(b"cd", &mut output);
+        assert_eq!(output, b"a\x1b[41mb\x1b[0m\x1b[41mc\x1b[0md");
+    }
+}
@@ -0,0 +1,126 @@
+//! Expanding the tabs into spaces, like `expand`, for `--expand-tabs`, and turning the indentation into tabs,
+//! like `unexpand`, for `--unexpand`, with the tab stops of `--tabstop`.
+//!
+//! A tab is replaced with the spaces reaching the next tab stop, every `--tabstop` columns, 8 by default. The
+//! columns are counted from the start of the content of the line, whatever is printed in front of it, so the
+//! indentation of the lines stays the same with line numbers or file names, and the tab separating the line
+//! numbers from the lines is expanded as well, keeping the gutter aligned. A character takes a single column,
+//! the continuation bytes of UTF-8 characters none.
+//!
+//! Only the indentation is turned into tabs, the spaces further in the line being often part of the text:
+//! the spaces and tabs a line starts with are replaced with as many tabs as fit in their columns, and the
+//! spaces left over, which normalizes the indentation of sources concatenated from different editors.
+
+use std::borrow::Cow;
+
+/// `Tabs` enum decides what happens to the tabs of the lines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Tabs {
+    /// The tabs are printed as they are.
+    #[default]
+    Keep,
+    /// The tabs are replaced with spaces, up to tab stops the given number of columns apart.
+    Expand(usize),
+    /// The indentation is turned into tabs, up to tab stops the given number of columns apart.
+    Unexpand(usize),
+}
+
+impl Tabs {
+    /// Returns `true` if the lines are changed.
+    pub fn is_active(self) -> bool {
+        self != Tabs::Keep
+    }
+
+    /// Returns the content of a line with its tabs handled, or the line as it is if nothing changes.
+    pub fn apply(self, line: &[u8]) -> Cow<'_, [u8]> {
+        match self {
+            Tabs::Keep => Cow::Borrowed(line),
+            Tabs::Expand(tabstop) => expand(line, tabstop, 0),
+            Tabs::Unexpand(tabstop) => unexpand(line, tabstop),
+        }
+    }
+
+    /// Returns the separator of the line numbers with its tabs handled, the numbers taking `width` columns.
+    pub fn separator(self, separator: &str, width: usize) -> String {
+        match self {
+            Tabs::Keep | Tabs::Unexpand(_) => separator.to_string(),
+            Tabs::Expand(tabstop) => {
+                String::from_utf8_lossy(&expand(separator.as_bytes(), tabstop, width)).into_owned()
+            }
+        }
+    }
+}
+
+/// Returns `data` with every tab replaced with spaces up to the next tab stop, `data` starting at `column`.
+///
+/// Data without any tab is returned as it is, without being copied.
+pub fn expand(data: &[u8], tabstop: usize, mut column: usize) -> Cow<'_, [u8]> {
+    if !data.contains(&b'\t') {
+        return Cow::Borrowed(data);
+    }
+    let mut expanded = Vec::with_capacity(data.len() + tabstop);
+    for &byte in data {
+        match byte {
+            b'\t' => {
+                let spaces = tabstop - column % tabstop;
+                expanded.resize(expanded.len() + spaces, b' ');
+                column += spaces;
+                continue;
+            }
+            0x80..=0xbf => {}
+            _ => column += 1,
+        }
+        expanded.push(byte);
+    }
+    Cow::Owned(expanded)
+}
+
+/// Returns `line` with the spaces and tabs it starts with replaced with tabs up to the last tab stop they reach,
+/// and spaces from there.
+///
+/// A line whose indentation is made of tabs only is returned as it is, without being copied.
+pub fn unexpand(line: &[u8], tabstop: usize) -> Cow<'_, [u8]> {
+    let indent = line.iter().position(|&byte| byte != b' ' && byte != b'\t').unwrap_or(line.len());
+    if !line[..indent].contains(&b' ') {
+        return Cow::Borrowed(line);
+    }
+    let columns = line[..indent].iter().fold(0, |column, &byte| match byte {
+        b'\t' => column + tabstop - column % tabstop,
+        _ => column + 1,
+    });
+    let mut unexpanded = Vec::with_capacity(line.len());
+    unexpanded.resize(columns / tabstop, b'\t');
+    unexpanded.resize(unexpanded.len() + columns % tabstop, b' ');
+    unexpanded.extend_from_slice(&line[indent..]);
+    Cow::Owned(unexpanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tabs_are_expanded_up_to_the_next_tab_stop() {
+        assert_eq!(expand(b"a\tbc\td", 4, 0), &b"a   bc  d"[..]);
+        assert_eq!(expand("é\tx".as_bytes(), 4, 0), "é   x".as_bytes());
+        assert_eq!(expand(b"\t", 8, 6), &b"  "[..]);
+        assert!(matches!(expand(b"no tabs", 8, 0), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn separator_keeps_the_gutter_aligned() {
+        assert_eq!(Tabs::Expand(8).separator("\t", 6), "  ");
+        assert_eq!(Tabs::Expand(4).separator(" |\t", 3), " |   ");
+        assert_eq!(Tabs::Keep.separator("\t", 6), "\t");
+        assert_eq!(Tabs::Expand(8).apply(b"\tx"), &b"        x"[..]);
+    }
+
+    #[test]
+    fn indentation_is_turned_into_tabs() {
+        assert_eq!(unexpand(b"          x  y", 4), &b"\t\t  x  y"[..]);
+        assert_eq!(unexpand(b"  \t x", 4), &b"\t x"[..]);
+        assert_eq!(unexpand(b"    ", 4), &b"\t"[..]);
+        assert!(matches!(unexpand(b"\tx  y", 4), Cow::Borrowed(_)));
+        assert_eq!(Tabs::Unexpand(8).separator("\t", 6), "\t");
+    }
+}
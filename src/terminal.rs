@@ -0,0 +1,48 @@
+//! Telling whether the output goes to a terminal, which decides the defaults meant for a person reading it.
+//!
+//! On a terminal, the output is colored with `--color auto`, the Markdown and JSON files are rendered, the pager
+//! of `--paging auto` is started, and the binary files are kept off the screen: their control bytes would garble
+//! the terminal, so they are skipped with a warning unless `--force` is given. Redirected into a file or a pipe,
+//! the output is left as it is. The width of the terminal is what `--wrap` wraps the lines at.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use terminal_size::{terminal_size, Width};
+
+use crate::binary::BinaryMode;
+
+/// Returns `true` if the standard output is a terminal.
+pub fn stdout_is_terminal() -> bool {
+    io::stdout().is_terminal()
+}
+
+/// Returns the number of columns of the terminal the standard output goes to, or else the one of the `COLUMNS`
+/// environment variable, `None` if neither is known.
+pub fn width() -> Option<usize> {
+    terminal_size()
+        .map(|(Width(width), _)| usize::from(width))
+        .or_else(|| env::var("COLUMNS").ok()?.parse().ok())
+        .filter(|&width| width > 0)
+}
+
+/// Returns the `BinaryMode` to use when the output goes straight to the standard output: the binary files that
+/// would be printed as they are get a warning instead on a terminal, unless `force` is `true`.
+pub fn binary_mode(mode: BinaryMode, force: bool) -> BinaryMode {
+    if mode == BinaryMode::Raw && !force && stdout_is_terminal() { BinaryMode::Warn } else { mode }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_binary_files_printed_as_they_are_are_kept_off_the_terminal() {
+        for mode in [BinaryMode::Skip, BinaryMode::Hex] {
+            assert_eq!(binary_mode(mode, false), mode);
+        }
+        assert_eq!(binary_mode(BinaryMode::Raw, true), BinaryMode::Raw);
+        let expected = if stdout_is_terminal() { BinaryMode::Warn } else { BinaryMode::Raw };
+        assert_eq!(binary_mode(BinaryMode::Raw, false), expected);
+    }
+}
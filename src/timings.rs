@@ -0,0 +1,207 @@
+//! Measuring where the time goes while the files are printed, for `--timings`.
+//!
+//! Opening the input files and writing the output are timed where they happen, in `InputFile::open` and in the
+//! `Timed` writer the output goes through, and the durations are added up in process wide counters. Every file
+//! is timed from the moment its turn comes to the moment it has been printed, which gives its total duration:
+//! what is neither opening nor writing is reading it, and processing its lines. The files are not spliced
+//! into the output while they are timed, so that every write is measured. The waits of `--rate`,
+//! `--lines-per-sec` and `--delay` happen before the writes reach `Timed`, so they are counted in the duration of
+//! the files rather than as writing.
+//!
+//! Once everything has been printed, the durations of every file, their total, and the throughput are written
+//! to the standard error.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+
+/// Whether the durations are measured, so nothing is measured when they are not reported.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The time spent opening files, in nanoseconds.
+static OPEN: AtomicU64 = AtomicU64::new(0);
+
+/// The time spent writing the output, in nanoseconds.
+static WRITE: AtomicU64 = AtomicU64::new(0);
+
+/// The number of bytes written to the output.
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// Runs `f`, adding the time it takes to `counter` if the durations are measured.
+fn measure<T>(counter: &AtomicU64, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    counter.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    result
+}
+
+/// Runs `f`, counting the time it takes as time spent opening a file.
+pub fn measure_open<T>(f: impl FnOnce() -> T) -> T {
+    measure(&OPEN, f)
+}
+
+/// `Timed` struct is a writer measuring the time spent writing into the writer it wraps.
+pub struct Timed<W>(pub W);
+
+impl<W: Write> Write for Timed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = measure(&WRITE, || self.0.write(buf))?;
+        WRITTEN.fetch_add(written as u64, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        measure(&WRITE, || self.0.flush())
+    }
+}
+
+/// `Snapshot` struct is the state of the counters at some point in time.
+#[derive(Debug, Clone, Copy)]
+struct Snapshot {
+    at: Instant,
+    open: u64,
+    write: u64,
+    written: u64,
+}
+
+impl Snapshot {
+    fn take() -> Self {
+        Snapshot {
+            at: Instant::now(),
+            open: OPEN.load(Ordering::Relaxed),
+            write: WRITE.load(Ordering::Relaxed),
+            written: WRITTEN.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// `FileTimings` struct is where the time went while a file was printed, or while all of them were.
+///
+/// # Fields
+///
+/// * `name`: The name of the file, `total` for all of them.
+/// * `open`: The time spent opening the file.
+/// * `read`: The time spent reading the file and processing its lines, which is what is left of `total`.
+/// * `write`: The time spent writing the output.
+/// * `total`: The time the file took to print.
+/// * `bytes`: The number of bytes written to the output.
+#[derive(Debug, Clone)]
+struct FileTimings {
+    name: String,
+    open: Duration,
+    read: Duration,
+    write: Duration,
+    total: Duration,
+    bytes: u64,
+}
+
+impl FileTimings {
+    /// Returns the timings of what happened between two snapshots.
+    fn between(name: &str, start: Snapshot, end: Snapshot) -> Self {
+        let open = Duration::from_nanos(end.open - start.open);
+        let write = Duration::from_nanos(end.write - start.write);
+        let total = end.at - start.at;
+        FileTimings {
+            name: name.to_string(),
+            open,
+            read: total.saturating_sub(open + write),
+            write,
+            total,
+            bytes: end.written - start.written,
+        }
+    }
+
+    /// Returns the number of megabytes (millions of bytes) written every second.
+    fn throughput(&self) -> f64 {
+        let seconds = self.total.as_secs_f64();
+        if seconds > 0.0 { self.bytes as f64 / 1e6 / seconds } else { 0.0 }
+    }
+}
+
+/// `Timings` struct keeps the timings of the printed files.
+///
+/// # Fields
+///
+/// * `start`: The snapshot taken when the timings started, `None` when nothing is measured.
+/// * `file`: The snapshot taken when the file being printed started, `None` between two files.
+/// * `files`: The timings of the files printed so far.
+#[derive(Debug)]
+pub struct Timings {
+    start: Option<Snapshot>,
+    file: Option<Snapshot>,
+    files: Vec<FileTimings>,
+}
+
+impl Timings {
+    /// Starts measuring the durations if `enabled` is `true`.
+    pub fn new(enabled: bool) -> Self {
+        ENABLED.store(enabled, Ordering::Relaxed);
+        Timings { start: enabled.then(Snapshot::take), file: None, files: Vec::new() }
+    }
+
+    /// Starts timing a file.
+    pub fn start_file(&mut self) {
+        if self.start.is_some() {
+            self.file = Some(Snapshot::take());
+        }
+    }
+
+    /// Ends timing the file started last, and keeps its timings.
+    pub fn end_file(&mut self, filename: &str) {
+        if let Some(start) = self.file.take() {
+            self.files.push(FileTimings::between(filename, start, Snapshot::take()));
+        }
+    }
+
+    /// Writes the timings of the files into `out`, if they are measured, followed by the ones of the whole run,
+    /// whose throughput is the one of the whole output.
+    ///
+    /// # Errors
+    /// The function will return an error if writing to `out` fails.
+    pub fn report<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let Some(start) = self.start else {
+            return Ok(());
+        };
+        let total = FileTimings::between("total", start, Snapshot::take());
+        writeln!(
+            out,
+            "{:>10} {:>10} {:>10} {:>10} {:>12} {:>9}  file",
+            "open ms", "read ms", "write ms", "total ms", "bytes", "MB/s"
+        )?;
+        for timings in self.files.iter().chain([&total]) {
+            writeln!(
+                out,
+                "{:>10.3} {:>10.3} {:>10.3} {:>10.3} {:>12} {:>9.1}  {}",
+                timings.open.as_secs_f64() * 1e3,
+                timings.read.as_secs_f64() * 1e3,
+                timings.write.as_secs_f64() * 1e3,
+                timings.total.as_secs_f64() * 1e3,
+                timings.bytes,
+                timings.throughput(),
+                timings.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `--timings`, added by `args`, is given.
+pub fn from_matches(matches: &ArgMatches) -> bool {
+    matches.get_flag("timings")
+}
+
+/// Adds the timing option to the command.
+///
+/// * `timings` ('--timings'): this option will write the time spent opening, reading and writing every file,
+///   and the throughput, to the standard error once they are printed.
+pub fn args(command: Command) -> Command {
+    command.arg(Arg::new("timings")
+        .action(ArgAction::SetTrue)
+        .long("timings")
+        .help("Write the open, read and write durations of the files and the throughput to stderr"))
+}
@@ -0,0 +1,363 @@
+//! A minimal interactive viewer of the output, for `--interactive`.
+//!
+//! The files are processed as usual, with every option applied, and the lines that would be printed are shown
+//! in a full screen view instead: they can be scrolled, jumped to by number and searched. The viewer is built
+//! on `ratatui` and only available with the `tui` feature, which keeps the default binary small.
+
+use clap::{ArgMatches, Command};
+#[cfg(feature = "tui")]
+use clap::{Arg, ArgAction};
+#[cfg(feature = "tui")]
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+#[cfg(feature = "tui")]
+use ratatui::layout::{Constraint, Layout};
+#[cfg(feature = "tui")]
+use ratatui::style::{Style, Stylize};
+#[cfg(feature = "tui")]
+use ratatui::text::{Line, Span};
+#[cfg(feature = "tui")]
+use ratatui::widgets::Paragraph;
+#[cfg(feature = "tui")]
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::{Config, MinicatError};
+
+/// The number of columns between two tab stops, tabs are expanded since the terminal is drawn cell by cell.
+#[cfg(feature = "tui")]
+const TAB_WIDTH: usize = 8;
+
+/// `Prompt` enum is what the status line is reading from the keyboard.
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    /// A pattern to search for, after `/`.
+    Search,
+    /// A line number to jump to, after `:`.
+    Jump,
+}
+
+/// `Viewer` struct is the state of the interactive view.
+///
+/// # Fields
+///
+/// * `lines`: The lines of the output, as they are drawn.
+/// * `top`: The index of the first line on the screen.
+/// * `height`: The number of lines on the screen, as of the last time it was drawn.
+/// * `prompt`: What the status line is reading, `None` when it shows the position in the output.
+/// * `input`: The text typed after the prompt.
+/// * `pattern`: The last pattern searched for, whose occurrences are highlighted.
+/// * `message`: A notice shown on the status line until the next key is pressed.
+#[cfg(feature = "tui")]
+struct Viewer {
+    lines: Vec<String>,
+    top: usize,
+    height: usize,
+    prompt: Option<Prompt>,
+    input: String,
+    pattern: Option<String>,
+    message: Option<String>,
+}
+
+#[cfg(feature = "tui")]
+impl Viewer {
+    fn new(output: &[u8]) -> Self {
+        let lines = output.split_inclusive(|&byte| byte == b'\n').map(expand_line).collect();
+        Viewer { lines, top: 0, height: 1, prompt: None, input: String::new(), pattern: None, message: None }
+    }
+
+    /// Draws the view and handles the keys until the viewer is quit.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Draws the visible lines and the status line.
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, status] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        self.height = usize::from(body.height).max(1);
+        self.scroll_to(self.top);
+        let end = (self.top + self.height).min(self.lines.len());
+        let visible = self.lines[self.top..end].iter().map(|line| self.highlight(line)).collect::<Vec<_>>();
+        frame.render_widget(Paragraph::new(visible), body);
+        let status_line = match (self.prompt, &self.message) {
+            (Some(Prompt::Search), _) => format!("/{}", self.input),
+            (Some(Prompt::Jump), _) => format!(":{}", self.input),
+            (None, Some(message)) => message.clone(),
+            (None, None) => format!(
+                "lines {}-{} of {}  (q quit, / search, n/N next/previous, : go to line)",
+                (self.top + 1).min(self.lines.len()),
+                end,
+                self.lines.len()
+            ),
+        };
+        frame.render_widget(Paragraph::new(status_line).reversed(), status);
+    }
+
+    /// Splits a line into spans, the occurrences of the searched pattern reversed.
+    fn highlight<'l>(&self, line: &'l str) -> Line<'l> {
+        let Some(pattern) = self.pattern.as_deref().filter(|pattern| !pattern.is_empty()) else {
+            return Line::raw(line);
+        };
+        let mut spans = Vec::new();
+        let mut end = 0;
+        for (start, found) in line.match_indices(pattern) {
+            spans.push(Span::raw(&line[end..start]));
+            spans.push(Span::styled(found, Style::new().reversed()));
+            end = start + found.len();
+        }
+        spans.push(Span::raw(&line[end..]));
+        Line::from(spans)
+    }
+
+    /// Handles a key press, returning `false` once the viewer is to be quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.message = None;
+        if let Some(prompt) = self.prompt {
+            self.handle_prompt_key(prompt, key.code);
+            return true;
+        }
+        let page = self.height.saturating_sub(1).max(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => self.scroll_to(self.top + 1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_to(self.top.saturating_sub(1)),
+            KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Char('f') => self.scroll_to(self.top + page),
+            KeyCode::PageUp | KeyCode::Char('b') => self.scroll_to(self.top.saturating_sub(page)),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll_to(0),
+            KeyCode::End | KeyCode::Char('G') => self.scroll_to(self.lines.len()),
+            KeyCode::Char('/') => self.prompt = Some(Prompt::Search),
+            KeyCode::Char(':') => self.prompt = Some(Prompt::Jump),
+            KeyCode::Char('n') => self.search(true),
+            KeyCode::Char('N') => self.search(false),
+            _ => {}
+        }
+        true
+    }
+
+    /// Handles a key typed after a prompt: Enter runs the search or the jump, Esc cancels it.
+    fn handle_prompt_key(&mut self, prompt: Prompt, code: KeyCode) {
+        match code {
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Esc => {
+                self.prompt = None;
+                self.input.clear();
+            }
+            KeyCode::Enter => {
+                self.prompt = None;
+                let input = std::mem::take(&mut self.input);
+                match prompt {
+                    Prompt::Search => {
+                        self.pattern = Some(input).filter(|pattern| !pattern.is_empty()).or(self.pattern.take());
+                        self.search(true);
+                    }
+                    Prompt::Jump => match input.trim().parse::<usize>() {
+                        Ok(number) if number > 0 => self.scroll_to(number - 1),
+                        _ => self.message = Some(format!("`{}` is not a line number", input)),
+                    },
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Scrolls to the next line containing the pattern after the first one on the screen, or the previous one
+    /// before it, wrapping around the ends of the output.
+    fn search(&mut self, forward: bool) {
+        let Some(pattern) = self.pattern.as_deref() else {
+            self.message = Some("no pattern to search for, type / first".to_string());
+            return;
+        };
+        let count = self.lines.len();
+        let found = (1..=count)
+            .map(|offset| if forward { (self.top + offset) % count } else { (self.top + count - offset) % count })
+            .find(|&index| self.lines[index].contains(pattern));
+        match found {
+            Some(index) => self.top = index,
+            None => self.message = Some(format!("pattern not found: {}", pattern)),
+        }
+    }
+
+    /// Moves the first line on the screen, without scrolling past the last screenful of lines.
+    fn scroll_to(&mut self, top: usize) {
+        self.top = top.min(self.lines.len().saturating_sub(self.height));
+    }
+}
+
+/// Decodes a line of the output for drawing, without its newline and with its tabs expanded to spaces.
+#[cfg(feature = "tui")]
+fn expand_line(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for c in String::from_utf8_lossy(line).chars() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - column % TAB_WIDTH;
+            expanded.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else if !c.is_control() {
+            expanded.push(c);
+            column += 1;
+        }
+    }
+    expanded
+}
+
+/// Returns `true` if `--interactive`, added by `args`, is given.
+#[cfg(feature = "tui")]
+pub fn requested(matches: &ArgMatches) -> bool {
+    matches.get_flag("interactive")
+}
+
+/// Returns `true` if `--interactive` is given, which it cannot be without the `tui` feature.
+#[cfg(not(feature = "tui"))]
+pub fn requested(_matches: &ArgMatches) -> bool {
+    false
+}
+
+/// Processes the files of the `Config` and shows the output in the interactive viewer until it is quit.
+///
+/// # Errors
+/// The function will return an error if the terminal cannot be drawn on or read from. The files that cannot
+/// be read are reported before the viewer opens and the rest of the output is still shown, the error of
+/// `cat` is then returned once the viewer has been quit.
+#[cfg(feature = "tui")]
+pub fn view(config: &Config) -> Result<(), MinicatError> {
+    let mut output = Vec::new();
+    let result = crate::cat(config, &mut output, false);
+    if let Err(e) = &result {
+        if !e.is_file_error() && !matches!(e, MinicatError::FilesFailed(_)) {
+            return result;
+        }
+    }
+    let mut viewer = Viewer::new(&output);
+    let mut terminal = ratatui::init();
+    let shown = viewer.run(&mut terminal);
+    ratatui::restore();
+    shown?;
+
+    result
+}
+
+/// Prints the output as usual, there is no viewer without the `tui` feature and `--interactive` is not offered.
+#[cfg(not(feature = "tui"))]
+pub fn view(config: &Config) -> Result<(), MinicatError> {
+    crate::cat(config, &mut std::io::stdout().lock(), true)
+}
+
+/// Adds the interactive viewer option to the command, if the `tui` feature is enabled.
+///
+/// * `interactive` ('--interactive'): this option will show the output in a full screen viewer, with scrolling,
+///   jumping to a line and searching, instead of printing it.
+pub fn args(command: Command) -> Command {
+    #[cfg(feature = "tui")]
+    let command = command
+        .arg(Arg::new("interactive")
+            .action(ArgAction::SetTrue)
+            .long("interactive")
+            .overrides_with("interactive")
+            .conflicts_with_all(["follow", "follow_name", "paging"])
+            .help("Browse the output in a full screen viewer with search"));
+    command
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    /// Returns a viewer of the lines `1` to `count`, showing `height` of them.
+    fn viewer(count: usize, height: usize) -> Viewer {
+        let output: String = (1..=count).map(|number| format!("{}\n", number)).collect();
+        let mut viewer = Viewer::new(output.as_bytes());
+        viewer.height = height;
+        viewer
+    }
+
+    /// Presses the keys one after the other, returning `false` if one of them quit the viewer.
+    fn press(viewer: &mut Viewer, codes: &[KeyCode]) -> bool {
+        codes.iter().all(|&code| viewer.handle_key(KeyEvent::from(code)))
+    }
+
+    /// Types `text` and Enter after a prompt.
+    fn type_in(viewer: &mut Viewer, text: &str) {
+        let mut codes: Vec<_> = text.chars().map(KeyCode::Char).collect();
+        codes.push(KeyCode::Enter);
+        press(viewer, &codes);
+    }
+
+    #[test]
+    fn keys_scroll_within_the_output() {
+        let mut viewer = viewer(20, 5);
+        press(&mut viewer, &[KeyCode::Char('j'), KeyCode::Down]);
+        assert_eq!(viewer.top, 2);
+        press(&mut viewer, &[KeyCode::PageDown]);
+        assert_eq!(viewer.top, 6);
+        press(&mut viewer, &[KeyCode::Char('G')]);
+        assert_eq!(viewer.top, 15);
+        press(&mut viewer, &[KeyCode::Down, KeyCode::Char('k')]);
+        assert_eq!(viewer.top, 14);
+        press(&mut viewer, &[KeyCode::Home]);
+        assert_eq!(viewer.top, 0);
+        press(&mut viewer, &[KeyCode::Up, KeyCode::PageUp]);
+        assert_eq!(viewer.top, 0);
+        assert!(!press(&mut viewer, &[KeyCode::Char('q')]));
+    }
+
+    #[test]
+    fn colon_jumps_to_a_line() {
+        let mut viewer = viewer(20, 5);
+        press(&mut viewer, &[KeyCode::Char(':')]);
+        type_in(&mut viewer, "8");
+        assert_eq!(viewer.top, 7);
+        press(&mut viewer, &[KeyCode::Char(':')]);
+        type_in(&mut viewer, "x");
+        assert_eq!(viewer.top, 7);
+        assert_eq!(viewer.message.as_deref(), Some("`x` is not a line number"));
+    }
+
+    #[test]
+    fn slash_searches_and_n_goes_on_around_the_ends() {
+        let mut viewer = viewer(30, 5);
+        press(&mut viewer, &[KeyCode::Char('/')]);
+        type_in(&mut viewer, "2");
+        assert_eq!(viewer.top, 1);
+        press(&mut viewer, &[KeyCode::Char('n')]);
+        assert_eq!(viewer.top, 11);
+        press(&mut viewer, &[KeyCode::Char('N'), KeyCode::Char('N')]);
+        assert_eq!(viewer.top, 28);
+        press(&mut viewer, &[KeyCode::Char('/')]);
+        type_in(&mut viewer, "x");
+        assert_eq!(viewer.message.as_deref(), Some("pattern not found: x"));
+    }
+
+    #[test]
+    fn lines_are_drawn_with_a_status_line() {
+        let mut viewer = Viewer::new(b"a\tb\nc\n");
+        let mut terminal = Terminal::new(TestBackend::new(12, 3)).unwrap();
+        terminal.draw(|frame| viewer.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y| (0..12).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(0), "a       b   ");
+        assert_eq!(row(1), "c           ");
+        assert!(row(2).starts_with("lines 1-2 of"));
+    }
+
+    #[test]
+    fn expanded_lines_lose_their_newline_and_controls() {
+        assert_eq!(expand_line(b"ab\tc\r\n"), "ab      c");
+        assert_eq!(expand_line(b"\x1b[1mx"), "[1mx");
+    }
+}
@@ -0,0 +1,100 @@
+//! Walking directory trees for `--recursive`.
+//!
+//! The trees are walked with the `ignore` crate, which picks the files the way ripgrep does: the files matched
+//! by a `.gitignore`, an `.ignore` or the git exclude files are left out, and so are the hidden files, unless
+//! `--no-ignore` and `--hidden` are given. The entries of every directory are visited in the order of their
+//! names, so the files always come out in the same order. Symbolic links are followed, and a link leading back
+//! into a directory being walked is reported instead of looping forever.
+
+use std::path::Path;
+
+use ignore::WalkBuilder;
+
+/// `Selection` struct decides which files of a tree are printed.
+///
+/// # Fields
+///
+/// * `ignored`: Whether the files matched by the ignore files are printed too, with `--no-ignore`.
+/// * `hidden`: Whether the hidden files and directories are printed too, with `--hidden`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub ignored: bool,
+    pub hidden: bool,
+}
+
+/// Appends the regular files of the tree under `root` to `files`, in the order of their paths.
+///
+/// The entries that cannot be read, and the links leading back into a directory being walked, are reported on
+/// the standard error and skipped. Whatever is neither a directory nor a regular file, like a socket or a
+/// broken link, is skipped silently.
+pub fn walk(root: &Path, selection: Selection, files: &mut Vec<String>) {
+    let walker = WalkBuilder::new(root)
+        .standard_filters(!selection.ignored)
+        // `standard_filters` sets the hidden filter too, so it has to come first
+        .hidden(!selection.hidden)
+        .follow_links(true)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+    for entry in walker {
+        match entry {
+            Ok(entry) if entry.file_type().is_some_and(|file_type| file_type.is_file()) => {
+                files.push(entry.path().to_string_lossy().into_owned());
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("minicat: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Returns the files of the tree under `root`, relative to it.
+    fn files(root: &Path, selection: Selection) -> Vec<String> {
+        let mut files = Vec::new();
+        walk(root, selection, &mut files);
+        let prefix = format!("{}/", root.display());
+        files.iter().map(|file| file.strip_prefix(&prefix).unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn files_come_out_in_the_order_of_their_paths() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("b/c")).unwrap();
+        for name in ["z", "a", "b/y", "b/c/x", "b/a"] {
+            fs::write(root.path().join(name), name).unwrap();
+        }
+        assert_eq!(files(root.path(), Selection::default()), ["a", "b/a", "b/c/x", "b/y", "z"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_followed_but_not_around_a_loop() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("d")).unwrap();
+        fs::write(root.path().join("d/f"), "f").unwrap();
+        std::os::unix::fs::symlink(root.path().join("d/f"), root.path().join("link")).unwrap();
+        std::os::unix::fs::symlink(root.path(), root.path().join("d/loop")).unwrap();
+        std::os::unix::fs::symlink(root.path().join("missing"), root.path().join("broken")).unwrap();
+        assert_eq!(files(root.path(), Selection::default()), ["d/f", "link"]);
+    }
+
+    #[test]
+    fn ignored_and_hidden_files_are_left_out_unless_asked_for() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join(".hidden")).unwrap();
+        for name in [".ignore", "kept", "skipped.log", ".dot", ".hidden/inner"] {
+            fs::write(root.path().join(name), "*.log\n").unwrap();
+        }
+        assert_eq!(files(root.path(), Selection::default()), ["kept"]);
+        let hidden = Selection { hidden: true, ..Selection::default() };
+        assert_eq!(files(root.path(), hidden), [".dot", ".hidden/inner", ".ignore", "kept"]);
+        let ignored = Selection { ignored: true, ..Selection::default() };
+        assert_eq!(files(root.path(), ignored), ["kept", "skipped.log"]);
+        let everything = Selection { ignored: true, hidden: true };
+        assert_eq!(files(root.path(), everything), [".dot", ".hidden/inner", ".ignore", "kept", "skipped.log"]);
+    }
+}
@@ -0,0 +1,184 @@
+//! Fitting the long lines to a width: soft-wrapping them at the width of the terminal for `--wrap`, and
+//! truncating them with an ellipsis for `--truncate`.
+//!
+//! A line wider than the terminal, or than the columns given with `--wrap=COLS`, is cut into rows that fit. The
+//! rows after the first one are continuation lines: they are indented as far as the line numbers, the file
+//! names and the timestamps in front of the line, and get no number of their own. A line wider than the columns
+//! of `--truncate` is cut instead, what is left out being replaced with `…`.
+//!
+//! The widths are the ones a terminal gives the text, measured by grapheme clusters with `unicode-segmentation`
+//! and `unicode-width`: two columns for the wide East Asian characters, a single one for a letter and its
+//! combining marks or for an emoji sequence, none for the control characters, and a tab reaches the next
+//! multiple of 8 columns. The escape sequences of the colors take no column and are never cut apart. The lines
+//! are only fitted as they are printed, they are still read, counted and numbered as the lines of the file.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::ansi::AnsiStripper;
+
+/// The distance between two tab stops of a terminal.
+const TAB: usize = 8;
+
+/// The marker of the end of a truncated line.
+const ELLIPSIS: &str = "…";
+
+/// `Piece` enum is what a run of bytes of a line shows as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece<'a> {
+    /// A grapheme cluster.
+    Text(&'a str),
+    /// A byte that is not valid UTF-8, which shows as a replacement character.
+    Invalid,
+    /// A byte of an escape sequence, which does not show.
+    Escape,
+}
+
+impl Piece<'_> {
+    /// Returns the number of columns the piece takes at the given column.
+    fn width(self, column: usize) -> usize {
+        match self {
+            Piece::Text("\t") => TAB - column % TAB,
+            Piece::Text(grapheme) if grapheme.chars().all(char::is_control) => 0,
+            Piece::Text(grapheme) => grapheme.width(),
+            Piece::Invalid => 1,
+            Piece::Escape => 0,
+        }
+    }
+}
+
+/// Walks the pieces of `data`, handing `visit` the bytes of each of them and what they show as.
+fn walk(data: &[u8], mut visit: impl FnMut(&[u8], Piece)) {
+    let mut escapes = AnsiStripper::default();
+    for chunk in data.utf8_chunks() {
+        for grapheme in chunk.valid().graphemes(true) {
+            // every byte goes through the state machine, which has to see the whole escape sequences
+            let visible = grapheme.bytes().filter(|&byte| !escapes.feed(byte)).count() == 0;
+            visit(grapheme.as_bytes(), if visible { Piece::Text(grapheme) } else { Piece::Escape });
+        }
+        for byte in chunk.invalid().chunks(1) {
+            visit(byte, if escapes.feed(byte[0]) { Piece::Invalid } else { Piece::Escape });
+        }
+    }
+}
+
+/// Returns the number of columns `data` takes.
+fn measure(data: &[u8]) -> usize {
+    let mut column = 0;
+    walk(data, |_, piece| column += piece.width(column));
+    column
+}
+
+/// Wraps the line rendered into `output` at `columns` columns, the content of the line starting at `start`,
+/// after its decorations. The rows are ended with `newline`, the terminator of the line, which must not be
+/// written yet.
+pub fn wrap(output: &mut Vec<u8>, start: usize, columns: usize, newline: &[u8]) {
+    let gutter = measure(&output[..start]);
+    // the content could not fit next to the decorations anyway
+    if gutter >= columns {
+        return;
+    }
+    let content = output.split_off(start);
+    let mut column = gutter;
+    walk(&content, |bytes, piece| {
+        let mut width = piece.width(column);
+        if width > 0 && column + width > columns && column > gutter {
+            output.extend_from_slice(newline);
+            output.resize(output.len() + gutter, b' ');
+            column = gutter;
+            width = piece.width(column);
+        }
+        column += width;
+        output.extend_from_slice(bytes);
+    });
+}
+
+/// Truncates the content of the line rendered into `output`, which starts at `start`, to `columns` columns, the
+/// last of them being the ellipsis if anything is left out. The line terminator must not be written yet, but the
+/// carriage return of a CRLF ending is kept at the end of the content.
+pub fn truncate(output: &mut Vec<u8>, start: usize, columns: usize) {
+    if measure(&output[start..]) <= columns {
+        return;
+    }
+    let mut content = output.split_off(start);
+    let carriage_return = content.pop_if(|&mut byte| byte == b'\r');
+    let mut column = 0;
+    let mut cut = false;
+    walk(&content, |bytes, piece| {
+        // the escape sequences left out would switch the colors back off at the end of the line
+        if piece == Piece::Escape {
+            output.extend_from_slice(bytes);
+            return;
+        }
+        if cut {
+            return;
+        }
+        let width = piece.width(column);
+        if column + width >= columns {
+            output.extend_from_slice(ELLIPSIS.as_bytes());
+            cut = true;
+            return;
+        }
+        column += width;
+        output.extend_from_slice(bytes);
+    });
+    output.extend(carriage_return);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(line: &str, start: usize, columns: usize, newline: &[u8]) -> String {
+        let mut output = line.as_bytes().to_vec();
+        wrap(&mut output, start, columns, newline);
+        String::from_utf8(output).unwrap()
+    }
+
+    fn truncated(line: &str, columns: usize) -> String {
+        let mut output = line.as_bytes().to_vec();
+        truncate(&mut output, 0, columns);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn measure_counts_columns() {
+        assert_eq!(measure(b"abc"), 3);
+        assert_eq!(measure("\u{65e5}\u{672c}".as_bytes()), 4);
+        assert_eq!(measure("e\u{301}".as_bytes()), 1);
+        assert_eq!(measure(b"a\tb"), 9);
+        assert_eq!(measure(b"\x1b[31mred\x1b[0m"), 3);
+    }
+
+    #[test]
+    fn wrap_indents_the_continuation_rows() {
+        assert_eq!(wrapped("abcdef", 0, 4, b"\n"), "abcd\nef");
+        assert_eq!(wrapped("1: abcdef", 3, 6, b"\n"), "1: abc\n   def");
+        assert_eq!(wrapped("12345", 5, 4, b"\n"), "12345");
+    }
+
+    #[test]
+    fn wrap_ends_the_rows_like_the_line() {
+        assert_eq!(wrapped("abcdef", 0, 2, b"\r\n"), "ab\r\ncd\r\nef");
+    }
+
+    #[test]
+    fn wrap_keeps_wide_characters_whole() {
+        assert_eq!(wrapped("a\u{65e5}\u{672c}", 0, 2, b"\n"), "a\n\u{65e5}\n\u{672c}");
+    }
+
+    #[test]
+    fn truncate_ends_with_an_ellipsis() {
+        assert_eq!(truncated("abcdef", 4), "abc\u{2026}");
+        assert_eq!(truncated("abcd", 4), "abcd");
+        assert_eq!(truncated("\x1b[31mabcdef\x1b[0m", 3), "\x1b[31mab\u{2026}\x1b[0m");
+    }
+
+    #[test]
+    fn truncate_measures_the_display_width() {
+        assert_eq!(truncated("\u{65e5}\u{672c}\u{8a9e}", 4), "\u{65e5}\u{2026}");
+        assert_eq!(truncated("e\u{301}e\u{301}e\u{301}", 2), "e\u{301}\u{2026}");
+        assert_eq!(truncated("\u{65e5}\u{672c}", 4), "\u{65e5}\u{672c}");
+        assert_eq!(truncated("abcdef\r", 4), "abc\u{2026}\r");
+    }
+}
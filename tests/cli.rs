@@ -0,0 +1,1477 @@
+//! Runs the `rust-minicat` binary on the files of `tests/inputs` and on files written for the test, and checks
+//! what it prints.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// Returns a command running the binary.
+fn minicat() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_rust-minicat"));
+    command.stdin(Stdio::null());
+    command
+}
+
+/// Runs the binary with `args`, and returns its whole output, checking that it succeeded.
+fn run(args: &[&str]) -> Output {
+    let output = minicat().args(args).output().expect("the binary runs");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+/// Runs the binary with `args` and `input` on its standard input, and returns what it printed, checking that it
+/// succeeded.
+fn pipe(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let mut child = minicat()
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("the binary runs");
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    // the input is written on its own thread, so that the output is read meanwhile whatever their sizes
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output.stdout
+}
+
+/// Interrupts a running binary like Ctrl+C does, and returns its whole output.
+#[cfg(unix)]
+fn interrupt(child: Child) -> Output {
+    // SAFETY: the signal is sent to the child process, which is still running
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    child.wait_with_output().unwrap()
+}
+
+/// Opens a pseudo-terminal, and returns its master side, the terminal side and the path of the latter.
+#[cfg(unix)]
+fn pseudo_terminal() -> (fs::File, fs::File, String) {
+    use std::os::fd::FromRawFd;
+
+    let (mut master, mut terminal) = (0, 0);
+    let mut name = [0 as libc::c_char; 64];
+    // SAFETY: the descriptors and the name are written by `openpty`, the name buffer being large enough
+    let opened = unsafe {
+        libc::openpty(&mut master, &mut terminal, name.as_mut_ptr(), std::ptr::null(), std::ptr::null())
+    };
+    assert_eq!(opened, 0, "{}", std::io::Error::last_os_error());
+    // SAFETY: the name was written as a NUL-terminated string
+    let name = unsafe { std::ffi::CStr::from_ptr(name.as_ptr()) }.to_string_lossy().into_owned();
+    // SAFETY: both sides were just opened, and are only owned by these files
+    unsafe { (fs::File::from_raw_fd(master), fs::File::from_raw_fd(terminal), name) }
+}
+
+/// The input of the tests of the display options: a tab, blank lines, control characters and a byte above
+/// ASCII, a CRLF line and no final newline.
+const SAMPLE: &[u8] = b"a\tb\n\nx\x01\x80y\r\n\n\n\nend";
+
+/// Returns the path of a file of `directory` as a string.
+fn path(directory: &Path, name: &str) -> String {
+    directory.join(name).to_string_lossy().into_owned()
+}
+
+#[test]
+fn show_ends_marks_the_ends_of_the_lines() {
+    assert_eq!(pipe(&["-E"], SAMPLE), b"a\tb$\n$\nx\x01\x80y^M$\n$\n$\n$\nend");
+    assert_eq!(pipe(&["--show-ends"], b"line\n"), b"line$\n");
+}
+
+#[test]
+fn show_tabs_shows_the_tabs_as_carets() {
+    assert_eq!(pipe(&["-T"], SAMPLE), b"a^Ib\n\nx\x01\x80y\r\n\n\n\nend");
+    assert_eq!(pipe(&["--show-tabs"], b"\t\t\n"), b"^I^I\n");
+}
+
+#[test]
+fn show_nonprinting_uses_the_caret_and_meta_notations() {
+    assert_eq!(pipe(&["-v"], SAMPLE), b"a\tb\n\nx^AM-^@y^M\n\n\n\nend");
+    assert_eq!(pipe(&["--show-nonprinting"], b"\x7f\xff\xa0\x1b\n"), b"^?M-^?M- ^[\n");
+}
+
+#[test]
+fn show_all_is_show_nonprinting_ends_and_tabs() {
+    assert_eq!(pipe(&["-A"], SAMPLE), pipe(&["-vET"], SAMPLE));
+    assert_eq!(pipe(&["--show-all"], SAMPLE), b"a^Ib$\n$\nx^AM-^@y^M$\n$\n$\n$\nend");
+}
+
+#[test]
+fn squeeze_blank_keeps_one_empty_line_of_a_run() {
+    assert_eq!(pipe(&["-s"], SAMPLE), b"a\tb\n\nx\x01\x80y\r\n\nend");
+    assert_eq!(pipe(&["--squeeze-blank", "-n"], b"\n\n\na\n"), b"     1\t\n     2\ta\n");
+}
+
+#[test]
+fn e_and_t_add_show_nonprinting() {
+    assert_eq!(pipe(&["-e"], SAMPLE), pipe(&["-vE"], SAMPLE));
+    assert_eq!(pipe(&["-t"], SAMPLE), pipe(&["-vT"], SAMPLE));
+}
+
+#[test]
+fn plain_switches_off_the_decorations_before_it() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "lines");
+    fs::write(&input, "a\nb\n").unwrap();
+    assert_eq!(run(&["-n", &input]).stdout, b"     1\ta\n     2\tb\n");
+    assert_eq!(run(&["-n", "--plain", &input]).stdout, b"a\nb\n");
+    assert_eq!(run(&["--plain", "-E", &input]).stdout, b"a$\nb$\n");
+}
+
+#[test]
+fn unbuffered_prints_every_line_right_away() {
+    use std::io::Read;
+    use std::sync::mpsc;
+
+    let mut child = minicat().args(["-u", "-n"]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    stdin.write_all(b"first\n").unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = [0; 13];
+        stdout.read_exact(&mut line).unwrap();
+        sender.send(line).unwrap();
+    });
+    // the standard input is still open, so the line is only there if it was flushed
+    let line = receiver.recv_timeout(Duration::from_secs(5)).expect("the line is printed before the input ends");
+    assert_eq!(&line, b"     1\tfirst\n");
+    drop(stdin);
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn long_options_are_the_short_ones() {
+    let pairs: [(&[&str], &[&str]); 4] = [
+        (&["-n"], &["--number"]),
+        (&["-b"], &["--number-nonblank"]),
+        (&["-vET"], &["--show-nonprinting", "--show-ends", "--show-tabs"]),
+        (&["-s"], &["--squeeze-blank"]),
+    ];
+    for (short, long) in pairs {
+        assert_eq!(pipe(short, SAMPLE), pipe(long, SAMPLE), "{:?}", long);
+    }
+    assert_eq!(pipe(&["--number-nonblank"], b"a\n\nb\n"), b"     1\ta\n\n     2\tb\n");
+}
+
+#[test]
+fn dash_reads_the_standard_input() {
+    assert_eq!(pipe(&[], b"input\n"), b"input\n");
+    assert_eq!(pipe(&["tests/inputs/oneline.txt", "-"], b"input\n"), b"Hello World\ninput\n");
+    assert_eq!(pipe(&["-n", "-", "tests/inputs/oneline.txt"], b"input\n"), b"     1\tinput\n     2\tHello World\n");
+}
+
+#[test]
+fn binary_files_are_copied_byte_for_byte() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "bytes");
+    let bytes: Vec<u8> = (0..=255).cycle().take(100_000).collect();
+    fs::write(&input, &bytes).unwrap();
+    assert_eq!(run(&[&input]).stdout, bytes);
+    assert_eq!(pipe(&[], &bytes), bytes);
+}
+
+#[test]
+fn line_endings_are_kept_when_the_lines_are_processed() {
+    assert_eq!(pipe(&["-n"], b"a\r\nb"), b"     1\ta\r\n     2\tb");
+    assert_eq!(pipe(&["-n"], b"a\n\n"), b"     1\ta\n     2\t\n");
+}
+
+#[test]
+fn invalid_utf8_is_kept_replaced_or_rejected() {
+    let input = b"a\xffb\nok\n";
+    assert_eq!(pipe(&["-n"], input), b"     1\ta\xffb\n     2\tok\n");
+    assert_eq!(pipe(&["--lossy"], input), "a\u{fffd}b\nok\n".as_bytes());
+    let mut child = minicat().arg("--strict").stdin(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("line 1: invalid UTF-8"));
+}
+
+#[test]
+fn large_output_comes_out_whole() {
+    let input: String = (1..=20_000).map(|line| format!("line {}\n", line)).collect();
+    let output = pipe(&["-n"], input.as_bytes());
+    let expected: String = (1..=20_000).map(|line| format!("{:>6}\tline {}\n", line, line)).collect();
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
+
+#[test]
+fn plain_copy_goes_into_pipes_and_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let args = ["tests/inputs/threelines.txt", "tests/inputs/empty.txt", "tests/inputs/oneline.txt"];
+    let expected: Vec<u8> = args.iter().flat_map(|input| fs::read(input).unwrap()).collect();
+    assert_eq!(run(&args).stdout, expected);
+    let output = path(directory.path(), "output");
+    let file = fs::File::create(&output).unwrap();
+    assert!(minicat().args(args).stdout(file).status().unwrap().success());
+    assert_eq!(fs::read(&output).unwrap(), expected);
+}
+
+#[test]
+fn mmap_prints_the_same_lines() {
+    for args in [&["-n"][..], &["-E"], &["--reverse"]] {
+        let input = "tests/inputs/morelines.txt";
+        let mapped = run(&[args, &["--mmap", input]].concat()).stdout;
+        assert_eq!(mapped, run(&[args, &[input]].concat()).stdout, "{:?}", args);
+    }
+}
+
+#[test]
+fn splice_large_files_into_a_pipe() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "large");
+    let bytes: Vec<u8> = (0..=250).cycle().take(3 << 20).collect();
+    fs::write(&input, &bytes).unwrap();
+    let output = run(&[&input, "tests/inputs/oneline.txt", &input]).stdout;
+    assert_eq!(output.len(), 2 * bytes.len() + 12);
+    assert!(output[..bytes.len()] == bytes[..] && output[bytes.len() + 12..] == bytes[..]);
+}
+
+#[test]
+fn long_lines_are_read_whole() {
+    let line = "x".repeat(1 << 20);
+    let input = format!("{}\nshort\n{}", line, line);
+    let output = pipe(&["-n", "--buffer-size", "1K"], input.as_bytes());
+    assert_eq!(output, format!("     1\t{}\n     2\tshort\n     3\t{}", line, line).as_bytes());
+}
+
+#[test]
+fn prefetched_files_are_printed_in_order() {
+    let directory = tempfile::tempdir().unwrap();
+    let files: Vec<String> = (0..20).map(|index| path(directory.path(), &format!("{:02}", index))).collect();
+    for (index, file) in files.iter().enumerate() {
+        fs::write(file, format!("file {}\n", index)).unwrap();
+    }
+    let args: Vec<&str> = ["--jobs", "4", "-n"].into_iter().chain(files.iter().map(String::as_str)).collect();
+    let expected: String = (0..20).map(|index| format!("{:>6}\tfile {}\n", index + 1, index)).collect();
+    assert_eq!(String::from_utf8(run(&args).stdout).unwrap(), expected);
+    let output = minicat().args(["--jobs", "2", &files[0], "/nonexistent", &files[1]]).output().unwrap();
+    assert_eq!(output.stdout, b"file 0\nfile 1\n");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn buffer_size_does_not_change_the_output() {
+    let expected = run(&["-n", "tests/inputs/morelines.txt"]).stdout;
+    for size in ["1", "7", "1M"] {
+        assert_eq!(run(&["--buffer-size", size, "-n", "tests/inputs/morelines.txt"]).stdout, expected);
+    }
+    assert!(!minicat().args(["--buffer-size", "0"]).output().unwrap().status.success());
+}
+
+#[test]
+fn drop_cache_does_not_change_the_output() {
+    let input = "tests/inputs/morelines.txt";
+    assert_eq!(run(&["--drop-cache", input]).stdout, fs::read(input).unwrap());
+    assert_eq!(run(&["--drop-cache", "-n", input]).stdout, run(&["-n", input]).stdout);
+}
+
+#[test]
+fn exit_status_tells_that_a_file_failed() {
+    let output = minicat().args(["/nonexistent", "tests/inputs/oneline.txt"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"Hello World\n");
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "minicat: /nonexistent: No such file or directory\n");
+}
+
+#[test]
+fn fail_fast_stops_at_the_first_failed_file() {
+    let output = minicat().args(["--fail-fast", "/nonexistent", "tests/inputs/oneline.txt"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "minicat: /nonexistent: No such file or directory\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn closed_pipe_ends_the_program_quietly() {
+    use std::io::Read;
+    use std::os::unix::process::ExitStatusExt;
+
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "large");
+    fs::write(&input, "y\n".repeat(1 << 20)).unwrap();
+    for args in [&[][..], &["-n"]] {
+        let mut child = minicat().args(args).arg(&input).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+        let mut byte = [0];
+        child.stdout.take().unwrap().read_exact(&mut byte).unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.status.signal(), Some(libc::SIGPIPE));
+        assert!(output.stderr.is_empty(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn directory_is_reported_like_coreutils() {
+    let directory = tempfile::tempdir().unwrap();
+    let name = directory.path().to_str().unwrap();
+    for (args, expected) in [(&[][..], "Hello World\n"), (&["-n"], "     1\tHello World\n")] {
+        let output = minicat().args(args).args([name, "tests/inputs/oneline.txt"]).output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+        let errors = String::from_utf8(output.stderr).unwrap();
+        assert!(errors.starts_with(&format!("minicat: {}: Is a directory\n", name)), "{}", errors);
+    }
+}
+
+#[test]
+fn input_is_output_is_refused() {
+    let directory = tempfile::tempdir().unwrap();
+    let file = path(directory.path(), "file");
+    fs::write(&file, "hi\n").unwrap();
+    let append = fs::OpenOptions::new().append(true).open(&file).unwrap();
+    let output = minicat().args([&file, "tests/inputs/oneline.txt"]).stdout(append).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains(&format!("minicat: {}: input file is output file\n", file)), "{}", errors);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "hi\nHello World\n");
+    let output = minicat().args(["--output", &file, &file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_prints_the_appended_data() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "log");
+    fs::write(&input, "first\n").unwrap();
+    let child = minicat().args(["-f", "-n", &input]).stdout(Stdio::piped()).spawn().unwrap();
+    thread::sleep(Duration::from_millis(500));
+    let mut log = fs::OpenOptions::new().append(true).open(&input).unwrap();
+    log.write_all(b"sec").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    log.write_all(b"ond\n").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    let output = interrupt(child);
+    assert_eq!(output.status.code(), Some(130));
+    assert_eq!(output.stdout, b"     1\tfirst\n     2\tsecond\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_name_reopens_rotated_and_truncated_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "log");
+    fs::write(&input, "old\n").unwrap();
+    let child = minicat().args(["-F", &input]).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    thread::sleep(Duration::from_millis(500));
+    fs::rename(&input, path(directory.path(), "log.1")).unwrap();
+    fs::write(&input, "new\n").unwrap();
+    thread::sleep(Duration::from_millis(600));
+    fs::write(&input, "c\n").unwrap();
+    thread::sleep(Duration::from_millis(600));
+    let output = interrupt(child);
+    assert_eq!(output.stdout, b"old\nnew\nc\n");
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("file has been replaced, following the new file"), "{}", errors);
+    assert!(errors.contains("file truncated"), "{}", errors);
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_name_prints_a_banner_only_when_the_output_switches_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\n").unwrap();
+    fs::write(&second, "b\n").unwrap();
+    let child = minicat().args(["-F", &first, &second]).stdout(Stdio::piped()).spawn().unwrap();
+    thread::sleep(Duration::from_millis(700));
+    fs::OpenOptions::new().append(true).open(&first).unwrap().write_all(b"c\n").unwrap();
+    thread::sleep(Duration::from_millis(700));
+    let output = interrupt(child);
+    let expected = format!("==> {first} <==\na\n\n==> {second} <==\nb\n\n==> {first} <==\nc\n");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[cfg(unix)]
+#[test]
+fn followed_files_keep_their_own_state() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "same\n").unwrap();
+    fs::write(&second, "same\n").unwrap();
+    let child = minicat()
+        .args(["--follow", "--squeeze-repeats", "-n", "--restart-numbering", &first, &second])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(500));
+    fs::write(&first, "same\nsame\nother\n").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    let output = interrupt(child);
+    let expected = format!(
+        "==> {first} <==\n     1\tsame\n\n==> {second} <==\n     1\tsame\n\n==> {first} <==\n     2\tother\n"
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_name_waits_for_a_missing_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "later");
+    let child = minicat().args(["-F", &input]).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    thread::sleep(Duration::from_millis(500));
+    fs::write(&input, "appeared\n").unwrap();
+    thread::sleep(Duration::from_millis(500));
+    let output = interrupt(child);
+    assert_eq!(output.status.code(), Some(130));
+    assert_eq!(output.stdout, b"appeared\n");
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("waiting for it to appear"), "{}", errors);
+    assert!(errors.contains("file has appeared"), "{}", errors);
+}
+
+#[test]
+fn max_lines_limits_every_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "1\n2\n3\n4\n5\n").unwrap();
+    fs::write(&second, "a\nb\nc\n").unwrap();
+    assert_eq!(run(&["--max-lines", "2", &first, &second]).stdout, b"1\n2\na\nb\n");
+    assert_eq!(pipe(&["--max-lines", "0"], b"a\nb\n"), b"");
+    assert_eq!(pipe(&["--max-lines", "5"], b"a\nb"), b"a\nb");
+}
+
+#[test]
+fn skip_lines_drops_the_start_of_every_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "1\n2\n3\n4\n5\n").unwrap();
+    fs::write(&second, "a\nb\nc\n").unwrap();
+    assert_eq!(run(&["--skip-lines", "2", &first, &second]).stdout, b"3\n4\n5\nc\n");
+    assert_eq!(pipe(&["--skip-lines", "1", "--max-lines", "1"], b"a\nb\nc\n"), b"b\n");
+    assert_eq!(pipe(&["--skip-lines", "9"], b"a\nb\n"), b"");
+}
+
+#[test]
+fn lines_selects_a_range_of_every_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "1\n2\n3\n4\n5\n").unwrap();
+    fs::write(&second, "a\nb\nc\n").unwrap();
+    assert_eq!(run(&["--lines", "2:3", &first, &second]).stdout, b"2\n3\nb\nc\n");
+    assert_eq!(run(&["--lines", "4:", "-n", &first]).stdout, b"     1\t4\n     2\t5\n");
+    assert_eq!(run(&["--lines", "4:", "-n", "--number-original", &first]).stdout, b"     4\t4\n     5\t5\n");
+    let output = minicat().args(["--lines", "3:2", &first]).output().unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn skip_bytes_and_count_bytes_select_bytes_of_every_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "1\n2\n3\n4\n5\n").unwrap();
+    fs::write(&second, "a\nb\nc\n").unwrap();
+    assert_eq!(run(&["--skip-bytes", "2", "--count-bytes", "4", &first, &second]).stdout, b"2\n3\nb\nc\n");
+    assert_eq!(run(&["--skip-bytes", "8", &first, &second]).stdout, b"5\n");
+    assert_eq!(pipe(&["--skip-bytes", "1", "--count-bytes", "3", "-E"], b"abcdef\n"), b"bcd");
+    assert_eq!(pipe(&["--count-bytes", "1K"], &[b'x'; 2000]), [b'x'; 1024]);
+}
+
+#[test]
+fn reverse_prints_the_lines_of_every_file_last_to_first() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "1\n2\n3\n").unwrap();
+    fs::write(&second, "a\nb\n").unwrap();
+    assert_eq!(run(&["--reverse", &first, &second]).stdout, b"3\n2\n1\nb\na\n");
+    assert_eq!(run(&["--reverse", "-n", &first]).stdout, b"     1\t3\n     2\t2\n     3\t1\n");
+    // like tac, the last line without a newline runs into the one before it
+    assert_eq!(pipe(&["--reverse"], b"x\ny"), b"yx\n");
+}
+
+#[test]
+fn repeats_collapse_runs_of_identical_lines() {
+    assert_eq!(pipe(&["--squeeze-repeats"], b"a\na\na\nb\na\n"), b"a\nb\na\n");
+    assert_eq!(pipe(&["--count-repeats"], b"a\na\na\nb\na\n"), b"a\n(repeated 3 times)\nb\na\n");
+    assert_eq!(pipe(&["--squeeze-repeats", "-n"], b"x\nx\ny\n"), b"     1\tx\n     2\ty\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn match_keeps_only_the_matching_lines() {
+    assert_eq!(pipe(&["--match", "an"], b"apple\nbanana\ncherry\n"), b"banana\n");
+    assert_eq!(pipe(&["--match", "^[ac]", "-n"], b"apple\nbanana\ncherry\n"), b"     1\tapple\n     2\tcherry\n");
+    assert_eq!(
+        pipe(&["--match", "^[ac]", "-n", "--number-original"], b"apple\nbanana\ncherry\n"),
+        b"     1\tapple\n     3\tcherry\n"
+    );
+    let output = minicat().args(["--match", "("]).output().unwrap();
+    assert!(!output.status.success());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn match_keeps_numbering_the_lines_across_the_files() {
+    let files = ["tests/inputs/threelines.txt", "tests/inputs/threelines.txt"];
+    assert_eq!(
+        run(&[&["--match", "^[HB]", "-n"][..], &files].concat()).stdout,
+        b"     1\tHello World\n     2\tBut I will make it\n     3\tHello World\n     4\tBut I will make it\n"
+    );
+    assert_eq!(
+        run(&[&["--match", "^[HB]", "-b", "--number-original"][..], &files].concat()).stdout,
+        b"     1\tHello World\n     3\tBut I will make it\n     1\tHello World\n     3\tBut I will make it\n"
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn max_lines_counts_the_matching_lines() {
+    let input = b"apple\nbanana\ncherry\navocado\n";
+    assert_eq!(pipe(&["--match", "^[ac]", "--max-lines", "2"], input), b"apple\ncherry\n");
+    assert_eq!(pipe(&["--exclude", "an", "--lines", "2:3"], input), b"cherry\navocado\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn exclude_drops_the_matching_lines() {
+    assert_eq!(pipe(&["--exclude", "an"], b"apple\nbanana\ncherry\n"), b"apple\ncherry\n");
+    assert_eq!(pipe(&["--exclude", "^a", "--exclude", "y$"], b"apple\nbanana\ncherry\n"), b"banana\n");
+    assert_eq!(pipe(&["--match", "a", "--exclude", "ch"], b"apple\nbanana\ncherry\n"), b"apple\nbanana\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn highlight_colors_the_matches() {
+    assert_eq!(
+        pipe(&["--highlight", "an", "--color=always"], b"banana\nkiwi\n"),
+        b"b\x1b[1;31man\x1b[0m\x1b[1;31man\x1b[0ma\nkiwi\n"
+    );
+    assert_eq!(pipe(&["--highlight", "an", "--color=never"], b"banana\n"), b"banana\n");
+    // the output is not a terminal, so the matches are left alone by default
+    assert_eq!(pipe(&["--highlight", "an"], b"banana\n"), b"banana\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn replace_rewrites_the_printed_lines() {
+    assert_eq!(pipe(&["--replace", "s/an/AN/"], b"banana\n"), b"bANana\n");
+    assert_eq!(pipe(&["--replace", r"s/a(n)/<\1>/g", "-n"], b"banana\n"), b"     1\tb<n><n>a\n");
+    assert_eq!(pipe(&["--replace", "s/a/A/", "--match", "^a"], b"apple\nbanana\n"), b"Apple\n");
+    let output = minicat().args(["--replace", "x"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("expected s/PATTERN/REPLACEMENT/"));
+}
+
+#[test]
+fn numbers_are_formatted_like_gnu_cat() {
+    assert_eq!(pipe(&["-n"], b"a\n\nb"), b"     1\ta\n     2\t\n     3\tb");
+    assert_eq!(pipe(&["-b"], b"a\n\nb\n"), b"     1\ta\n\n     2\tb\n");
+    let lines = "x\n".repeat(1_000_000);
+    let output = pipe(&["-n"], lines.as_bytes());
+    assert!(output.ends_with(b"999999\tx\n1000000\tx\n"));
+    assert_eq!(pipe(&["-n", "--compact-numbers"], b"a\n\nb\n"), b"1\ta\n2\t\n3\tb\n");
+}
+
+#[test]
+fn number_width_pad_and_separator_format_the_numbers() {
+    let args = ["-n", "--number-width", "3", "--number-pad", "zeros", "--number-separator", ": "];
+    assert_eq!(pipe(&args, b"a\nb\n"), b"001: a\n002: b\n");
+    assert_eq!(pipe(&["-n", "--number-width", "2", "--number-separator", "|"], b"a\n"), b" 1|a\n");
+    let args = ["-n", "--number-width", "4", "--number-pad", "zeros", "--number-separator", "| "];
+    assert_eq!(pipe(&args, b"line\n"), b"0001| line\n");
+}
+
+#[test]
+fn number_start_and_step_count_the_lines() {
+    let args = ["-n", "--number-start", "10", "--number-step", "5"];
+    assert_eq!(pipe(&args, b"a\nb\nc\n"), b"    10\ta\n    15\tb\n    20\tc\n");
+    assert_eq!(pipe(&["-n", "--number-start", "0"], b"a\nb\n"), b"     0\ta\n     1\tb\n");
+    let args = ["-b", "--number-start", "1000", "--number-step", "10"];
+    assert_eq!(pipe(&args, b"a\n\nb\n"), b"  1000\ta\n\n  1010\tb\n");
+}
+
+#[test]
+fn number_start_and_step_stop_at_the_largest_number() {
+    let max = usize::MAX.to_string();
+    let expected = format!("{max}\ta\n{max}\tb\n");
+    assert_eq!(pipe(&["-n", "--number-start", &max], b"a\nb\n"), expected.as_bytes());
+    assert_eq!(pipe(&["-n", "--number-start", &max, "--number-step", &max], b"a\nb\n"), expected.as_bytes());
+    let expected = format!("     1\ta\n{max}\tb\n{max}\tc\n");
+    assert_eq!(pipe(&["-n", "--number-step", &max], b"a\nb\nc\n"), expected.as_bytes());
+}
+
+#[test]
+fn numbering_goes_on_across_files_unless_restarted() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\nb\n").unwrap();
+    fs::write(&second, "c\n").unwrap();
+    assert_eq!(run(&["-n", &first, &second]).stdout, b"     1\ta\n     2\tb\n     3\tc\n");
+    assert_eq!(run(&["-n", "--restart-numbering", &first, &second]).stdout, b"     1\ta\n     2\tb\n     1\tc\n");
+    let output = run(&["-n", "--restart-numbering", "--continuous-numbering", &first, &second]).stdout;
+    assert_eq!(output, b"     1\ta\n     2\tb\n     3\tc\n");
+}
+
+#[test]
+fn number_format_writes_hexadecimal_and_octal_numbers() {
+    let lines = "x\n".repeat(17);
+    assert!(pipe(&["-n", "--number-format", "hex"], lines.as_bytes()).ends_with(b"     f\tx\n    10\tx\n    11\tx\n"));
+    assert!(pipe(&["-n", "--number-format", "oct"], lines.as_bytes()).ends_with(b"    17\tx\n    20\tx\n    21\tx\n"));
+    assert_eq!(pipe(&["-n", "--number-format", "dec", "--compact-numbers"], b"x\n"), b"1\tx\n");
+}
+
+#[test]
+fn with_filename_prefixes_the_lines_like_grep() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\nb\n").unwrap();
+    fs::write(&second, "c\n").unwrap();
+    let expected = format!("{first}:1:a\n{first}:2:b\n{second}:1:c\n");
+    assert_eq!(String::from_utf8(run(&["-H", &first, &second]).stdout).unwrap(), expected);
+    assert_eq!(pipe(&["--with-filename", "-n"], b"x\n"), b"-:1:     1\tx\n");
+}
+
+#[test]
+fn header_prints_a_banner_before_every_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\nb\n").unwrap();
+    fs::write(&second, "c\n").unwrap();
+    let expected = format!("==> {first} <==\na\nb\n\n==> {second} <==\nc\n");
+    assert_eq!(String::from_utf8(run(&["--header", &first, &second]).stdout).unwrap(), expected);
+    let expected = format!("-- {first} --\na\nb\n\n-- {second} --\nc\n");
+    assert_eq!(String::from_utf8(run(&["--header=-- {name} --", &first, &second]).stdout).unwrap(), expected);
+}
+
+#[test]
+fn file_separator_is_printed_between_the_files() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\nb\n").unwrap();
+    fs::write(&second, "c\n").unwrap();
+    assert_eq!(run(&["--file-separator", r"--\n", &first, &second]).stdout, b"a\nb\n--\nc\n");
+    assert_eq!(run(&["--file-separator", r"\f", &first]).stdout, b"a\nb\n");
+    assert_eq!(run(&["--file-separator", r"\x00", &first, &first]).stdout, b"a\nb\n\0a\nb\n");
+}
+
+#[test]
+fn timestamps_prefix_every_line_with_the_time() {
+    let output = String::from_utf8(pipe(&["--timestamps"], b"a\nb\n")).unwrap();
+    let lines: Vec<_> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for (line, text) in lines.iter().zip(["a", "b"]) {
+        let (time, rest) = line.split_once(' ').unwrap();
+        assert_eq!(rest, text);
+        assert!(chrono::NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.3f").is_ok(), "{time}");
+    }
+    let year = chrono::Local::now().format("%Y").to_string();
+    assert_eq!(pipe(&["--timestamps=[%Y]"], b"a\n"), format!("[{year}] a\n").as_bytes());
+}
+
+#[cfg(feature = "syntect")]
+#[test]
+fn syntax_highlights_source_files_on_colored_output() {
+    let directory = tempfile::tempdir().unwrap();
+    let source = path(directory.path(), "main.rs");
+    let script = path(directory.path(), "script");
+    fs::write(&source, "fn main() {}\n").unwrap();
+    fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+    let colored = run(&["--syntax", "--color=always", &source]).stdout;
+    assert!(colored.starts_with(b"\x1b[38;2;"));
+    assert_ne!(colored, run(&["--syntax", "--color=always", "--language", "py", &source]).stdout);
+    assert!(run(&["--syntax", "--color=always", &script]).stdout.contains(&0x1b));
+    assert_eq!(run(&["--syntax", &source]).stdout, b"fn main() {}\n");
+    let output = minicat().args(["--language", "nope", "--color=always", &source]).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "error: unknown language `nope`\n");
+}
+
+#[cfg(feature = "syntect")]
+#[test]
+fn themes_are_listed_and_color_the_line_numbers() {
+    let config = tempfile::tempdir().unwrap();
+    let folder = config.path().join("minicat").join("themes");
+    fs::create_dir_all(&folder).unwrap();
+    let theme = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><plist version="1.0"><dict><key>name</key><string>Mine</string>"#,
+        "<key>settings</key><array><dict><key>settings</key><dict>",
+        "<key>foreground</key><string>#102030</string></dict></dict></array></dict></plist>"
+    );
+    fs::write(folder.join("mine.tmTheme"), theme).unwrap();
+    let listed = minicat().arg("--list-themes").env("XDG_CONFIG_HOME", config.path()).output().unwrap();
+    let listed = String::from_utf8(listed.stdout).unwrap();
+    assert!(listed.lines().any(|name| name == "base16-ocean.dark"));
+    assert!(listed.lines().any(|name| name == "mine"));
+    let input = path(config.path(), "input");
+    fs::write(&input, "a\n").unwrap();
+    let numbered = minicat()
+        .args(["-n", "--color=always", "--theme", "mine", &input])
+        .env("XDG_CONFIG_HOME", config.path())
+        .output()
+        .unwrap();
+    assert_eq!(numbered.stdout, b"\x1b[38;2;16;32;48m     1\t\x1b[0ma\n");
+    let output = minicat().args(["--theme", "nope", "--color=always", &input]).output().unwrap();
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "error: unknown theme `nope`, see --list-themes\n");
+}
+
+#[test]
+fn color_policy_decides_whether_the_decorations_are_colored() {
+    let colored = b"\x1b[32m     1\t\x1b[0ma\n";
+    assert_eq!(pipe(&["-n", "--color=always"], b"a\n"), colored);
+    assert_eq!(pipe(&["-n", "--color"], b"a\n"), colored);
+    assert_eq!(pipe(&["-n", "--color=never"], b"a\n"), b"     1\ta\n");
+    // the output is a pipe, so auto leaves the colors out
+    assert_eq!(pipe(&["-n", "--color=auto"], b"a\n"), b"     1\ta\n");
+    let mut child = minicat()
+        .args(["--header", "--color=always"])
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"a\n").unwrap();
+    assert_eq!(child.wait_with_output().unwrap().stdout, b"\x1b[1m==> - <==\x1b[0m\na\n");
+}
+
+#[test]
+fn strip_ansi_removes_the_escape_sequences() {
+    let input = "\x1b[31mred\x1b[0m \u{e9}\n\x1b]0;ti\ntle\x07end\n".as_bytes();
+    assert_eq!(pipe(&["--strip-ansi"], input), "red \u{e9}\nend\n".as_bytes());
+    assert_eq!(pipe(&["--strip-ansi", "-n"], b"\x1b[1mbold\x1b[0m\n"), b"     1\tbold\n");
+    assert_eq!(pipe(&[], b"\x1b[1mbold\x1b[0m\n"), b"\x1b[1mbold\x1b[0m\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn exclude_patterns_too_big_together_are_a_usage_error() {
+    let output = minicat().args([r"--exclude=\w{200}x", r"--exclude=\w{200}y", "-"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.starts_with("error: Compiled regex exceeds size limit") && errors.ends_with("bytes.\n"), "{errors}");
+}
+
+#[cfg(unix)]
+#[test]
+fn paging_pipes_the_output_through_the_pager() {
+    let paged = |args: &[&str], pager: &str| {
+        let mut child = minicat()
+            .args(args)
+            .env("PAGER", pager)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        let writer = thread::spawn(move || stdin.write_all(&b"line\n".repeat(100_000)));
+        let output = child.wait_with_output().unwrap();
+        let _ = writer.join().unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+    assert_eq!(paged(&["--paging=always", "-n"], "head -n 2"), b"     1\tline\n     2\tline\n");
+    // the output is a pipe, so the pager is left out by default
+    assert_eq!(paged(&[], "head -n 2").len(), 500_000);
+    assert_eq!(paged(&["--paging=always"], "a-pager-that-does-not-exist").len(), 500_000);
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn interactive_cannot_follow_or_page() {
+    for other in ["--follow", "--paging=always"] {
+        let output = minicat().args(["--interactive", other]).output().unwrap();
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
+
+#[test]
+fn render_styles_markdown_only_on_colored_output() {
+    let directory = tempfile::tempdir().unwrap();
+    let readme = path(directory.path(), "README.md");
+    let text = path(directory.path(), "notes.txt");
+    fs::write(&readme, "# Title\n- item\n").unwrap();
+    fs::write(&text, "# Title\n").unwrap();
+    let rendered = b"\x1b[1;4;35mTitle\x1b[0m\n\x1b[33m\xe2\x80\xa2 \x1b[0mitem\n";
+    assert_eq!(run(&["--render", "auto", "--color=always", &readme]).stdout, rendered);
+    assert_eq!(run(&["--render", "auto", "--color=always", &text]).stdout, b"# Title\n");
+    assert_eq!(run(&["--render", "markdown", "--color=always", &text]).stdout, b"\x1b[1;4;35mTitle\x1b[0m\n");
+    assert_eq!(run(&["--render", "markdown", &readme]).stdout, b"# Title\n- item\n");
+}
+
+#[test]
+fn pretty_json_reindents_the_documents() {
+    let expected = b"{\n  \"a\": [\n    1,\n    \"x y\"\n  ],\n  \"b\": {}\n}\n";
+    assert_eq!(pipe(&["--pretty-json"], br#"{"a":[1, "x y"],"b":{}}"#), expected);
+    assert_eq!(pipe(&["--pretty-json"], b"{\"a\":1}\n{\"a\":2}\n"), b"{\n  \"a\": 1\n}\n{\n  \"a\": 2\n}\n");
+    assert_eq!(pipe(&["--pretty-json", "-n"], b"[]"), b"     1\t[]\n");
+    // the output is not a terminal, so the .json files are only re-indented when asked to
+    let directory = tempfile::tempdir().unwrap();
+    let document = path(directory.path(), "document.json");
+    fs::write(&document, r#"{"a":1}"#).unwrap();
+    assert_eq!(run(&[&document]).stdout, br#"{"a":1}"#);
+}
+
+#[test]
+fn hex_dumps_the_files_like_xxd() {
+    let expected = "00000000: 6869 0a                                  hi.\n";
+    assert_eq!(pipe(&["-x"], b"hi\n"), expected.as_bytes());
+    assert_eq!(pipe(&["--hex", "-n", "-E"], b"hi\n"), expected.as_bytes());
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    fs::write(&first, [b'a'; 10]).unwrap();
+    let output = String::from_utf8(run(&["--hex", &first, &first]).stdout).unwrap();
+    assert!(output.starts_with("00000000: 6161") && output.contains("\n00000010: 6161 6161 "), "{output}");
+}
+
+#[test]
+fn dump_prints_octal_decimal_or_hex_groups() {
+    let octal = String::from_utf8(pipe(&["--dump", "octal"], b"ab\n")).unwrap();
+    assert!(octal.starts_with("0000000: 141 142 012 ") && octal.ends_with("  ab.\n"), "{octal}");
+    let decimal = String::from_utf8(pipe(&["--dump", "decimal", "--dump-group", "2"], b"abcd")).unwrap();
+    assert!(decimal.starts_with("0000000: 24930 25444 "), "{decimal}");
+    let hex = String::from_utf8(pipe(&["--dump", "hex", "--dump-group", "4"], b"abcd")).unwrap();
+    assert!(hex.starts_with("00000000: 61626364 "), "{hex}");
+    let output = minicat().args(["--dump", "octal", "--dump-group", "3"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn base64_encodes_and_decodes_the_files() {
+    assert_eq!(pipe(&["--base64-encode"], b"hello"), b"aGVsbG8=\n");
+    assert_eq!(pipe(&["--base64-decode"], b"aGVs\nbG8=\n"), b"hello");
+    let encoded = pipe(&["--base64-encode"], &[0xab; 100]);
+    assert_eq!(pipe(&["--base64-decode"], &encoded), [0xab; 100]);
+    let mut child = minicat()
+        .arg("--base64-decode")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"aGV!").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(errors, "minicat: -: invalid base64 character '!' at byte 3\n");
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn checksum_reports_every_file_and_the_total() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\n").unwrap();
+    fs::write(&second, "b\n").unwrap();
+    let output = run(&["--checksum", "sha256,md5", &first, &second]);
+    assert_eq!(output.stdout, b"a\nb\n");
+    let expected = format!(
+        "SHA256 ({first}) = 87428fc522803d31065e7bce3cf03fe475096631e5e07bbd7a0fde60c4cf25c7\n\
+         MD5 ({first}) = 60b725f10c9c85c70d97880dfe8191b3\n\
+         SHA256 ({second}) = 0263829989b6fd954f72baaf2fc64bc2e2f01d692d4de72986ea808f6e99813f\n\
+         MD5 ({second}) = 3b5d5c3712955042212316173ccf37be\n\
+         SHA256 (total) = 911169ddaaf146aff539f58c26c489af3b892dff0fe283c1c264c65ae5aa59a2\n\
+         MD5 (total) = dd8c6a395b5dd36c56d23275028f526c\n"
+    );
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), expected);
+    let output = minicat().args(["--checksum", "crc", &first]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn verify_checks_the_printed_files() {
+    use sha2::Digest;
+
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(directory.path().join("a"), "hi\n").unwrap();
+    let digest: String = sha2::Sha256::digest(b"hi\n").iter().map(|byte| format!("{:02x}", byte)).collect();
+    fs::write(directory.path().join("sums"), format!("{}  ./a\n", digest)).unwrap();
+    let output = minicat().current_dir(directory.path()).args(["--verify", "sums", "a"]).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::write(directory.path().join("a"), "changed\n").unwrap();
+    let output = minicat().current_dir(directory.path()).args(["--verify", "sums", "a"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("checksum mismatch"));
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn verify_checks_the_files_as_they_are() {
+    use sha2::Digest;
+
+    let directory = tempfile::tempdir().unwrap();
+    let mut sums = String::new();
+    for (name, content) in [("a", &b"a\n"[..]), ("b", b"\xef\xbb\xbfb\n"), ("c.gz", b"\x1f\x8b not gzip")] {
+        fs::write(directory.path().join(name), content).unwrap();
+        let digest: String = sha2::Sha256::digest(content).iter().map(|byte| format!("{:02x}", byte)).collect();
+        sums.push_str(&format!("{digest}  {name}\n"));
+    }
+    fs::write(directory.path().join("sums"), sums).unwrap();
+    for files in [["a", "b", "c.gz"], ["c.gz", "b", "a"]] {
+        let output = minicat().current_dir(directory.path()).args(["--verify", "sums"]).args(files).output().unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+    let output = minicat().current_dir(directory.path()).args(["--verify", "sums", "-n", "a"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn stats_count_the_lines_without_their_decorations() {
+    let plain = run(&["--stats-only", "tests/inputs/threelines.txt"]).stderr;
+    assert_eq!(String::from_utf8(plain.clone()).unwrap(), " 3 13 55 23 tests/inputs/threelines.txt\n");
+    for decorations in [&["-n", "-E"][..], &["--color=always", "-b"], &["-H", "-A", "--header"]] {
+        let output = run(&[decorations, &["--stats-only", "tests/inputs/threelines.txt"]].concat());
+        assert_eq!(output.stderr, plain, "{:?}", decorations);
+    }
+}
+
+#[test]
+fn timings_report_a_row_per_file_and_the_total() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    fs::write(&first, "a\n").unwrap();
+    fs::write(&second, "bcd\n").unwrap();
+    let output = run(&["--timings", &first, &second]);
+    assert_eq!(output.stdout, b"a\nbcd\n");
+    let report = String::from_utf8(output.stderr).unwrap();
+    let rows: Vec<Vec<&str>> = report.lines().map(|row| row.split_whitespace().collect()).collect();
+    assert_eq!(rows[0], ["open", "ms", "read", "ms", "write", "ms", "total", "ms", "bytes", "MB/s", "file"]);
+    let files: Vec<_> = rows[1..].iter().map(|row| (row[4], row[6])).collect();
+    assert_eq!(files, [("2", first.as_str()), ("4", second.as_str()), ("6", "total")]);
+}
+
+#[test]
+fn timings_leave_the_pacing_waits_out_of_the_writes() {
+    let output = run(&["--timings", "--lines-per-sec", "20", "tests/inputs/threelines.txt"]);
+    let report = String::from_utf8(output.stderr).unwrap();
+    let total = report.lines().last().unwrap();
+    let columns: Vec<f64> = total.split_whitespace().take(4).map(|ms| ms.parse().unwrap()).collect();
+    let (write, total) = (columns[2], columns[3]);
+    assert!(total >= 90.0, "{}", report);
+    assert!(write < 50.0, "{}", report);
+}
+
+#[test]
+fn gzip_files_are_decompressed_like_zcat() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(b"zipped\n").unwrap();
+    let compressed = encoder.finish().unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let log = path(directory.path(), "access.log.gz");
+    let renamed = path(directory.path(), "access.log");
+    fs::write(&log, &compressed).unwrap();
+    fs::write(&renamed, &compressed).unwrap();
+    assert_eq!(run(&["-n", &log]).stdout, b"     1\tzipped\n");
+    assert_eq!(run(&["--no-decompress", &log]).stdout, compressed);
+    assert_eq!(run(&[&renamed]).stdout, compressed);
+    assert_eq!(run(&["--decompress", &renamed]).stdout, b"zipped\n");
+    assert_eq!(pipe(&["--decompress"], &compressed), b"zipped\n");
+    assert_eq!(pipe(&["--decompress"], b"plain\n"), b"plain\n");
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_files_are_decompressed_by_extension_or_magic_number() {
+    let compressed = zstd::encode_all(&b"zstd\n"[..], 0).unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let file = path(directory.path(), "data.zst");
+    fs::write(&file, &compressed).unwrap();
+    assert_eq!(run(&[&file]).stdout, b"zstd\n");
+    assert_eq!(pipe(&["--decompress"], &compressed), b"zstd\n");
+}
+
+#[test]
+fn tar_members_are_printed_without_extracting_them() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (name, content) in [("dir/in.txt", "inside\n"), ("other.txt", "other\n")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        builder.append_data(&mut header, name, content.as_bytes()).unwrap();
+    }
+    let directory = tempfile::tempdir().unwrap();
+    let archive = path(directory.path(), "logs.tar.gz");
+    fs::write(&archive, builder.into_inner().unwrap().finish().unwrap()).unwrap();
+    assert_eq!(run(&[&format!("{archive}:dir/in.txt")]).stdout, b"inside\n");
+    assert_eq!(run(&["--archive-member", "other.txt", "-n", &archive]).stdout, b"     1\tother\n");
+    assert_eq!(run(&["--list-members", &archive]).stdout, b"dir/in.txt\nother.txt\n");
+    let output = minicat().arg(format!("{archive}:nope")).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains("nope: no such member in the archive"));
+    let text = path(directory.path(), "text.tar");
+    fs::write(&text, "x".repeat(1024)).unwrap();
+    let output = minicat().arg(format!("{text}:a")).output().unwrap();
+    assert!(String::from_utf8(output.stderr).unwrap().contains(": not a tar archive\n"));
+}
+
+#[test]
+fn zip_members_are_printed_and_listed() {
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    writer.add_directory("dir/", SimpleFileOptions::default()).unwrap();
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    writer.start_file("dir/in.txt", deflated).unwrap();
+    writer.write_all(&b"inside\n".repeat(100)).unwrap();
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    writer.start_file("other.txt", stored).unwrap();
+    writer.write_all(b"other\n").unwrap();
+    let directory = tempfile::tempdir().unwrap();
+    let archive = path(directory.path(), "files.zip");
+    fs::write(&archive, writer.finish().unwrap().into_inner()).unwrap();
+    assert_eq!(run(&[&format!("{archive}:dir/in.txt")]).stdout, b"inside\n".repeat(100));
+    assert_eq!(run(&["--archive-member", "other.txt", &archive]).stdout, b"other\n");
+    assert_eq!(run(&["--list-members", &archive]).stdout, b"dir/\ndir/in.txt\nother.txt\n");
+    let output = minicat().arg(format!("{archive}:dir")).output().unwrap();
+    assert!(String::from_utf8(output.stderr).unwrap().contains("dir: not a regular file in the archive"));
+}
+
+/// Answers the HTTP requests made to a local port with `responses`, one connection each, and returns the URL
+/// of the server.
+#[cfg(feature = "http")]
+fn serve(responses: Vec<&'static str>) -> String {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for response in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                line.clear();
+            }
+            reader.into_inner().write_all(response.as_bytes()).unwrap();
+        }
+    });
+    url
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn urls_are_fetched_like_files() {
+    let url = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Length: 6\r\nConnection: close\r\n\r\nbody\n\n",
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+    assert_eq!(run(&["-n", "-s", &format!("{url}/page")]).stdout, b"     1\tbody\n     2\t\n");
+    let output = minicat().arg(format!("{url}/missing")).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.starts_with(&format!("minicat: {url}/missing: HTTP 404 Not Found\n")), "{errors}");
+}
+
+#[test]
+fn glob_patterns_are_expanded_in_place() {
+    let directory = tempfile::tempdir().unwrap();
+    for (name, content) in [("b.log", "b\n"), ("a.log", "a\n"), (".h.log", "h\n"), ("c.txt", "c\n")] {
+        fs::write(directory.path().join(name), content).unwrap();
+    }
+    let logs = path(directory.path(), "*.log");
+    let text = path(directory.path(), "c.txt");
+    assert_eq!(run(&[&text, &logs, &text]).stdout, b"c\na\nb\nc\n");
+    let output = minicat().args(["--no-glob", &logs]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let output = minicat().args([&path(directory.path(), "*.md"), &text]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"c\n");
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.contains("*.md: No such file or directory"), "{errors}");
+}
+
+#[test]
+fn recursive_prints_the_files_of_the_directories() {
+    let directory = tempfile::tempdir().unwrap();
+    fs::create_dir(directory.path().join("sub")).unwrap();
+    fs::write(directory.path().join("sub/b"), "b\n").unwrap();
+    fs::write(directory.path().join("a"), "a\n").unwrap();
+    let root = directory.path().to_str().unwrap();
+    let output = minicat().arg(root).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let expected = format!("==> {root}/a <==\na\n\n==> {root}/sub/b <==\nb\n");
+    assert_eq!(String::from_utf8(run(&["-r", "--header", root]).stdout).unwrap(), expected);
+}
+
+#[test]
+fn recursive_skips_the_gitignored_and_hidden_files() {
+    let directory = tempfile::tempdir().unwrap();
+    fs::create_dir(directory.path().join(".git")).unwrap();
+    for (name, content) in [(".gitignore", "*.log\n"), ("kept", "kept\n"), ("skipped.log", "log\n"), (".h", "h\n")] {
+        fs::write(directory.path().join(name), content).unwrap();
+    }
+    let root = directory.path().to_str().unwrap();
+    assert_eq!(run(&["-r", root]).stdout, b"kept\n");
+    assert_eq!(run(&["-r", "--no-ignore", root]).stdout, b"kept\nlog\n");
+    assert_eq!(run(&["-r", "--hidden", root]).stdout, b"*.log\nh\nkept\n");
+}
+
+#[test]
+fn binary_files_of_the_trees_are_skipped_printed_or_dumped() {
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(directory.path().join("binary"), b"ab\0c").unwrap();
+    fs::write(directory.path().join("text"), b"t\n").unwrap();
+    let root = directory.path().to_str().unwrap();
+    let output = run(&["-r", root]);
+    assert_eq!(output.stdout, b"t\n");
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), format!("minicat: {root}/binary: binary file skipped\n"));
+    assert_eq!(run(&["-r", "--binary=raw", root]).stdout, b"ab\0ct\n");
+    let dump = String::from_utf8(run(&["-r", "--binary=hex", root]).stdout).unwrap();
+    assert!(dump.starts_with("00000000: 6162 0063 ") && dump.ends_with("  ab.c\nt\n"), "{dump}");
+}
+
+#[test]
+fn files_from_adds_the_listed_files_after_the_arguments() {
+    let directory = tempfile::tempdir().unwrap();
+    let first = path(directory.path(), "first");
+    let second = path(directory.path(), "second");
+    let list = path(directory.path(), "list");
+    fs::write(&first, "1\n").unwrap();
+    fs::write(&second, "2\n").unwrap();
+    fs::write(&list, format!("{second}\r\n\n{first}\n")).unwrap();
+    assert_eq!(run(&["--files-from", &list]).stdout, b"2\n1\n");
+    assert_eq!(run(&["--files-from", &list, &first]).stdout, b"1\n2\n1\n");
+    assert_eq!(pipe(&["--files-from", "-"], format!("{first}\n").as_bytes()), b"1\n");
+}
+
+#[test]
+fn files_from0_reads_the_paths_up_to_nul_bytes() {
+    let directory = tempfile::tempdir().unwrap();
+    let name = path(directory.path(), "two\nlines");
+    fs::write(&name, "x\n").unwrap();
+    let list = path(directory.path(), "list");
+    fs::write(&list, format!("{name}\0{name}\0")).unwrap();
+    assert_eq!(run(&["--files-from0", &list]).stdout, b"x\nx\n");
+    let output = minicat().args(["--files-from", &list, "--files-from0", &list]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket_is_read_like_a_pipe() {
+    use std::os::unix::net::UnixListener;
+
+    let directory = tempfile::tempdir().unwrap();
+    let socket = path(directory.path(), "socket");
+    let listener = UnixListener::bind(&socket).unwrap();
+    thread::spawn(move || {
+        for message in [&b"a\nb\n"[..], b"c\n"] {
+            listener.accept().unwrap().0.write_all(message).unwrap();
+        }
+    });
+    assert_eq!(run(&["-n", "--unix-socket", &socket]).stdout, b"     1\ta\n     2\tb\n");
+    assert_eq!(run(&[&format!("unix://{socket}")]).stdout, b"c\n");
+}
+
+#[test]
+fn listen_prints_what_a_single_connection_sends() {
+    use std::net::{TcpListener, TcpStream};
+
+    let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let child = minicat().args(["-n", "--listen", &address]).stdout(Stdio::piped()).spawn().unwrap();
+    let mut stream = loop {
+        match TcpStream::connect(&address) {
+            Ok(stream) => break stream,
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    stream.write_all(b"a\nb\n").unwrap();
+    drop(stream);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"     1\ta\n     2\tb\n");
+}
+
+#[cfg(all(unix, feature = "serial"))]
+#[test]
+fn serial_device_lines_are_printed_as_they_arrive() {
+    // a pseudo-terminal stands for the device, what is written to its master side being received on the other
+    let (mut master, _device, name) = pseudo_terminal();
+    let child = minicat().args(["-n", "--serial", &name, "--baud", "115200"]).stdout(Stdio::piped()).spawn().unwrap();
+    thread::sleep(Duration::from_millis(300));
+    master.write_all(b"a\nb\n").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    let output = interrupt(child);
+    assert_eq!(output.stdout, b"     1\ta\n     2\tb\n");
+}
+
+#[test]
+fn output_copies_the_transformed_output_into_a_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let copy = path(directory.path(), "copy");
+    let output = run(&["-n", "-o", &copy, "tests/inputs/threelines.txt"]);
+    let expected = fs::read(&copy).unwrap();
+    assert!(expected.starts_with(b"     1\t"), "{}", String::from_utf8_lossy(&expected));
+    assert_eq!(output.stdout, expected);
+    assert_eq!(run(&["-q", "--output", &copy, "tests/inputs/threelines.txt"]).stdout, b"");
+    assert_eq!(fs::read(&copy).unwrap(), fs::read("tests/inputs/threelines.txt").unwrap());
+    let output = minicat().args(["-q", "tests/inputs/threelines.txt"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn output_tmp_then_rename_replaces_the_file_when_following_is_interrupted() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "log");
+    let output = path(directory.path(), "copy");
+    fs::write(&input, "first\n").unwrap();
+    let child = minicat()
+        .args(["--follow", "--output", &output, "--output-tmp-then-rename", &input])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(interrupt(child).status.code(), Some(130));
+    assert_eq!(fs::read_to_string(&output).unwrap(), "first\n");
+    assert_eq!(fs::read_dir(directory.path()).unwrap().count(), 2, "the temporary file is left behind");
+}
+
+#[cfg(all(target_os = "linux", feature = "clipboard"))]
+#[test]
+fn clipboard_failure_is_reported_after_printing() {
+    let output = minicat()
+        .args(["--clipboard", "tests/inputs/oneline.txt"])
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, fs::read("tests/inputs/oneline.txt").unwrap());
+    assert!(String::from_utf8(output.stderr).unwrap().starts_with("minicat: clipboard: "));
+}
+
+#[cfg(all(unix, feature = "serve"))]
+#[test]
+fn serve_answers_every_request_with_the_output_of_the_files() {
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    let directory = tempfile::tempdir().unwrap();
+    let log = path(directory.path(), "build.log");
+    fs::write(&log, "started\n").unwrap();
+    let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+    let child = minicat().args(["-n", "--serve", &address, &log]).stderr(Stdio::piped()).spawn().unwrap();
+    let get = |target: &str| loop {
+        if let Ok(mut stream) = TcpStream::connect(&address) {
+            write!(stream, "GET {target} HTTP/1.1\r\nHost: minicat\r\nConnection: close\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            break response;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let response = get("/");
+    assert!(response.starts_with("HTTP/1.1 200 ") && response.ends_with("\r\n\r\n     1\tstarted\n"), "{response}");
+    assert!(response.contains("Content-Type: text/plain; charset=utf-8\r\n"), "{response}");
+    fs::write(&log, "started\nfinished\n").unwrap();
+    assert!(get("/").ends_with("\r\n\r\n     1\tstarted\n     2\tfinished\n"));
+    assert!(get("/other").starts_with("HTTP/1.1 404 "));
+    let output = interrupt(child);
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert!(errors.starts_with(&format!("minicat: serving on http://{address}/\n")), "{errors}");
+}
+
+#[test]
+fn delay_waits_between_the_lines_or_the_characters() {
+    let start = std::time::Instant::now();
+    assert_eq!(pipe(&["--delay", "50ms"], b"a\nb\nc\n"), b"a\nb\nc\n");
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    let start = std::time::Instant::now();
+    assert_eq!(pipe(&["--delay", "30ms", "--delay-per", "char"], b"ab\n"), b"ab\n");
+    assert!(start.elapsed() >= Duration::from_millis(60));
+    let output = minicat().args(["--delay-per", "char"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn stdin_timeout_gives_up_on_a_silent_input() {
+    let mut child = minicat()
+        .args(["--stdin-timeout", "100ms", "-", "tests/inputs/oneline.txt"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let silent = child.stdin.take();
+    let output = child.wait_with_output().unwrap();
+    drop(silent);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, fs::read("tests/inputs/oneline.txt").unwrap());
+    let errors = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(errors, "minicat: -: no data received after 100ms\n");
+    assert_eq!(pipe(&["--stdin-timeout", "5"], b"typed\n"), b"typed\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn binary_files_are_kept_off_the_terminal_without_force() {
+    use std::io::Read;
+
+    let directory = tempfile::tempdir().unwrap();
+    let binary = path(directory.path(), "binary");
+    fs::write(&binary, b"ab\0c").unwrap();
+    let (mut master, terminal, _) = pseudo_terminal();
+    let on_terminal = |args: &[&str]| {
+        let stdout = Stdio::from(terminal.try_clone().unwrap());
+        minicat().args(["--paging=never"]).args(args).stdout(stdout).output().unwrap()
+    };
+    let output = on_terminal(&[&binary, "tests/inputs/oneline.txt"]);
+    let errors = String::from_utf8(output.stderr).unwrap();
+    let warning = "binary file not printed on the terminal, use --force to print it";
+    assert_eq!(errors, format!("minicat: {binary}: {warning}\n"));
+    let output = on_terminal(&["--force", &binary]);
+    assert!(output.status.success() && output.stderr.is_empty());
+    let mut printed = Vec::new();
+    // the terminal turns the newline of the first file into CRLF, the binary file comes right after it
+    (&mut master).take(fs::metadata("tests/inputs/oneline.txt").unwrap().len() + 5).read_to_end(&mut printed).unwrap();
+    assert!(printed.ends_with(b"\r\nab\0c"), "{printed:?}");
+}
+
+#[test]
+fn expand_tabs_keeps_the_columns_after_the_line_numbers() {
+    assert_eq!(pipe(&["--expand-tabs"], b"a\tb\n"), b"a       b\n");
+    assert_eq!(pipe(&["--expand-tabs", "--tabstop", "4"], b"\tx\nab\ty\n"), b"    x\nab  y\n");
+    assert_eq!(pipe(&["-n", "--expand-tabs"], b"\tx\n"), b"     1          x\n");
+    let output = minicat().args(["--tabstop", "0"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn unexpand_turns_the_leading_spaces_into_tabs() {
+    assert_eq!(pipe(&["--unexpand"], b"          x  y\n\tz\n"), b"\t  x  y\n\tz\n");
+    assert_eq!(pipe(&["--unexpand", "--tabstop", "2"], b"     x\n"), b"\t\t x\n");
+    let output = minicat().args(["--unexpand", "--expand-tabs"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn wrap_ends_the_rows_like_the_lines() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "long");
+    fs::write(&input, "abcdef\n").unwrap();
+    assert_eq!(run(&["--crlf", "--wrap=3", &input]).stdout, b"abc\r\ndef\r\n");
+    fs::write(&input, "abcdef\r\n").unwrap();
+    assert_eq!(run(&["--wrap=3", &input]).stdout, b"abc\r\ndef\r\n");
+    assert_eq!(run(&["--lf", "--wrap=3", &input]).stdout, b"abc\ndef\n");
+}
+
+#[test]
+fn truncate_cuts_the_content_of_the_wide_lines() {
+    let input = "abcdefgh\nab\n\u{65e5}\u{672c}\u{8a9e}\r\n";
+    let expected = "     1\tabcd\u{2026}\n     2\tab\n     3\t\u{65e5}\u{672c}\u{2026}\r\n";
+    assert_eq!(String::from_utf8(pipe(&["-n", "--truncate", "5"], input.as_bytes())).unwrap(), expected);
+}
+
+#[test]
+fn show_whitespace_issues_colors_the_trailing_blanks_and_the_mixed_indentation() {
+    let input = b"ok\ntrail  \n \tmixed\n";
+    let expected = b"ok\ntrail\x1b[41m  \x1b[0m\n\x1b[41m \t\x1b[0mmixed\n";
+    assert_eq!(pipe(&["--show-whitespace-issues", "--color=always"], input), expected);
+    assert_eq!(pipe(&["--show-whitespace-issues"], input), input);
+}
+
+#[test]
+fn check_line_endings_warns_about_mixed_endings_without_changing_the_output() {
+    let directory = tempfile::tempdir().unwrap();
+    let mixed = path(directory.path(), "mixed");
+    fs::write(&mixed, "a\r\nb\nc\r\n").unwrap();
+    let output = run(&["--check-line-endings", "-n", &mixed, "tests/inputs/threelines.txt"]);
+    assert_eq!(output.stdout, run(&["-n", &mixed, "tests/inputs/threelines.txt"]).stdout);
+    let warning = "inconsistent line endings: 1 LF (first on line 2), 2 CRLF (first on line 1)";
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), format!("minicat: {mixed}: {warning}\n"));
+}
+
+#[test]
+fn crlf_and_lf_convert_the_line_endings() {
+    assert_eq!(pipe(&["--crlf"], b"a\r\nb\nc\rd\nend"), b"a\r\nb\r\nc\rd\r\nend");
+    assert_eq!(pipe(&["--lf", "-n"], b"a\r\nb\n"), b"     1\ta\n     2\tb\n");
+    let output = minicat().args(["--lf", "--crlf"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn from_encoding_transcodes_the_inputs_before_the_lines_are_handled() {
+    let utf16 = b"\xff\xfea\x00\n\x00\xe9\x00\n\x00";
+    let expected = "     1\ta\n     2\t\u{e9}\n";
+    assert_eq!(pipe(&["--from-encoding", "utf-16le", "-n", "--strict"], utf16), expected.as_bytes());
+    assert_eq!(pipe(&["--from-encoding=latin1"], b"caf\xe9\n"), "caf\u{e9}\n".as_bytes());
+    let output = minicat().args(["--from-encoding", "klingon"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn to_encoding_caps_the_transcoded_output() {
+    let full = run(&["--to-encoding", "utf-16le", "tests/inputs/threelines.txt"]).stdout;
+    let text = fs::read_to_string("tests/inputs/threelines.txt").unwrap();
+    let mut expected = vec![0xff, 0xfe];
+    expected.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    assert_eq!(full, expected);
+    for bytes in [1, 7, 20] {
+        let cap = bytes.to_string();
+        let output = run(&["--to-encoding", "utf-16le", "--max-bytes", &cap, "tests/inputs/threelines.txt"]);
+        assert_eq!(output.stdout, &expected[..bytes]);
+    }
+}
+
+#[cfg(feature = "checksum")]
+#[test]
+fn to_encoding_hashes_the_transcoded_output() {
+    use sha2::Digest;
+
+    let output = run(&["--to-encoding", "utf-16le", "--checksum", "sha256", "tests/inputs/threelines.txt"]);
+    let digest: String = sha2::Sha256::digest(&output.stdout).iter().map(|byte| format!("{:02x}", byte)).collect();
+    assert!(String::from_utf8(output.stderr).unwrap().contains(&digest));
+}
+
+#[test]
+fn bom_of_a_single_file_is_copied() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "marked");
+    fs::write(&input, b"\xef\xbb\xbfline\n").unwrap();
+    assert_eq!(run(&[&input]).stdout, b"\xef\xbb\xbfline\n");
+}
+
+#[test]
+fn bom_of_the_files_after_the_first_is_stripped() {
+    let directory = tempfile::tempdir().unwrap();
+    let input = path(directory.path(), "marked");
+    fs::write(&input, b"\xef\xbb\xbfline\n").unwrap();
+    assert_eq!(run(&[&input, &input]).stdout, b"\xef\xbb\xbfline\nline\n");
+    assert_eq!(run(&["-n", &input, &input]).stdout, b"     1\tline\n     2\tline\n");
+    assert_eq!(run(&["--keep-bom", &input, &input]).stdout, b"\xef\xbb\xbfline\n\xef\xbb\xbfline\n");
+}
+
+#[test]
+fn bom_of_utf16_files_is_stripped_and_of_binary_files_kept() {
+    let directory = tempfile::tempdir().unwrap();
+    let little = path(directory.path(), "little");
+    let big = path(directory.path(), "big");
+    let binary = path(directory.path(), "binary");
+    fs::write(&little, b"\xff\xfel\x00\n\x00").unwrap();
+    fs::write(&big, b"\xfe\xff\x00l\x00\n").unwrap();
+    fs::write(&binary, b"\xef\xbb\xbf\x00\x01\x02").unwrap();
+    assert_eq!(run(&[&little, &little]).stdout, b"\xff\xfel\x00\n\x00l\x00\n\x00");
+    assert_eq!(run(&[&big, &big]).stdout, b"\xfe\xff\x00l\x00\n\x00l\x00\n");
+    assert_eq!(run(&["--keep-bom", &little, &little]).stdout, [&fs::read(&little).unwrap()[..]; 2].concat());
+    assert_eq!(run(&[&binary, &binary]).stdout, [&fs::read(&binary).unwrap()[..]; 2].concat());
+}